@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE users
+    ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'author'
+    CHECK (role IN ('reader', 'author', 'editor', 'admin'));
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_users_created_at;
+DROP INDEX IF EXISTS idx_posts_created_at;
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX idx_posts_created_at ON posts(created_at, id);
+CREATE INDEX idx_users_created_at ON users(created_at, id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS post_slugs;
+ALTER TABLE posts DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+ALTER TABLE posts ADD COLUMN slug VARCHAR(120);
+
+-- Existing rows get an ASCII-only approximation of the slugs the application
+-- generates. Repeated titles, and titles with nothing usable, fall back to a
+-- piece of the post id.
+WITH derived AS (
+    SELECT id,
+           created_at,
+           trim(BOTH '-' FROM left(regexp_replace(lower(title), '[^a-z0-9]+', '-', 'g'), 100)) AS base
+    FROM posts
+), ranked AS (
+    SELECT id,
+           base,
+           row_number() OVER (PARTITION BY base ORDER BY created_at, id) AS n
+    FROM derived
+)
+UPDATE posts
+SET slug = CASE
+    WHEN ranked.base = '' THEN 'post-' || left(ranked.id::text, 8)
+    WHEN ranked.n = 1 THEN ranked.base
+    ELSE ranked.base || '-' || left(ranked.id::text, 8)
+END
+FROM ranked
+WHERE posts.id = ranked.id;
+
+ALTER TABLE posts ALTER COLUMN slug SET NOT NULL;
+ALTER TABLE posts ADD CONSTRAINT posts_slug_key UNIQUE (slug);
+
+-- Slugs a post has had before, so links to them can be redirected.
+CREATE TABLE post_slugs (
+    slug VARCHAR(120) PRIMARY KEY,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_post_slugs_post ON post_slugs(post_id);
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_posts_publish_at;
+DROP INDEX IF EXISTS idx_posts_status_published_at;
+
+ALTER TABLE posts ADD COLUMN published BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE posts SET published = (status = 'published');
+CREATE INDEX idx_posts_published ON posts(published);
+
+ALTER TABLE posts
+    DROP COLUMN published_at,
+    DROP COLUMN publish_at,
+    DROP COLUMN status;
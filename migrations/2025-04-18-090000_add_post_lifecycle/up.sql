@@ -0,0 +1,15 @@
+-- Your SQL goes here
+ALTER TABLE posts
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'draft'
+    CHECK (status IN ('draft', 'in_review', 'scheduled', 'published', 'archived')),
+    ADD COLUMN publish_at TIMESTAMP,
+    ADD COLUMN published_at TIMESTAMP;
+
+UPDATE posts SET status = 'published', published_at = updated_at WHERE published;
+
+ALTER TABLE posts
+    DROP COLUMN published,
+    ADD CONSTRAINT posts_publish_at_check CHECK ((status = 'scheduled') = (publish_at IS NOT NULL));
+
+CREATE INDEX idx_posts_status_published_at ON posts(status, published_at, id);
+CREATE INDEX idx_posts_publish_at ON posts(publish_at) WHERE status = 'scheduled';
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE post_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    editor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    title VARCHAR(100) NOT NULL,
+    content TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT post_revisions_revision_key UNIQUE (post_id, revision)
+);
+
+-- Existing posts start their history at their current state.
+INSERT INTO post_revisions (post_id, revision, editor_id, title, content, status, created_at)
+SELECT id, 1, author_id, title, content, status, updated_at FROM posts;
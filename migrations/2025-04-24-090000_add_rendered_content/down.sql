@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE comments DROP COLUMN IF EXISTS content_html;
+ALTER TABLE posts DROP COLUMN IF EXISTS content_html;
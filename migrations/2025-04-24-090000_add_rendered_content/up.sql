@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Sanitized HTML rendered from `content` whenever it is saved. Rows written
+-- before rendering existed have none and are rendered when read.
+ALTER TABLE posts ADD COLUMN content_html TEXT;
+ALTER TABLE comments ADD COLUMN content_html TEXT;
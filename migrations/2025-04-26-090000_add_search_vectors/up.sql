@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- Kept up to date by Postgres on every write. Titles weigh more than bodies
+-- when results are ranked.
+ALTER TABLE posts ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') ||
+    setweight(to_tsvector('english', content), 'B')
+) STORED;
+CREATE INDEX idx_posts_search_vector ON posts USING GIN (search_vector);
+
+ALTER TABLE comments ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    to_tsvector('english', content)
+) STORED;
+CREATE INDEX idx_comments_search_vector ON comments USING GIN (search_vector);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS comments_reply_count ON comments;
+DROP FUNCTION IF EXISTS comments_count_replies();
+DROP INDEX IF EXISTS idx_comments_parent;
+ALTER TABLE comments
+    DROP COLUMN IF EXISTS deleted_at,
+    DROP COLUMN IF EXISTS reply_count,
+    DROP COLUMN IF EXISTS parent_id;
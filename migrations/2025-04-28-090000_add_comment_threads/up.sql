@@ -0,0 +1,34 @@
+-- Your SQL goes here
+-- Comments reply to at most one other comment on the same post. Deleting a
+-- comment with replies through the application leaves a tombstone
+-- (`deleted_at` set, content cleared) so the thread keeps its shape. Rows
+-- removed by a cascade from their author take nothing else with them: their
+-- replies move up to the top level.
+ALTER TABLE comments
+    ADD COLUMN parent_id UUID REFERENCES comments(id) ON DELETE SET NULL,
+    ADD COLUMN reply_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN deleted_at TIMESTAMP,
+    ADD CONSTRAINT comments_parent_id_check CHECK (parent_id <> id);
+
+CREATE INDEX idx_comments_parent ON comments(parent_id);
+
+-- Keeps `reply_count` at the number of direct replies, however they come
+-- and go.
+CREATE FUNCTION comments_count_replies() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.parent_id IS NOT DISTINCT FROM NEW.parent_id THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count - 1 WHERE id = OLD.parent_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count + 1 WHERE id = NEW.parent_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER comments_reply_count
+    AFTER INSERT OR DELETE OR UPDATE OF parent_id ON comments
+    FOR EACH ROW EXECUTE FUNCTION comments_count_replies();
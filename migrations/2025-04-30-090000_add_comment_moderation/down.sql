@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS comment_moderation;
+DROP INDEX IF EXISTS idx_comments_status_created_at;
+ALTER TABLE comments DROP COLUMN IF EXISTS status;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- New comments wait for a moderator unless the moderation settings let them
+-- through, and only approved ones are public. Comments written before there
+-- was a queue were already public, so they start out approved.
+ALTER TABLE comments
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'approved'
+    CHECK (status IN ('pending', 'approved', 'rejected', 'spam'));
+
+ALTER TABLE comments ALTER COLUMN status SET DEFAULT 'pending';
+
+CREATE INDEX idx_comments_status_created_at ON comments(status, created_at, id);
+
+-- A post's overrides of the global moderation settings; NULL keeps the
+-- global value.
+CREATE TABLE comment_moderation (
+    post_id UUID PRIMARY KEY REFERENCES posts(id) ON DELETE CASCADE,
+    require_approval BOOLEAN,
+    auto_approve_trusted BOOLEAN,
+    hold_first_time BOOLEAN
+);
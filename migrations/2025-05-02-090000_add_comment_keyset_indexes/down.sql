@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+CREATE INDEX IF NOT EXISTS idx_comments_post ON comments(post_id);
+DROP INDEX IF EXISTS idx_comments_post_status_created_at;
+DROP INDEX IF EXISTS idx_comments_post_created_at;
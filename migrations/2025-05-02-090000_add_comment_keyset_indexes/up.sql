@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Cursor pages of a post's comments seek on `(created_at, id)`; these let
+-- that seek start inside the post's rows instead of filtering them. Public
+-- listings also filter on status. The old single-column index is a prefix
+-- of the first one.
+CREATE INDEX idx_comments_post_created_at ON comments(post_id, created_at, id);
+CREATE INDEX idx_comments_post_status_created_at ON comments(post_id, status, created_at, id);
+DROP INDEX IF EXISTS idx_comments_post;
@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS comments_reply_count ON comments;
+
+CREATE OR REPLACE FUNCTION comments_count_replies() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.parent_id IS NOT DISTINCT FROM NEW.parent_id THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count - 1 WHERE id = OLD.parent_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count + 1 WHERE id = NEW.parent_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+UPDATE comments
+SET reply_count = (SELECT count(*) FROM comments reply WHERE reply.parent_id = comments.id);
+
+CREATE TRIGGER comments_reply_count
+    AFTER INSERT OR DELETE OR UPDATE OF parent_id ON comments
+    FOR EACH ROW EXECUTE FUNCTION comments_count_replies();
@@ -0,0 +1,56 @@
+-- Your SQL goes here
+-- `reply_count` is public, so it only counts the replies readers can see:
+-- approved ones that are not tombstones, or tombstones that still have some
+-- of those themselves. A tombstone whose count drops to zero is shown to no
+-- one, which can in turn change what its own parent counts.
+DROP TRIGGER IF EXISTS comments_reply_count ON comments;
+
+DO $$
+BEGIN
+    LOOP
+        UPDATE comments
+        SET reply_count = counted.replies
+        FROM (
+            SELECT parent.id, count(reply.id) FILTER (
+                WHERE reply.status = 'approved'
+                    AND (reply.deleted_at IS NULL OR reply.reply_count > 0)
+            ) AS replies
+            FROM comments parent
+            LEFT JOIN comments reply ON reply.parent_id = parent.id
+            GROUP BY parent.id
+        ) counted
+        WHERE comments.id = counted.id AND comments.reply_count <> counted.replies;
+        EXIT WHEN NOT FOUND;
+    END LOOP;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION comments_count_replies() RETURNS trigger AS $$
+DECLARE
+    was_public BOOLEAN := TG_OP IN ('UPDATE', 'DELETE')
+        AND OLD.status = 'approved'
+        AND (OLD.deleted_at IS NULL OR OLD.reply_count > 0);
+    is_public BOOLEAN := TG_OP IN ('INSERT', 'UPDATE')
+        AND NEW.status = 'approved'
+        AND (NEW.deleted_at IS NULL OR NEW.reply_count > 0);
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.parent_id IS NOT DISTINCT FROM NEW.parent_id
+        AND was_public = is_public THEN
+        RETURN NULL;
+    END IF;
+    IF was_public AND OLD.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count - 1 WHERE id = OLD.parent_id;
+    END IF;
+    IF is_public AND NEW.parent_id IS NOT NULL THEN
+        UPDATE comments SET reply_count = reply_count + 1 WHERE id = NEW.parent_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Changing a comment's own count can change whether it is public, so the
+-- trigger also follows `reply_count` up the thread.
+CREATE TRIGGER comments_reply_count
+    AFTER INSERT OR DELETE OR UPDATE OF parent_id, status, deleted_at, reply_count ON comments
+    FOR EACH ROW EXECUTE FUNCTION comments_count_replies();
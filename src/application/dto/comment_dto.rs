@@ -2,27 +2,70 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::domain::models::comment::{CreateComment, UpdateComment};
+use crate::domain::models::{
+    comment::{
+        CommentFilter, CommentNode, CommentQuery, CommentSortField, CommentStatus,
+        CommentStreamQuery, CreateComment, ModerateComments, ModerationQueueQuery,
+        ModerationSettings, PostModeration, UpdateComment, UpdateModeration,
+    },
+    pagination::{CursorPosition, PageRequest, SortDirection},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CommentResponse {
     pub id: Uuid,
     pub content: String,
+    /// `content` rendered from Markdown and sanitized.
+    pub content_html: String,
     pub post_id: Uuid,
-    pub author_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    /// `null` on tombstones: a deleted comment no longer says who wrote it.
+    pub author_id: Option<Uuid>,
+    /// Direct replies that are approved, deleted ones that still have
+    /// replies of their own included.
+    pub reply_count: i32,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Set on tombstones: deleted comments kept because they have replies.
+    /// Their content is empty.
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    /// Only `approved` comments are public.
+    pub status: CommentStatus,
 }
 
 impl From<crate::domain::models::comment::Comment> for CommentResponse {
     fn from(comment: crate::domain::models::comment::Comment) -> Self {
         Self {
+            content_html: comment.html().into_owned(),
+            author_id: (!comment.is_deleted()).then_some(comment.author_id),
             id: comment.id,
             content: comment.content,
             post_id: comment.post_id,
-            author_id: comment.author_id,
+            parent_id: comment.parent_id,
+            reply_count: comment.reply_count,
             created_at: comment.created_at,
             updated_at: comment.updated_at,
+            deleted_at: comment.deleted_at,
+            status: comment.status,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentThreadResponse {
+    #[serde(flatten)]
+    pub comment: CommentResponse,
+    /// 0 for top-level comments.
+    pub depth: i32,
+    pub replies: Vec<CommentThreadResponse>,
+}
+
+impl From<CommentNode> for CommentThreadResponse {
+    fn from(node: CommentNode) -> Self {
+        Self {
+            comment: CommentResponse::from(node.comment),
+            depth: node.depth,
+            replies: node.replies.into_iter().map(Self::from).collect(),
         }
     }
 }
@@ -32,6 +75,8 @@ pub struct CreateCommentRequest {
     #[validate(length(min = 1))]
     pub content: String,
     pub post_id: Uuid,
+    /// The comment this replies to.
+    pub parent_id: Option<Uuid>,
 }
 
 impl From<CreateCommentRequest> for CreateComment {
@@ -39,7 +84,7 @@ impl From<CreateCommentRequest> for CreateComment {
         Self {
             content: request.content,
             post_id: request.post_id,
-            author_id: Uuid::new_v4(),
+            parent_id: request.parent_id,
         }
     }
 }
@@ -57,3 +102,161 @@ impl From<UpdateCommentRequest> for UpdateComment {
         }
     }
 }
+
+/// Pages through a post's threads by their top-level comments.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListThreadsParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+impl From<ListThreadsParams> for PageRequest {
+    fn from(params: ListThreadsParams) -> Self {
+        PageRequest::new(params.page, params.limit)
+    }
+}
+
+/// Query parameters for a post's comments. Supplying `cursor` switches from
+/// page numbers to keyset pagination; an empty `cursor` starts from the top.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListCommentsParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    pub sort: Option<CommentSortField>,
+    pub direction: Option<SortDirection>,
+    pub author_id: Option<Uuid>,
+    pub created_after: Option<chrono::NaiveDateTime>,
+    pub created_before: Option<chrono::NaiveDateTime>,
+}
+
+impl ListCommentsParams {
+    fn filter(&self) -> CommentFilter {
+        CommentFilter {
+            author_id: self.author_id,
+            status: None,
+            created_after: self.created_after,
+            created_before: self.created_before,
+        }
+    }
+
+    pub fn into_stream_query(self, after: Option<CursorPosition>) -> CommentStreamQuery {
+        CommentStreamQuery {
+            filter: self.filter(),
+            direction: self.direction.unwrap_or_default(),
+            after,
+            limit: PageRequest::new(None, self.limit).limit,
+        }
+    }
+}
+
+impl From<ListCommentsParams> for CommentQuery {
+    fn from(params: ListCommentsParams) -> Self {
+        Self {
+            filter: params.filter(),
+            sort: params.sort.unwrap_or_default(),
+            direction: params.direction.unwrap_or_default(),
+            page: PageRequest::new(params.page, params.limit),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ModerationQueueParams {
+    /// `pending` unless given.
+    pub status: Option<CommentStatus>,
+    pub post_id: Option<Uuid>,
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+impl From<ModerationQueueParams> for ModerationQueueQuery {
+    fn from(params: ModerationQueueParams) -> Self {
+        Self {
+            status: params.status.unwrap_or(CommentStatus::Pending),
+            post_id: params.post_id,
+            page: PageRequest::new(params.page, params.limit),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ModerateCommentsRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub ids: Vec<Uuid>,
+    pub status: CommentStatus,
+}
+
+impl From<ModerateCommentsRequest> for ModerateComments {
+    fn from(request: ModerateCommentsRequest) -> Self {
+        Self {
+            ids: request.ids,
+            status: request.status,
+        }
+    }
+}
+
+/// A post's moderation overrides. Leaving a setting out, or setting it to
+/// `null`, falls back to the global one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateModerationRequest {
+    pub require_approval: Option<bool>,
+    pub auto_approve_trusted: Option<bool>,
+    pub hold_first_time: Option<bool>,
+}
+
+impl From<UpdateModerationRequest> for UpdateModeration {
+    fn from(request: UpdateModerationRequest) -> Self {
+        Self {
+            require_approval: request.require_approval,
+            auto_approve_trusted: request.auto_approve_trusted,
+            hold_first_time: request.hold_first_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModerationSettingsResponse {
+    pub require_approval: bool,
+    pub auto_approve_trusted: bool,
+    pub hold_first_time: bool,
+}
+
+impl From<ModerationSettings> for ModerationSettingsResponse {
+    fn from(settings: ModerationSettings) -> Self {
+        Self {
+            require_approval: settings.require_approval,
+            auto_approve_trusted: settings.auto_approve_trusted,
+            hold_first_time: settings.hold_first_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostModerationResponse {
+    pub post_id: Uuid,
+    /// The post's own settings; `null` where it uses the global one.
+    pub require_approval: Option<bool>,
+    pub auto_approve_trusted: Option<bool>,
+    pub hold_first_time: Option<bool>,
+    /// What applies to new comments on the post.
+    pub effective: ModerationSettingsResponse,
+}
+
+impl From<PostModeration> for PostModerationResponse {
+    fn from(moderation: PostModeration) -> Self {
+        Self {
+            post_id: moderation.overrides.post_id,
+            require_approval: moderation.overrides.require_approval,
+            auto_approve_trusted: moderation.overrides.auto_approve_trusted,
+            hold_first_time: moderation.overrides.hold_first_time,
+            effective: moderation.effective.into(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use crate::domain::models::health::{DatabaseCheck, PoolStats, ReadinessReport};
+
+#[derive(Debug, Serialize)]
+pub struct LivenessResponse {
+    pub status: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub checks: ReadinessChecks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    pub database: DatabaseCheckResponse,
+    pub migrations: MigrationCheckResponse,
+    pub pool: PoolStatsResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseCheckResponse {
+    pub status: &'static str,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationCheckResponse {
+    pub status: &'static str,
+    pub pending: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolStatsResponse {
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub max_size: u32,
+    pub in_flight: usize,
+    pub max_concurrency: usize,
+}
+
+impl From<ReadinessReport> for ReadinessResponse {
+    fn from(report: ReadinessReport) -> Self {
+        let DatabaseCheck {
+            reachable,
+            latency_ms,
+            pending_migrations,
+            pool,
+        } = report.database;
+
+        let migrations = match pending_migrations {
+            Some(pending) if pending.is_empty() => MigrationCheckResponse {
+                status: "up_to_date",
+                pending,
+            },
+            Some(pending) => MigrationCheckResponse {
+                status: "pending",
+                pending,
+            },
+            None => MigrationCheckResponse {
+                status: "unknown",
+                pending: Vec::new(),
+            },
+        };
+
+        Self {
+            status: report.status.as_str(),
+            checks: ReadinessChecks {
+                database: DatabaseCheckResponse {
+                    status: match (reachable, pool.saturated()) {
+                        (false, _) => "down",
+                        (true, true) => "saturated",
+                        (true, false) => "up",
+                    },
+                    latency_ms,
+                },
+                migrations,
+                pool: pool.into(),
+            },
+        }
+    }
+}
+
+impl From<PoolStats> for PoolStatsResponse {
+    fn from(stats: PoolStats) -> Self {
+        Self {
+            connections: stats.connections,
+            idle_connections: stats.idle_connections,
+            max_size: stats.max_size,
+            in_flight: stats.in_flight,
+            max_concurrency: stats.max_concurrency,
+        }
+    }
+}
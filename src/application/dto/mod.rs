@@ -1,3 +1,20 @@
 pub mod comment_dto;
+pub mod health_dto;
+pub mod pagination_dto;
 pub mod post_dto;
+pub mod search_dto;
+pub mod taxonomy_dto;
 pub mod user_dto;
+
+use serde::{Deserialize, Deserializer};
+
+/// For `Option<Option<T>>` fields with `#[serde(default)]`: an explicit
+/// `null` becomes `Some(None)`, which serde would otherwise fold into the
+/// `None` of a missing field.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
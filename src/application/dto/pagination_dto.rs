@@ -0,0 +1,115 @@
+use axum::http::Uri;
+use serde::Serialize;
+
+use crate::domain::models::pagination::{CursorPage, Page};
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub meta: PageMeta,
+    pub links: PageLinks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageMeta {
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageLinks {
+    #[serde(rename = "self")]
+    pub current: String,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+impl<T> PaginatedResponse<T> {
+    /// Builds the envelope for `page`, deriving navigation links from the
+    /// request URI so every other query parameter is preserved.
+    pub fn new<U>(page: Page<U>, uri: &Uri) -> Self
+    where
+        T: From<U>,
+    {
+        let links = PageLinks {
+            current: link_with(uri, "page", &page.page.to_string()),
+            next: page
+                .has_next()
+                .then(|| link_with(uri, "page", &(page.page + 1).to_string())),
+            prev: page
+                .has_prev()
+                .then(|| link_with(uri, "page", &(page.page - 1).to_string())),
+        };
+        let meta = PageMeta {
+            total: page.total,
+            page: page.page,
+            limit: page.limit,
+            total_pages: page.total_pages(),
+        };
+
+        Self {
+            data: page.items.into_iter().map(T::from).collect(),
+            meta,
+            links,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CursorPaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub meta: CursorMeta,
+    pub links: CursorLinks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CursorMeta {
+    pub limit: i64,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CursorLinks {
+    #[serde(rename = "self")]
+    pub current: String,
+    pub next: Option<String>,
+}
+
+impl<T> CursorPaginatedResponse<T> {
+    /// `next_cursor` is the already-encoded cursor for the following page.
+    pub fn new<U>(page: CursorPage<U>, next_cursor: Option<String>, uri: &Uri) -> Self
+    where
+        T: From<U>,
+    {
+        let links = CursorLinks {
+            current: uri.to_string(),
+            next: next_cursor
+                .as_deref()
+                .map(|cursor| link_with(uri, "cursor", cursor)),
+        };
+
+        Self {
+            data: page.items.into_iter().map(T::from).collect(),
+            meta: CursorMeta {
+                limit: page.limit,
+                next_cursor,
+            },
+            links,
+        }
+    }
+}
+
+/// Rebuilds `uri` with `key` set to `value`, keeping every other parameter.
+fn link_with(uri: &Uri, key: &str, value: &str) -> String {
+    let mut params: Vec<(String, String)> = uri
+        .query()
+        .and_then(|query| serde_urlencoded::from_str(query).ok())
+        .unwrap_or_default();
+    params.retain(|(name, _)| name != key);
+    params.push((key.to_string(), value.to_string()));
+
+    let query = serde_urlencoded::to_string(&params).unwrap_or_default();
+    format!("{}?{}", uri.path(), query)
+}
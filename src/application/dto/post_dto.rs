@@ -2,39 +2,82 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::domain::models::post::{CreatePost, UpdatePost};
+use super::nullable;
+use crate::domain::models::{
+    pagination::{PageRequest, SortDirection},
+    post::{
+        ChangePostStatus, CreatePost, PostFilter, PostQuery, PostSortField, PostStatus, TaggedPost,
+        UpdatePost,
+    },
+    post_revision::PostRevision,
+    tag::Tag,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostResponse {
     pub id: Uuid,
+    pub slug: String,
     pub title: String,
     pub content: String,
+    /// `content` rendered from Markdown and sanitized.
+    pub content_html: String,
     pub author_id: Uuid,
-    pub published: bool,
+    pub status: PostStatus,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub published_at: Option<chrono::NaiveDateTime>,
+    pub category_id: Option<Uuid>,
+    pub tags: Vec<PostTagResponse>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
 
-impl From<crate::domain::models::post::Post> for PostResponse {
-    fn from(post: crate::domain::models::post::Post) -> Self {
+impl From<TaggedPost> for PostResponse {
+    fn from(TaggedPost { post, tags }: TaggedPost) -> Self {
         Self {
+            content_html: post.html().into_owned(),
             id: post.id,
+            slug: post.slug,
             title: post.title,
             content: post.content,
             author_id: post.author_id,
-            published: post.published,
+            status: post.status,
+            publish_at: post.publish_at,
+            published_at: post.published_at,
+            category_id: post.category_id,
+            tags: tags.into_iter().map(PostTagResponse::from).collect(),
             created_at: post.created_at,
             updated_at: post.updated_at,
         }
     }
 }
 
+/// A tag as it appears on a post.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostTagResponse {
+    pub slug: String,
+    pub name: String,
+}
+
+impl From<Tag> for PostTagResponse {
+    fn from(tag: Tag) -> Self {
+        Self {
+            slug: tag.slug,
+            name: tag.name,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 100))]
     pub title: String,
     #[validate(length(min = 1))]
     pub content: String,
+    pub category_id: Option<Uuid>,
+    /// Slugs of existing tags.
+    #[serde(default)]
+    #[validate(length(max = 10))]
+    pub tags: Vec<String>,
 }
 
 impl From<CreatePostRequest> for CreatePost {
@@ -42,7 +85,8 @@ impl From<CreatePostRequest> for CreatePost {
         Self {
             title: request.title,
             content: request.content,
-            author_id: Uuid::new_v4(), // In real app, this would come from auth
+            category_id: request.category_id,
+            tags: request.tags,
         }
     }
 }
@@ -53,7 +97,12 @@ pub struct UpdatePostRequest {
     pub title: Option<String>,
     #[validate(length(min = 1))]
     pub content: Option<String>,
-    pub published: Option<bool>,
+    /// `null` takes the post out of its category.
+    #[serde(default, deserialize_with = "nullable")]
+    pub category_id: Option<Option<Uuid>>,
+    /// Replaces the post's tags; `[]` removes them all.
+    #[validate(length(max = 10))]
+    pub tags: Option<Vec<String>>,
 }
 
 impl From<UpdatePostRequest> for UpdatePost {
@@ -61,7 +110,99 @@ impl From<UpdatePostRequest> for UpdatePost {
         Self {
             title: request.title,
             content: request.content,
-            published: request.published,
+            category_id: request.category_id,
+            tags: request.tags,
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePostStatusRequest {
+    pub status: PostStatus,
+    pub publish_at: Option<chrono::NaiveDateTime>,
+}
+
+impl From<ChangePostStatusRequest> for ChangePostStatus {
+    fn from(request: ChangePostStatusRequest) -> Self {
+        Self {
+            status: request.status,
+            publish_at: request.publish_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListPostsParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    pub sort: Option<PostSortField>,
+    pub direction: Option<SortDirection>,
+    /// Defaults to `published`; other statuses need authentication.
+    pub status: Option<PostStatus>,
+    pub author_id: Option<Uuid>,
+    pub created_after: Option<chrono::NaiveDateTime>,
+    pub created_before: Option<chrono::NaiveDateTime>,
+}
+
+impl From<ListPostsParams> for PostQuery {
+    fn from(params: ListPostsParams) -> Self {
+        Self {
+            filter: PostFilter {
+                status: params.status,
+                author_id: params.author_id,
+                created_after: params.created_after,
+                created_before: params.created_before,
+                ..Default::default()
+            },
+            sort: params.sort.unwrap_or_default(),
+            direction: params.direction.unwrap_or_default(),
+            page: PageRequest::new(params.page, params.limit),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostRevisionResponse {
+    pub revision: i32,
+    pub editor_id: Option<Uuid>,
+    pub title: String,
+    pub content: String,
+    pub status: PostStatus,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<PostRevision> for PostRevisionResponse {
+    fn from(revision: PostRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            editor_id: revision.editor_id,
+            title: revision.title,
+            content: revision.content,
+            status: revision.status,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListRevisionsParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+impl From<ListRevisionsParams> for PageRequest {
+    fn from(params: ListRevisionsParams) -> Self {
+        PageRequest::new(params.page, params.limit)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RevisionDiffParams {
+    /// The revision to compare against; defaults to the one before.
+    #[validate(range(min = 1))]
+    pub from: Option<i32>,
+}
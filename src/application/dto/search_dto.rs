@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::{
+    pagination::PageRequest,
+    search::{Search, SearchHit, SearchScope},
+};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SearchParams {
+    /// Words to find; `"phrases"`, `prefix*`, `-excluded` and `or` work as
+    /// they do in web search.
+    #[validate(length(min = 1, max = 200))]
+    pub q: String,
+    /// `posts` unless given.
+    pub scope: Option<SearchScope>,
+    pub author_id: Option<Uuid>,
+    /// A tag slug.
+    pub tag: Option<String>,
+    pub created_after: Option<chrono::NaiveDateTime>,
+    pub created_before: Option<chrono::NaiveDateTime>,
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+impl From<SearchParams> for Search {
+    fn from(params: SearchParams) -> Self {
+        Self {
+            q: params.q,
+            scope: params.scope.unwrap_or_default(),
+            author_id: params.author_id,
+            tag: params.tag,
+            created_after: params.created_after,
+            created_before: params.created_before,
+            page: PageRequest::new(params.page, params.limit),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHitResponse {
+    /// The post, or the comment when searching comments.
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub post_slug: String,
+    pub post_title: String,
+    pub author_id: Uuid,
+    /// HTML around the matches, each of them in `<mark>`.
+    pub snippet: String,
+    pub rank: f32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<SearchHit> for SearchHitResponse {
+    fn from(hit: SearchHit) -> Self {
+        Self {
+            id: hit.id,
+            post_id: hit.post_id,
+            post_slug: hit.post_slug,
+            post_title: hit.post_title,
+            author_id: hit.author_id,
+            snippet: hit.snippet,
+            rank: hit.rank,
+            created_at: hit.created_at,
+        }
+    }
+}
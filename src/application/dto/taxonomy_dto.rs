@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::nullable;
+use crate::domain::models::{
+    category::{CategoryNode, CreateCategory, UpdateCategory},
+    pagination::PageRequest,
+    tag::{CreateTag, TagCount, UpdateTag},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagResponse {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    /// Published posts carrying the tag.
+    pub post_count: i64,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<TagCount> for TagResponse {
+    fn from(TagCount { tag, post_count }: TagCount) -> Self {
+        Self {
+            id: tag.id,
+            slug: tag.slug,
+            name: tag.name,
+            post_count,
+            created_at: tag.created_at,
+            updated_at: tag.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateTagRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+}
+
+impl From<CreateTagRequest> for CreateTag {
+    fn from(request: CreateTagRequest) -> Self {
+        Self { name: request.name }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateTagRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+}
+
+impl From<UpdateTagRequest> for UpdateTag {
+    fn from(request: UpdateTagRequest) -> Self {
+        Self { name: request.name }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListTagsParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+impl From<ListTagsParams> for PageRequest {
+    fn from(params: ListTagsParams) -> Self {
+        PageRequest::new(params.page, params.limit)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryResponse {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Published posts filed under the category or any of its
+    /// subcategories.
+    pub post_count: i64,
+    pub children: Vec<CategoryResponse>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<CategoryNode> for CategoryResponse {
+    fn from(node: CategoryNode) -> Self {
+        let category = node.category;
+        Self {
+            id: category.id,
+            parent_id: category.parent_id,
+            slug: category.slug,
+            name: category.name,
+            description: category.description,
+            post_count: node.post_count,
+            children: node.children.into_iter().map(Self::from).collect(),
+            created_at: category.created_at,
+            updated_at: category.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCategoryRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+}
+
+impl From<CreateCategoryRequest> for CreateCategory {
+    fn from(request: CreateCategoryRequest) -> Self {
+        Self {
+            name: request.name,
+            description: request.description,
+            parent_id: request.parent_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateCategoryRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: Option<String>,
+    /// `null` clears the description.
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(max = 500))]
+    pub description: Option<Option<String>>,
+    /// `null` moves the category to the top level.
+    #[serde(default, deserialize_with = "nullable")]
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+impl From<UpdateCategoryRequest> for UpdateCategory {
+    fn from(request: UpdateCategoryRequest) -> Self {
+        Self {
+            name: request.name,
+            description: request.description,
+            parent_id: request.parent_id,
+        }
+    }
+}
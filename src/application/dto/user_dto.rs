@@ -2,13 +2,18 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::domain::models::user::{CreateUser, UpdateUser};
+use crate::domain::models::{
+    auth::{AuthSession, Credentials},
+    pagination::{PageRequest, SortDirection},
+    user::{CreateUser, Role, UpdateUser, UserFilter, UserQuery, UserSortField},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
     pub username: String,
     pub email: String,
+    pub role: Role,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
@@ -19,6 +24,7 @@ impl From<crate::domain::models::user::User> for UserResponse {
             id: user.id,
             username: user.username,
             email: user.email,
+            role: user.role,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -53,6 +59,7 @@ pub struct UpdateUserRequest {
     pub email: Option<String>,
     #[validate(length(min = 8))]
     pub password: Option<String>,
+    pub role: Option<Role>,
 }
 
 impl From<UpdateUserRequest> for UpdateUser {
@@ -61,12 +68,85 @@ impl From<UpdateUserRequest> for UpdateUser {
             username: request.username,
             email: request.email,
             password: request.password,
+            role: request.role,
         }
     }
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListUsersParams {
+    #[validate(range(min = 1, max = 1_000_000))]
+    pub page: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    pub sort: Option<UserSortField>,
+    pub direction: Option<SortDirection>,
+    pub role: Option<Role>,
+    pub created_after: Option<chrono::NaiveDateTime>,
+    pub created_before: Option<chrono::NaiveDateTime>,
+}
+
+impl From<ListUsersParams> for UserQuery {
+    fn from(params: ListUsersParams) -> Self {
+        Self {
+            filter: UserFilter {
+                role: params.role,
+                created_after: params.created_after,
+                created_before: params.created_before,
+            },
+            sort: params.sort.unwrap_or_default(),
+            direction: params.direction.unwrap_or_default(),
+            page: PageRequest::new(params.page, params.limit),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteUserParams {
+    pub reassign_posts_to: Option<Uuid>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthUserResponse {
     pub user: UserResponse,
     pub token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+impl From<AuthSession> for AuthUserResponse {
+    fn from(session: AuthSession) -> Self {
+        Self {
+            user: UserResponse::from(session.user),
+            token: session.access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: session.access_token_expires_in,
+            refresh_token: session.refresh_token,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LoginRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+impl From<LoginRequest> for Credentials {
+    fn from(request: LoginRequest) -> Self {
+        Self {
+            email: request.email,
+            password: request.password,
+        }
+    }
+}
+
+/// Body accepted by `/auth/refresh` and `/auth/logout` for clients that
+/// cannot rely on the refresh token cookie.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: Option<String>,
 }
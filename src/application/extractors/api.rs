@@ -0,0 +1,170 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use axum::{
+    Json,
+    extract::{
+        FromRequest, FromRequestParts, Path, RawPathParams, Request,
+        path::ErrorKind,
+        rejection::{JsonRejection, PathRejection},
+    },
+    http::request::Parts,
+};
+use serde::de::DeserializeOwned;
+
+use crate::shared::error::ApiError;
+
+/// `axum::Json`, but a body that cannot be read or parsed is answered with
+/// a problem response like every other error instead of axum's plain text.
+/// A value of the wrong type is reported against its field, the same way a
+/// failed validator rule is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+/// `axum::extract::Path` with problem-response rejections.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiPath<T>(pub T);
+
+/// `axum::extract::Query` with problem-response rejections.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ApiPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(Path(value)) => Ok(Self(value)),
+            Err(PathRejection::FailedToDeserializePathParams(error)) => {
+                let params = RawPathParams::from_request_parts(parts, state).await;
+                let names: Vec<&str> = params
+                    .iter()
+                    .flat_map(|params| params.iter().map(|(name, _)| name))
+                    .collect();
+                let field = match error.kind() {
+                    ErrorKind::ParseErrorAtKey { key, .. } => Some(key.as_str()),
+                    ErrorKind::ParseErrorAtIndex { index, .. } => names.get(*index).copied(),
+                    ErrorKind::ParseError { .. } | ErrorKind::Message(_) if names.len() == 1 => {
+                        Some(names[0])
+                    }
+                    _ => None,
+                };
+                Err(match field {
+                    Some(field) => ApiError::InvalidInput {
+                        field: field.to_string(),
+                        rule: "type",
+                        message: error.kind().to_string(),
+                    },
+                    None => ApiError::BadRequest(error.body_text()),
+                })
+            }
+            Err(rejection) => Err(rejection.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Decoded here rather than through `axum::extract::Query` so a bad
+        // value can be traced back to its parameter.
+        let query = parts.uri.query().unwrap_or_default();
+        let deserializer =
+            serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+        serde_path_to_error::deserialize(deserializer)
+            .map(Self)
+            .map_err(|error| {
+                let message = error.inner().to_string();
+                invalid_input(error.path(), message)
+            })
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        // axum decodes with `serde_path_to_error`; the path it recorded sits
+        // further down the rejection's source chain.
+        let mut source = rejection.source();
+        while let Some(error) = source {
+            if let Some(error) =
+                error.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>()
+            {
+                return invalid_input(error.path(), without_position(error.inner()));
+            }
+            source = error.source();
+        }
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+/// Turns a decoding failure into a violation on the field it happened at.
+/// A missing field is reported against that field, as `required`.
+fn invalid_input(path: &serde_path_to_error::Path, message: String) -> ApiError {
+    let at = path.to_string();
+    let parent = (at != ".").then_some(at);
+
+    if let Some(missing) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(name, _)| name)
+    {
+        return ApiError::InvalidInput {
+            field: match parent {
+                Some(parent) => format!("{}.{}", parent, missing),
+                None => missing.to_string(),
+            },
+            rule: "required",
+            message: "is required".to_string(),
+        };
+    }
+
+    match parent {
+        Some(field) => ApiError::InvalidInput {
+            field,
+            rule: "type",
+            message,
+        },
+        None => ApiError::BadRequest(message),
+    }
+}
+
+/// serde_json appends where in the document it gave up, which means nothing
+/// once the error is tied to a field.
+fn without_position(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message,
+    }
+}
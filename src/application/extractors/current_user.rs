@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, header, request::Parts},
+};
+use uuid::Uuid;
+
+use crate::{
+    application::routes::auth_routes::{ACCESS_TOKEN_COOKIE, cookie_value},
+    domain::{
+        models::{
+            auth::{Actor, TokenType},
+            user::Role,
+        },
+        services::auth_service::TokenCodec,
+    },
+    shared::error::ApiError,
+};
+
+/// The authenticated caller, resolved from an `Authorization: Bearer` header
+/// or, failing that, the access token cookie set by `/auth/login`.
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub id: Uuid,
+    pub role: Role,
+}
+
+impl CurrentUser {
+    pub fn actor(&self) -> Actor {
+        Actor {
+            user_id: self.id,
+            role: self.role,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let codec = parts
+            .extensions
+            .get::<TokenCodec>()
+            .ok_or(ApiError::InternalServerError)?;
+
+        let token = bearer_token(&parts.headers)
+            .or_else(|| cookie_value(&parts.headers, ACCESS_TOKEN_COOKIE))
+            .ok_or(ApiError::Unauthorized)?;
+
+        let claims = codec.verify(&token, TokenType::Access)?;
+
+        Ok(Self {
+            id: claims.sub,
+            role: claims.role,
+        })
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+
+    if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() {
+        Some(token.trim().to_string())
+    } else {
+        None
+    }
+}
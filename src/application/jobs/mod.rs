@@ -0,0 +1 @@
+pub mod scheduled_publishing;
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::services::post_service::PostService;
+
+/// Publishes due scheduled posts every `interval` until `token` is
+/// cancelled. A failed run is logged and retried on the next tick; several
+/// instances may run this at once since each post is published by a single
+/// conditional update.
+pub async fn run<S>(post_service: S, interval: Duration, token: CancellationToken)
+where
+    S: PostService,
+{
+    let mut ticker = time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = ticker.tick() => {}
+        }
+
+        match post_service.publish_due_posts().await {
+            Ok(published) => {
+                for post in published {
+                    println!("Published scheduled post {} ({})", post.id, post.slug);
+                }
+            }
+            Err(e) => eprintln!("Scheduled publishing failed: {}", e),
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod dto;
+pub mod extractors;
+pub mod jobs;
 pub mod routes;
@@ -0,0 +1,154 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{AppendHeaders, IntoResponse},
+    routing::post,
+};
+use cookie::{Cookie, SameSite, time};
+use validator::Validate;
+
+use crate::{
+    application::dto::user_dto::{AuthUserResponse, LoginRequest, RefreshTokenRequest},
+    application::extractors::api::ApiJson,
+    domain::{models::auth::AuthSession, services::auth_service::AuthService},
+    shared::error::ApiError,
+};
+
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+
+// The refresh cookie is only ever needed by the auth endpoints themselves.
+const REFRESH_TOKEN_COOKIE_PATH: &str = "/api/auth";
+
+#[derive(Clone)]
+pub struct AuthRouterState<S: AuthService> {
+    pub auth_service: S,
+}
+
+pub fn auth_router<S>(auth_service: S) -> Router
+where
+    S: AuthService + Clone + Send + Sync + 'static,
+{
+    let state = AuthRouterState { auth_service };
+
+    Router::new()
+        .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .with_state(state)
+}
+
+async fn login<S>(
+    State(state): State<AuthRouterState<S>>,
+    ApiJson(payload): ApiJson<LoginRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: AuthService,
+{
+    payload.validate()?;
+    let session = state.auth_service.login(payload.into()).await?;
+    Ok(session_response(session))
+}
+
+async fn refresh<S>(
+    State(state): State<AuthRouterState<S>>,
+    headers: HeaderMap,
+    payload: Option<Json<RefreshTokenRequest>>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: AuthService,
+{
+    let token = refresh_token_from(&headers, payload).ok_or(ApiError::Unauthorized)?;
+    let session = state.auth_service.refresh(&token).await?;
+    Ok(session_response(session))
+}
+
+async fn logout<S>(
+    State(state): State<AuthRouterState<S>>,
+    headers: HeaderMap,
+    payload: Option<Json<RefreshTokenRequest>>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: AuthService,
+{
+    let token = refresh_token_from(&headers, payload).ok_or(ApiError::Unauthorized)?;
+    state.auth_service.logout(&token).await?;
+
+    let cookies = AppendHeaders([
+        (
+            header::SET_COOKIE,
+            removal_cookie(ACCESS_TOKEN_COOKIE, "/").to_string(),
+        ),
+        (
+            header::SET_COOKIE,
+            removal_cookie(REFRESH_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE_PATH).to_string(),
+        ),
+    ]);
+    Ok((StatusCode::NO_CONTENT, cookies))
+}
+
+/// Reads a cookie by name from the request's `Cookie` headers.
+pub fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| Cookie::parse(pair.trim()).ok())
+        .find(|cookie| cookie.name() == name)
+        .map(|cookie| cookie.value().to_string())
+}
+
+fn refresh_token_from(
+    headers: &HeaderMap,
+    payload: Option<Json<RefreshTokenRequest>>,
+) -> Option<String> {
+    payload
+        .and_then(|Json(body)| body.refresh_token)
+        .or_else(|| cookie_value(headers, REFRESH_TOKEN_COOKIE))
+}
+
+fn session_response(session: AuthSession) -> impl IntoResponse {
+    let access_cookie = session_cookie(
+        ACCESS_TOKEN_COOKIE,
+        session.access_token.clone(),
+        "/",
+        session.access_token_expires_in,
+    );
+    let refresh_cookie = session_cookie(
+        REFRESH_TOKEN_COOKIE,
+        session.refresh_token.clone(),
+        REFRESH_TOKEN_COOKIE_PATH,
+        session.refresh_token_expires_in,
+    );
+
+    (
+        AppendHeaders([
+            (header::SET_COOKIE, access_cookie.to_string()),
+            (header::SET_COOKIE, refresh_cookie.to_string()),
+        ]),
+        Json(AuthUserResponse::from(session)),
+    )
+}
+
+fn session_cookie(
+    name: &'static str,
+    value: String,
+    path: &'static str,
+    max_age_secs: i64,
+) -> Cookie<'static> {
+    Cookie::build(name, value)
+        .path(path)
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .max_age(time::Duration::seconds(max_age_secs))
+        .finish()
+}
+
+fn removal_cookie(name: &'static str, path: &'static str) -> Cookie<'static> {
+    let mut cookie = session_cookie(name, String::new(), path, 0);
+    cookie.make_removal();
+    cookie
+}
@@ -1,33 +1,54 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{OriginalUri, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
 };
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    application::dto::comment_dto::{CommentResponse, CreateCommentRequest, UpdateCommentRequest},
+    application::dto::{
+        comment_dto::{
+            CommentResponse, CommentThreadResponse, CreateCommentRequest, ListCommentsParams,
+            ListThreadsParams, ModerateCommentsRequest, ModerationQueueParams,
+            PostModerationResponse, UpdateCommentRequest, UpdateModerationRequest,
+        },
+        pagination_dto::{CursorPaginatedResponse, PaginatedResponse},
+    },
+    application::extractors::{
+        api::{ApiJson, ApiPath, ApiQuery},
+        current_user::CurrentUser,
+    },
+    domain::models::comment::CommentSortField,
     domain::services::comment_service::CommentService,
-    shared::error::ApiError,
+    shared::{cursor::CursorCodec, error::ApiError},
 };
 
 #[derive(Clone)]
 pub struct CommentRouterState<S: CommentService> {
     pub comment_service: S,
+    pub cursor_codec: CursorCodec,
 }
 
-pub fn comment_router<S>(comment_service: S) -> Router
+pub fn comment_router<S>(comment_service: S, cursor_codec: CursorCodec) -> Router
 where
     S: CommentService + Clone + Send + Sync + 'static,
 {
-    let state = CommentRouterState { comment_service };
+    let state = CommentRouterState {
+        comment_service,
+        cursor_codec,
+    };
 
     Router::new()
         .route("/", post(create_comment))
         .route("/post/:post_id", get(get_comments_for_post))
+        .route("/post/:post_id/thread", get(get_thread_for_post))
+        .route("/post/:post_id/moderation", get(get_moderation))
+        .route("/post/:post_id/moderation", put(update_moderation))
+        .route("/queue", get(get_queue))
+        .route("/queue/moderate", post(moderate_comments))
         .route("/:id", get(get_comment))
         .route("/:id", put(update_comment))
         .route("/:id", delete(delete_comment))
@@ -36,62 +57,199 @@ where
 
 async fn get_comments_for_post<S>(
     State(state): State<CommentRouterState<S>>,
-    Path(post_id): Path<Uuid>,
+    current_user: Option<CurrentUser>,
+    ApiPath(post_id): ApiPath<Uuid>,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(mut params): ApiQuery<ListCommentsParams>,
+) -> Result<Response, ApiError>
+where
+    S: CommentService,
+{
+    params.validate()?;
+    let actor = current_user.map(|user| user.actor());
+
+    let Some(cursor) = params.cursor.take() else {
+        let comments = state
+            .comment_service
+            .find_by_post(actor.as_ref(), post_id, params.into())
+            .await?;
+        let response = PaginatedResponse::<CommentResponse>::new(comments, &uri);
+        return Ok(Json(response).into_response());
+    };
+
+    if params.page.is_some() {
+        return Err(ApiError::BadRequest(
+            "page and cursor cannot be combined".to_string(),
+        ));
+    }
+    if params
+        .sort
+        .is_some_and(|sort| sort != CommentSortField::CreatedAt)
+    {
+        return Err(ApiError::BadRequest(
+            "cursor pagination only supports sort=created_at".to_string(),
+        ));
+    }
+
+    let direction = params.direction.unwrap_or_default();
+    let after = match cursor.as_str() {
+        "" => None,
+        cursor => Some(state.cursor_codec.decode(post_id, direction, cursor)?),
+    };
+    let comments = state
+        .comment_service
+        .stream_by_post(actor.as_ref(), post_id, params.into_stream_query(after))
+        .await?;
+    let next_cursor = comments
+        .next
+        .map(|position| state.cursor_codec.encode(post_id, direction, &position));
+    let response = CursorPaginatedResponse::<CommentResponse>::new(comments, next_cursor, &uri);
+    Ok(Json(response).into_response())
+}
+
+async fn get_thread_for_post<S>(
+    State(state): State<CommentRouterState<S>>,
+    current_user: Option<CurrentUser>,
+    ApiPath(post_id): ApiPath<Uuid>,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(params): ApiQuery<ListThreadsParams>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: CommentService,
 {
-    let comments = state.comment_service.find_by_post(post_id).await?;
-    let response = comments
-        .into_iter()
-        .map(CommentResponse::from)
-        .collect::<Vec<_>>();
+    params.validate()?;
+    let actor = current_user.map(|user| user.actor());
+    let threads = state
+        .comment_service
+        .thread(actor.as_ref(), post_id, params.into())
+        .await?;
+    let response = PaginatedResponse::<CommentThreadResponse>::new(threads, &uri);
     Ok(Json(response))
 }
 
 async fn get_comment<S>(
     State(state): State<CommentRouterState<S>>,
-    Path(id): Path<Uuid>,
+    current_user: Option<CurrentUser>,
+    ApiPath(id): ApiPath<Uuid>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: CommentService,
 {
-    let comment = state.comment_service.find(id).await?;
+    let actor = current_user.map(|user| user.actor());
+    let comment = state.comment_service.find(actor.as_ref(), id).await?;
     Ok(Json(CommentResponse::from(comment)))
 }
 
 async fn create_comment<S>(
     State(state): State<CommentRouterState<S>>,
-    Json(payload): Json<CreateCommentRequest>,
+    current_user: CurrentUser,
+    ApiJson(payload): ApiJson<CreateCommentRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: CommentService,
 {
     payload.validate()?;
-    let comment = state.comment_service.create(payload.into()).await?;
+    let comment = state
+        .comment_service
+        .create(&current_user.actor(), payload.into())
+        .await?;
     Ok((StatusCode::CREATED, Json(CommentResponse::from(comment))))
 }
 
 async fn update_comment<S>(
     State(state): State<CommentRouterState<S>>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateCommentRequest>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(payload): ApiJson<UpdateCommentRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: CommentService,
 {
     payload.validate()?;
-    let comment = state.comment_service.update(id, payload.into()).await?;
+    let comment = state
+        .comment_service
+        .update(&current_user.actor(), id, payload.into())
+        .await?;
     Ok(Json(CommentResponse::from(comment)))
 }
 
 async fn delete_comment<S>(
     State(state): State<CommentRouterState<S>>,
-    Path(id): Path<Uuid>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: CommentService,
 {
-    state.comment_service.delete(id).await?;
+    state
+        .comment_service
+        .delete(&current_user.actor(), id)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn get_queue<S>(
+    State(state): State<CommentRouterState<S>>,
+    current_user: CurrentUser,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(params): ApiQuery<ModerationQueueParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: CommentService,
+{
+    params.validate()?;
+    let comments = state
+        .comment_service
+        .queue(&current_user.actor(), params.into())
+        .await?;
+    let response = PaginatedResponse::<CommentResponse>::new(comments, &uri);
+    Ok(Json(response))
+}
+
+async fn moderate_comments<S>(
+    State(state): State<CommentRouterState<S>>,
+    current_user: CurrentUser,
+    ApiJson(payload): ApiJson<ModerateCommentsRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: CommentService,
+{
+    payload.validate()?;
+    let comments = state
+        .comment_service
+        .moderate(&current_user.actor(), payload.into())
+        .await?;
+    let response: Vec<CommentResponse> = comments.into_iter().map(CommentResponse::from).collect();
+    Ok(Json(response))
+}
+
+async fn get_moderation<S>(
+    State(state): State<CommentRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath(post_id): ApiPath<Uuid>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: CommentService,
+{
+    let moderation = state
+        .comment_service
+        .moderation(&current_user.actor(), post_id)
+        .await?;
+    Ok(Json(PostModerationResponse::from(moderation)))
+}
+
+async fn update_moderation<S>(
+    State(state): State<CommentRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath(post_id): ApiPath<Uuid>,
+    ApiJson(payload): ApiJson<UpdateModerationRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: CommentService,
+{
+    let moderation = state
+        .comment_service
+        .update_moderation(&current_user.actor(), post_id, payload.into())
+        .await?;
+    Ok(Json(PostModerationResponse::from(moderation)))
+}
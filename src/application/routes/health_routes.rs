@@ -0,0 +1,42 @@
+use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+
+use crate::{
+    application::dto::health_dto::{LivenessResponse, ReadinessResponse},
+    domain::{models::health::ReadinessStatus, services::health_service::HealthService},
+};
+
+#[derive(Clone)]
+pub struct HealthRouterState<S: HealthService> {
+    pub health_service: S,
+}
+
+/// Probe endpoints, mounted outside `/api` so they bypass auth and CORS.
+pub fn health_router<S>(health_service: S) -> Router
+where
+    S: HealthService + Clone + Send + Sync + 'static,
+{
+    let state = HealthRouterState { health_service };
+
+    Router::new()
+        .route("/live", get(liveness))
+        .route("/ready", get(readiness))
+        .with_state(state)
+}
+
+/// The process is up and serving; says nothing about its dependencies.
+async fn liveness() -> impl IntoResponse {
+    Json(LivenessResponse { status: "alive" })
+}
+
+async fn readiness<S>(State(state): State<HealthRouterState<S>>) -> impl IntoResponse
+where
+    S: HealthService,
+{
+    let report = state.health_service.readiness().await;
+    let status = match report.status {
+        ReadinessStatus::Ready => StatusCode::OK,
+        ReadinessStatus::Degraded | ReadinessStatus::Draining => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(ReadinessResponse::from(report)))
+}
@@ -1,23 +1,87 @@
+pub mod auth_routes;
 pub mod comment_routes;
+pub mod health_routes;
 pub mod post_routes;
+pub mod search_routes;
+pub mod taxonomy_routes;
 pub mod user_routes;
 
-use axum::Router;
-use axum::routing::get;
+use axum::http::{HeaderValue, Method, header};
+use axum::{Extension, Router};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use crate::domain::services::{
-    comment_service::CommentService, post_service::PostService, user_service::UserService,
+    auth_service::AuthService, comment_service::CommentService, post_service::PostService,
+    search_service::SearchService, taxonomy_service::TaxonomyService, user_service::UserService,
 };
+use crate::shared::config::{Config, CorsConfig};
+use crate::shared::cursor::CursorCodec;
 
-pub fn create_routes<C, P, U>(comment_service: C, post_service: P, user_service: U) -> Router
+pub fn create_routes<C, P, T, S, U, A>(
+    comment_service: C,
+    post_service: P,
+    taxonomy_service: T,
+    search_service: S,
+    user_service: U,
+    auth_service: A,
+    config: &Config,
+) -> Router
 where
     C: CommentService + Clone + Send + Sync + 'static,
     P: PostService + Clone + Send + Sync + 'static,
+    T: TaxonomyService + Clone + Send + Sync + 'static,
+    S: SearchService + Clone + Send + Sync + 'static,
     U: UserService + Clone + Send + Sync + 'static,
+    A: AuthService + Clone + Send + Sync + 'static,
 {
-    Router::new()
+    let token_codec = auth_service.token_codec();
+    let cursor_codec = CursorCodec::new(config.auth.cursor_secret());
+
+    let router = Router::new()
+        .nest("/auth", auth_routes::auth_router(auth_service))
+        .nest(
+            "/tags",
+            taxonomy_routes::tag_router(taxonomy_service.clone(), post_service.clone()),
+        )
+        .nest(
+            "/categories",
+            taxonomy_routes::category_router(taxonomy_service, post_service.clone()),
+        )
         .nest("/posts", post_routes::post_router(post_service))
-        .nest("/users", user_routes::user_router(user_service))
-        .nest("/comments", comment_routes::comment_router(comment_service))
-        .route("/health", get(|| async { "OK" }))
+        .nest("/search", search_routes::search_router(search_service))
+        .nest(
+            "/users",
+            user_routes::user_router(user_service, config.features.registration),
+        )
+        .nest(
+            "/comments",
+            comment_routes::comment_router(comment_service, cursor_codec),
+        )
+        .layer(Extension(token_codec));
+
+    match cors_layer(&config.cors) {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
+}
+
+fn cors_layer(config: &CorsConfig) -> Option<CorsLayer> {
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+
+    // Origins are checked by `Config::validate`, so none are dropped here.
+    let origins: Vec<HeaderValue> = config
+        .allowed_origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect();
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+            .allow_credentials(true),
+    )
 }
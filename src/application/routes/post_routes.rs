@@ -1,15 +1,25 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{OriginalUri, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
 };
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    application::dto::post_dto::{CreatePostRequest, PostResponse, UpdatePostRequest},
+    application::dto::{
+        pagination_dto::PaginatedResponse,
+        post_dto::{
+            ChangePostStatusRequest, CreatePostRequest, ListPostsParams, ListRevisionsParams,
+            PostResponse, PostRevisionResponse, RevisionDiffParams, UpdatePostRequest,
+        },
+    },
+    application::extractors::{
+        api::{ApiJson, ApiPath, ApiQuery},
+        current_user::CurrentUser,
+    },
     domain::services::post_service::PostService,
     shared::error::ApiError,
 };
@@ -28,69 +38,213 @@ where
     Router::new()
         .route("/", get(get_posts))
         .route("/", post(create_post))
+        .route("/by-slug/:slug", get(get_post_by_slug))
         .route("/:id", get(get_post))
         .route("/:id", put(update_post))
         .route("/:id", delete(delete_post))
+        .route("/:id/status", put(change_post_status))
+        .route("/:id/revisions", get(get_revisions))
+        .route("/:id/revisions/:rev", get(get_revision))
+        .route("/:id/revisions/:rev/diff", get(diff_revisions))
+        .route("/:id/revisions/:rev/restore", post(restore_revision))
         .with_state(state)
 }
 
 async fn get_posts<S>(
     State(state): State<PostRouterState<S>>,
+    OriginalUri(uri): OriginalUri,
+    current_user: Option<CurrentUser>,
+    ApiQuery(params): ApiQuery<ListPostsParams>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: PostService,
 {
-    let posts = state.post_service.get_posts().await?;
-    let response = posts
-        .into_iter()
-        .map(PostResponse::from)
-        .collect::<Vec<_>>();
+    params.validate()?;
+    let actor = current_user.map(|user| user.actor());
+    let posts = state
+        .post_service
+        .get_posts(actor.as_ref(), params.into())
+        .await?;
+    let response = PaginatedResponse::<PostResponse>::new(posts, &uri);
     Ok(Json(response))
 }
 
 async fn get_post<S>(
     State(state): State<PostRouterState<S>>,
-    Path(id): Path<Uuid>,
+    current_user: Option<CurrentUser>,
+    ApiPath(id): ApiPath<Uuid>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: PostService,
 {
-    let post = state.post_service.get_post(id).await?;
+    let actor = current_user.map(|user| user.actor());
+    let post = state.post_service.get_post(actor.as_ref(), id).await?;
     Ok(Json(PostResponse::from(post)))
 }
 
+/// Former slugs answer with a permanent redirect to the current one.
+async fn get_post_by_slug<S>(
+    State(state): State<PostRouterState<S>>,
+    OriginalUri(uri): OriginalUri,
+    current_user: Option<CurrentUser>,
+    ApiPath(slug): ApiPath<String>,
+) -> Result<Response, ApiError>
+where
+    S: PostService,
+{
+    let actor = current_user.map(|user| user.actor());
+    let post = state
+        .post_service
+        .get_post_by_slug(actor.as_ref(), &slug)
+        .await?;
+    if post.post.slug == slug {
+        return Ok(Json(PostResponse::from(post)).into_response());
+    }
+
+    let base = uri.path().rsplit_once('/').map_or("", |(base, _)| base);
+    let location = format!("{}/{}", base, post.post.slug);
+    Ok((
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, location)],
+    )
+        .into_response())
+}
+
 async fn create_post<S>(
     State(state): State<PostRouterState<S>>,
-    Json(payload): Json<CreatePostRequest>,
+    current_user: CurrentUser,
+    ApiJson(payload): ApiJson<CreatePostRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: PostService,
 {
     payload.validate()?;
-    let post = state.post_service.create_post(payload.into()).await?;
+    let post = state
+        .post_service
+        .create_post(&current_user.actor(), payload.into())
+        .await?;
     Ok((StatusCode::CREATED, Json(PostResponse::from(post))))
 }
 
 async fn update_post<S>(
     State(state): State<PostRouterState<S>>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePostRequest>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(payload): ApiJson<UpdatePostRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: PostService,
 {
     payload.validate()?;
-    let post = state.post_service.update_post(id, payload.into()).await?;
+    let post = state
+        .post_service
+        .update_post(&current_user.actor(), id, payload.into())
+        .await?;
+    Ok(Json(PostResponse::from(post)))
+}
+
+async fn change_post_status<S>(
+    State(state): State<PostRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(payload): ApiJson<ChangePostStatusRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: PostService,
+{
+    let post = state
+        .post_service
+        .change_status(&current_user.actor(), id, payload.into())
+        .await?;
+    Ok(Json(PostResponse::from(post)))
+}
+
+async fn get_revisions<S>(
+    State(state): State<PostRouterState<S>>,
+    OriginalUri(uri): OriginalUri,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiQuery(params): ApiQuery<ListRevisionsParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: PostService,
+{
+    params.validate()?;
+    let revisions = state
+        .post_service
+        .get_revisions(&current_user.actor(), id, params.into())
+        .await?;
+    let response = PaginatedResponse::<PostRevisionResponse>::new(revisions, &uri);
+    Ok(Json(response))
+}
+
+async fn get_revision<S>(
+    State(state): State<PostRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath((id, revision)): ApiPath<(Uuid, i32)>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: PostService,
+{
+    let revision = state
+        .post_service
+        .get_revision(&current_user.actor(), id, revision)
+        .await?;
+    Ok(Json(PostRevisionResponse::from(revision)))
+}
+
+async fn diff_revisions<S>(
+    State(state): State<PostRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath((id, revision)): ApiPath<(Uuid, i32)>,
+    ApiQuery(params): ApiQuery<RevisionDiffParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: PostService,
+{
+    params.validate()?;
+    let from = match params.from {
+        Some(from) => from,
+        None if revision > 1 => revision - 1,
+        None => {
+            return Err(ApiError::BadRequest(
+                "The first revision has nothing before it; pass from to compare.".to_string(),
+            ));
+        }
+    };
+    let diff = state
+        .post_service
+        .diff_revisions(&current_user.actor(), id, from, revision)
+        .await?;
+    Ok(Json(diff))
+}
+
+async fn restore_revision<S>(
+    State(state): State<PostRouterState<S>>,
+    current_user: CurrentUser,
+    ApiPath((id, revision)): ApiPath<(Uuid, i32)>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: PostService,
+{
+    let post = state
+        .post_service
+        .restore_revision(&current_user.actor(), id, revision)
+        .await?;
     Ok(Json(PostResponse::from(post)))
 }
 
 async fn delete_post<S>(
     State(state): State<PostRouterState<S>>,
-    Path(id): Path<Uuid>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: PostService,
 {
-    state.post_service.delete_post(id).await?;
+    state
+        .post_service
+        .delete_post(&current_user.actor(), id)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
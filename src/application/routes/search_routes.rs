@@ -0,0 +1,45 @@
+use axum::{
+    Json, Router,
+    extract::{OriginalUri, State},
+    response::IntoResponse,
+    routing::get,
+};
+use validator::Validate;
+
+use crate::{
+    application::dto::{
+        pagination_dto::PaginatedResponse,
+        search_dto::{SearchHitResponse, SearchParams},
+    },
+    application::extractors::api::ApiQuery,
+    domain::services::search_service::SearchService,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct SearchRouterState<S: SearchService> {
+    pub search_service: S,
+}
+
+pub fn search_router<S>(search_service: S) -> Router
+where
+    S: SearchService + Clone + Send + Sync + 'static,
+{
+    let state = SearchRouterState { search_service };
+
+    Router::new().route("/", get(search)).with_state(state)
+}
+
+async fn search<S>(
+    State(state): State<SearchRouterState<S>>,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(params): ApiQuery<SearchParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: SearchService,
+{
+    params.validate()?;
+    let hits = state.search_service.search(params.into()).await?;
+    let response = PaginatedResponse::<SearchHitResponse>::new(hits, &uri);
+    Ok(Json(response))
+}
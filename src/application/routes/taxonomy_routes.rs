@@ -0,0 +1,274 @@
+use axum::{
+    Json, Router,
+    extract::{OriginalUri, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+};
+use validator::Validate;
+
+use crate::{
+    application::dto::{
+        pagination_dto::PaginatedResponse,
+        post_dto::{ListPostsParams, PostResponse},
+        taxonomy_dto::{
+            CategoryResponse, CreateCategoryRequest, CreateTagRequest, ListTagsParams, TagResponse,
+            UpdateCategoryRequest, UpdateTagRequest,
+        },
+    },
+    application::extractors::{
+        api::{ApiJson, ApiPath, ApiQuery},
+        current_user::CurrentUser,
+    },
+    domain::models::post::PostQuery,
+    domain::services::{post_service::PostService, taxonomy_service::TaxonomyService},
+    shared::error::ApiError,
+};
+
+/// Tag and category routes also list posts, so they need both services.
+#[derive(Clone)]
+pub struct TaxonomyRouterState<S: TaxonomyService, P: PostService> {
+    pub taxonomy_service: S,
+    pub post_service: P,
+}
+
+pub fn tag_router<S, P>(taxonomy_service: S, post_service: P) -> Router
+where
+    S: TaxonomyService + Clone + Send + Sync + 'static,
+    P: PostService + Clone + Send + Sync + 'static,
+{
+    let state = TaxonomyRouterState {
+        taxonomy_service,
+        post_service,
+    };
+
+    Router::new()
+        .route("/", get(get_tags))
+        .route("/", post(create_tag))
+        .route("/:slug", get(get_tag))
+        .route("/:slug", put(update_tag))
+        .route("/:slug", delete(delete_tag))
+        .route("/:slug/posts", get(get_tag_posts))
+        .with_state(state)
+}
+
+pub fn category_router<S, P>(taxonomy_service: S, post_service: P) -> Router
+where
+    S: TaxonomyService + Clone + Send + Sync + 'static,
+    P: PostService + Clone + Send + Sync + 'static,
+{
+    let state = TaxonomyRouterState {
+        taxonomy_service,
+        post_service,
+    };
+
+    Router::new()
+        .route("/", get(get_categories))
+        .route("/", post(create_category))
+        .route("/:slug", get(get_category))
+        .route("/:slug", put(update_category))
+        .route("/:slug", delete(delete_category))
+        .route("/:slug/posts", get(get_category_posts))
+        .with_state(state)
+}
+
+async fn get_tags<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(params): ApiQuery<ListTagsParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    params.validate()?;
+    let tags = state.taxonomy_service.get_tags(params.into()).await?;
+    let response = PaginatedResponse::<TagResponse>::new(tags, &uri);
+    Ok(Json(response))
+}
+
+async fn get_tag<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    ApiPath(slug): ApiPath<String>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    let tag = state.taxonomy_service.get_tag(&slug).await?;
+    Ok(Json(TagResponse::from(tag)))
+}
+
+async fn create_tag<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiJson(payload): ApiJson<CreateTagRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    payload.validate()?;
+    let tag = state
+        .taxonomy_service
+        .create_tag(&current_user.actor(), payload.into())
+        .await?;
+    Ok((StatusCode::CREATED, Json(TagResponse::from(tag))))
+}
+
+async fn update_tag<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiPath(slug): ApiPath<String>,
+    ApiJson(payload): ApiJson<UpdateTagRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    payload.validate()?;
+    let tag = state
+        .taxonomy_service
+        .update_tag(&current_user.actor(), &slug, payload.into())
+        .await?;
+    Ok(Json(TagResponse::from(tag)))
+}
+
+async fn delete_tag<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiPath(slug): ApiPath<String>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    state
+        .taxonomy_service
+        .delete_tag(&current_user.actor(), &slug)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Takes the same parameters as `GET /posts`, so the usual visibility rules
+/// apply.
+async fn get_tag_posts<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    OriginalUri(uri): OriginalUri,
+    current_user: Option<CurrentUser>,
+    ApiPath(slug): ApiPath<String>,
+    ApiQuery(params): ApiQuery<ListPostsParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    params.validate()?;
+    let tag = state.taxonomy_service.get_tag(&slug).await?;
+    let mut query = PostQuery::from(params);
+    query.filter.tag_id = Some(tag.tag.id);
+
+    let actor = current_user.map(|user| user.actor());
+    let posts = state.post_service.get_posts(actor.as_ref(), query).await?;
+    let response = PaginatedResponse::<PostResponse>::new(posts, &uri);
+    Ok(Json(response))
+}
+
+async fn get_categories<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    let categories = state.taxonomy_service.get_categories().await?;
+    let response: Vec<CategoryResponse> =
+        categories.into_iter().map(CategoryResponse::from).collect();
+    Ok(Json(response))
+}
+
+async fn get_category<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    ApiPath(slug): ApiPath<String>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    let category = state.taxonomy_service.get_category(&slug).await?;
+    Ok(Json(CategoryResponse::from(category)))
+}
+
+async fn create_category<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiJson(payload): ApiJson<CreateCategoryRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    payload.validate()?;
+    let category = state
+        .taxonomy_service
+        .create_category(&current_user.actor(), payload.into())
+        .await?;
+    Ok((StatusCode::CREATED, Json(CategoryResponse::from(category))))
+}
+
+async fn update_category<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiPath(slug): ApiPath<String>,
+    ApiJson(payload): ApiJson<UpdateCategoryRequest>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    payload.validate()?;
+    let category = state
+        .taxonomy_service
+        .update_category(&current_user.actor(), &slug, payload.into())
+        .await?;
+    Ok(Json(CategoryResponse::from(category)))
+}
+
+async fn delete_category<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    current_user: CurrentUser,
+    ApiPath(slug): ApiPath<String>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    state
+        .taxonomy_service
+        .delete_category(&current_user.actor(), &slug)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Includes posts filed under any subcategory.
+async fn get_category_posts<S, P>(
+    State(state): State<TaxonomyRouterState<S, P>>,
+    OriginalUri(uri): OriginalUri,
+    current_user: Option<CurrentUser>,
+    ApiPath(slug): ApiPath<String>,
+    ApiQuery(params): ApiQuery<ListPostsParams>,
+) -> Result<impl IntoResponse, ApiError>
+where
+    S: TaxonomyService,
+    P: PostService,
+{
+    params.validate()?;
+    let category = state.taxonomy_service.get_category(&slug).await?;
+    let mut query = PostQuery::from(params);
+    query.filter.category_ids = Some(category.ids());
+
+    let actor = current_user.map(|user| user.actor());
+    let posts = state.post_service.get_posts(actor.as_ref(), query).await?;
+    let response = PaginatedResponse::<PostResponse>::new(posts, &uri);
+    Ok(Json(response))
+}
@@ -1,6 +1,6 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{OriginalUri, State},
     http::StatusCode,
     response::IntoResponse,
     routing::{delete, get, post, put},
@@ -9,7 +9,16 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    application::dto::user_dto::{CreateUserRequest, UpdateUserRequest, UserResponse},
+    application::dto::{
+        pagination_dto::PaginatedResponse,
+        user_dto::{
+            CreateUserRequest, DeleteUserParams, ListUsersParams, UpdateUserRequest, UserResponse,
+        },
+    },
+    application::extractors::{
+        api::{ApiJson, ApiPath, ApiQuery},
+        current_user::CurrentUser,
+    },
     domain::services::user_service::UserService,
     shared::error::ApiError,
 };
@@ -19,15 +28,19 @@ pub struct UserRouterState<S: UserService> {
     pub user_service: S,
 }
 
-pub fn user_router<S>(user_service: S) -> Router
+pub fn user_router<S>(user_service: S, registration: bool) -> Router
 where
     S: UserService + Clone + Send + Sync + 'static,
 {
     let state = UserRouterState { user_service };
 
-    Router::new()
-        .route("/", post(create_user))
-        .route("/", get(get_all_users))
+    let router = if registration {
+        Router::new().route("/", post(create_user).get(get_all_users))
+    } else {
+        Router::new().route("/", get(get_all_users))
+    };
+
+    router
         .route("/:id", get(get_user))
         .route("/:id", put(update_user))
         .route("/:id", delete(delete_user))
@@ -37,7 +50,7 @@ where
 
 async fn get_user<S>(
     State(state): State<UserRouterState<S>>,
-    Path(id): Path<Uuid>,
+    ApiPath(id): ApiPath<Uuid>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
@@ -48,18 +61,21 @@ where
 
 async fn get_all_users<S>(
     State(state): State<UserRouterState<S>>,
+    OriginalUri(uri): OriginalUri,
+    ApiQuery(params): ApiQuery<ListUsersParams>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
 {
-    let users = state.user_service.find_all().await?; // Fetch all users
-    let response: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
+    params.validate()?;
+    let users = state.user_service.find_all(params.into()).await?;
+    let response = PaginatedResponse::<UserResponse>::new(users, &uri);
     Ok(Json(response))
 }
 
 async fn get_user_by_email<S>(
     State(state): State<UserRouterState<S>>,
-    Path(email): Path<String>,
+    ApiPath(email): ApiPath<String>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
@@ -70,7 +86,7 @@ where
 
 async fn create_user<S>(
     State(state): State<UserRouterState<S>>,
-    Json(payload): Json<CreateUserRequest>,
+    ApiJson(payload): ApiJson<CreateUserRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
@@ -82,24 +98,33 @@ where
 
 async fn update_user<S>(
     State(state): State<UserRouterState<S>>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateUserRequest>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(payload): ApiJson<UpdateUserRequest>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
 {
     payload.validate()?;
-    let user = state.user_service.update(id, payload.into()).await?;
+    let user = state
+        .user_service
+        .update(&current_user.actor(), id, payload.into())
+        .await?;
     Ok(Json(UserResponse::from(user)))
 }
 
 async fn delete_user<S>(
     State(state): State<UserRouterState<S>>,
-    Path(id): Path<Uuid>,
+    current_user: CurrentUser,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiQuery(params): ApiQuery<DeleteUserParams>,
 ) -> Result<impl IntoResponse, ApiError>
 where
     S: UserService,
 {
-    state.user_service.delete(id).await?;
+    state
+        .user_service
+        .delete(&current_user.actor(), id, params.reassign_posts_to)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
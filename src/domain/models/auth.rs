@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::user::{Role, User};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub role: Role,
+    pub jti: Uuid,
+    pub typ: TokenType,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct Credentials {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthSession {
+    pub user: User,
+    pub access_token: String,
+    pub access_token_expires_in: i64,
+    pub refresh_token: String,
+    pub refresh_token_expires_in: i64,
+}
+
+/// Whoever is performing an operation, as seen by the domain policies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Actor {
+    pub user_id: Uuid,
+    pub role: Role,
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::infrastructure::database::schema::categories;
+
+/// A node in the category tree. A post is filed under at most one category.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = categories)]
+pub struct Category {
+    pub id: Uuid,
+    /// `None` for top-level categories.
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = categories)]
+pub struct NewCategory {
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct CreateCategory {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+}
+
+/// `Some(None)` clears `description` or moves the category to the top level.
+#[derive(Debug, Clone, Validate, Deserialize)]
+pub struct UpdateCategory {
+    #[validate(length(min = 1, max = 50))]
+    pub name: Option<String>,
+    #[validate(length(max = 500))]
+    pub description: Option<Option<String>>,
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+/// A category with its subcategories. `post_count` covers published posts
+/// filed under the category or anywhere beneath it.
+#[derive(Debug, Clone)]
+pub struct CategoryNode {
+    pub category: Category,
+    pub post_count: i64,
+    pub children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    /// Arranges `categories` into trees, siblings ordered by name.
+    /// `post_counts` holds the published posts filed directly under each
+    /// category.
+    pub fn forest(categories: Vec<Category>, post_counts: &HashMap<Uuid, i64>) -> Vec<Self> {
+        let mut children: HashMap<Option<Uuid>, Vec<Category>> = HashMap::new();
+        for category in categories {
+            children
+                .entry(category.parent_id)
+                .or_default()
+                .push(category);
+        }
+        Self::grow(None, &mut children, post_counts)
+    }
+
+    fn grow(
+        parent_id: Option<Uuid>,
+        children: &mut HashMap<Option<Uuid>, Vec<Category>>,
+        post_counts: &HashMap<Uuid, i64>,
+    ) -> Vec<Self> {
+        let mut level = children.remove(&parent_id).unwrap_or_default();
+        level.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        level
+            .into_iter()
+            .map(|category| {
+                let children = Self::grow(Some(category.id), children, post_counts);
+                let post_count = post_counts.get(&category.id).copied().unwrap_or(0)
+                    + children.iter().map(|child| child.post_count).sum::<i64>();
+                Self {
+                    category,
+                    post_count,
+                    children,
+                }
+            })
+            .collect()
+    }
+
+    /// The node for `slug` anywhere in `forest`.
+    pub fn find(forest: Vec<Self>, slug: &str) -> Option<Self> {
+        forest.into_iter().find_map(|node| {
+            if node.category.slug == slug {
+                Some(node)
+            } else {
+                Self::find(node.children, slug)
+            }
+        })
+    }
+
+    /// This category's id and those of everything beneath it.
+    pub fn ids(&self) -> Vec<Uuid> {
+        let mut ids = vec![self.category.id];
+        for child in &self.children {
+            ids.extend(child.ids());
+        }
+        ids
+    }
+}
@@ -1,10 +1,28 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::{fmt, io::Write, str::FromStr};
+
 use chrono::NaiveDateTime;
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use diesel::{
+    AsChangeset, AsExpression, FromSqlRow, Identifiable, Insertable, Queryable, Selectable,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::infrastructure::database::schema::comments;
+use crate::domain::models::markdown::{self, Flavor};
+use crate::domain::models::pagination::{CursorPosition, PageRequest, SortDirection};
+use crate::domain::models::user::Role;
+use crate::infrastructure::database::schema::{comment_moderation, comments};
+use crate::shared::config::ModerationConfig;
+
+/// How deeply replies may nest. Top-level comments are at depth 0, so a
+/// comment at this depth cannot be replied to.
+pub const MAX_REPLY_DEPTH: i32 = 5;
 
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
 #[diesel(table_name = comments)]
@@ -15,6 +33,159 @@ pub struct Comment {
     pub author_id: Uuid,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// `content` as HTML, rendered when the comment was saved. `None` for
+    /// comments last saved before rendering existed; see [`Comment::html`].
+    pub content_html: Option<String>,
+    /// The comment this one replies to, on the same post.
+    pub parent_id: Option<Uuid>,
+    /// Direct replies readers can see; see [`Comment::is_public`].
+    /// Maintained by the store.
+    pub reply_count: i32,
+    /// Set when the comment was deleted while it had replies; its content is
+    /// gone but it keeps its place in the thread.
+    pub deleted_at: Option<NaiveDateTime>,
+    pub status: CommentStatus,
+}
+
+/// Where a comment is in moderation. Only `Approved` comments are public.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Spam,
+}
+
+impl CommentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentStatus::Pending => "pending",
+            CommentStatus::Approved => "approved",
+            CommentStatus::Rejected => "rejected",
+            CommentStatus::Spam => "spam",
+        }
+    }
+}
+
+impl fmt::Display for CommentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CommentStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(CommentStatus::Pending),
+            "approved" => Ok(CommentStatus::Approved),
+            "rejected" => Ok(CommentStatus::Rejected),
+            "spam" => Ok(CommentStatus::Spam),
+            other => Err(format!("unknown comment status: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for CommentStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for CommentStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        value.parse().map_err(Into::into)
+    }
+}
+
+/// How new comments are moderated. The defaults come from the
+/// `[moderation]` config section and a post may override any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModerationSettings {
+    /// Hold every comment that is not let through otherwise.
+    pub require_approval: bool,
+    /// Let authors skip the queue; moderators always do.
+    pub auto_approve_trusted: bool,
+    /// Hold comments from anyone without an approved comment yet.
+    pub hold_first_time: bool,
+}
+
+impl ModerationSettings {
+    /// These settings with a post's overrides applied.
+    pub fn overridden_by(self, overrides: &ModerationOverrides) -> Self {
+        Self {
+            require_approval: overrides.require_approval.unwrap_or(self.require_approval),
+            auto_approve_trusted: overrides
+                .auto_approve_trusted
+                .unwrap_or(self.auto_approve_trusted),
+            hold_first_time: overrides.hold_first_time.unwrap_or(self.hold_first_time),
+        }
+    }
+
+    /// Whether comments by `role` are approved without a moderator.
+    pub fn trusts(&self, role: Role) -> bool {
+        role >= Role::Editor || (self.auto_approve_trusted && role >= Role::Author)
+    }
+}
+
+impl From<&ModerationConfig> for ModerationSettings {
+    fn from(config: &ModerationConfig) -> Self {
+        Self {
+            require_approval: config.require_approval,
+            auto_approve_trusted: config.auto_approve_trusted,
+            hold_first_time: config.hold_first_time,
+        }
+    }
+}
+
+/// A post's overrides of the global [`ModerationSettings`]. `None` keeps the
+/// global value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = comment_moderation, primary_key(post_id), treat_none_as_null = true)]
+pub struct ModerationOverrides {
+    pub post_id: Uuid,
+    pub require_approval: Option<bool>,
+    pub auto_approve_trusted: Option<bool>,
+    pub hold_first_time: Option<bool>,
+}
+
+/// A post's moderation overrides and the settings they come to.
+#[derive(Debug, Clone)]
+pub struct PostModeration {
+    pub overrides: ModerationOverrides,
+    pub effective: ModerationSettings,
+}
+
+/// Replaces a post's overrides; anything left out falls back to the global
+/// setting.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateModeration {
+    pub require_approval: Option<bool>,
+    pub auto_approve_trusted: Option<bool>,
+    pub hold_first_time: Option<bool>,
+}
+
+/// Comments awaiting a moderator, or already decided, oldest first.
+/// Tombstones are left out.
+#[derive(Debug, Clone)]
+pub struct ModerationQueueQuery {
+    pub status: CommentStatus,
+    pub post_id: Option<Uuid>,
+    pub page: PageRequest,
+}
+
+/// A moderator's decision on several comments at once.
+#[derive(Debug, Clone)]
+pub struct ModerateComments {
+    pub ids: Vec<Uuid>,
+    pub status: CommentStatus,
 }
 
 #[derive(Debug, Insertable, Deserialize)]
@@ -23,6 +194,9 @@ pub struct NewComment {
     pub content: String,
     pub post_id: Uuid,
     pub author_id: Uuid,
+    pub content_html: Option<String>,
+    pub parent_id: Option<Uuid>,
+    pub status: CommentStatus,
 }
 
 #[derive(Debug, AsChangeset, Deserialize)]
@@ -31,6 +205,8 @@ pub struct UpdateCommentData {
     pub content: Option<String>,
     #[diesel(column_name = "updated_at")]
     pub updated_at: Option<NaiveDateTime>,
+    pub content_html: Option<Option<String>>,
+    pub status: Option<CommentStatus>,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -38,7 +214,8 @@ pub struct CreateComment {
     #[validate(length(min = 1))]
     pub content: String,
     pub post_id: Uuid,
-    pub author_id: Uuid,
+    /// Makes the comment a reply.
+    pub parent_id: Option<Uuid>,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -46,3 +223,110 @@ pub struct UpdateComment {
     #[validate(length(min = 1))]
     pub content: Option<String>,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommentFilter {
+    pub author_id: Option<Uuid>,
+    pub status: Option<CommentStatus>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommentQuery {
+    pub filter: CommentFilter,
+    pub sort: CommentSortField,
+    pub direction: SortDirection,
+    pub page: PageRequest,
+}
+
+/// Keyset-paginated read of a post's comments ordered by `(created_at, id)`.
+#[derive(Debug, Clone, Default)]
+pub struct CommentStreamQuery {
+    pub filter: CommentFilter,
+    pub direction: SortDirection,
+    pub after: Option<CursorPosition>,
+    pub limit: i64,
+}
+
+/// A comment with its replies, oldest first at every level.
+#[derive(Debug, Clone)]
+pub struct CommentNode {
+    pub comment: Comment,
+    pub depth: i32,
+    pub replies: Vec<CommentNode>,
+}
+
+impl CommentNode {
+    /// Arranges a post's `comments` into threads. Replies whose parent is not
+    /// among them are left out along with their own replies.
+    pub fn threads(comments: Vec<Comment>) -> Vec<Self> {
+        let mut replies: HashMap<Option<Uuid>, Vec<Comment>> = HashMap::new();
+        for comment in comments {
+            replies.entry(comment.parent_id).or_default().push(comment);
+        }
+        Self::grow(None, 0, &mut replies)
+    }
+
+    fn grow(
+        parent_id: Option<Uuid>,
+        depth: i32,
+        replies: &mut HashMap<Option<Uuid>, Vec<Comment>>,
+    ) -> Vec<Self> {
+        let mut level = replies.remove(&parent_id).unwrap_or_default();
+        level.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        level
+            .into_iter()
+            .map(|comment| Self {
+                replies: Self::grow(Some(comment.id), depth + 1, replies),
+                depth,
+                comment,
+            })
+            .collect()
+    }
+}
+
+impl Comment {
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Whether readers see the comment: it is approved and, if it is a
+    /// tombstone, still has replies they can see.
+    pub fn is_public(&self) -> bool {
+        self.status == CommentStatus::Approved && !self.is_hidden_tombstone()
+    }
+
+    /// A tombstone with no replies left that readers can see. The store
+    /// keeps it while it has any replies at all, but no one is shown it.
+    pub fn is_hidden_tombstone(&self) -> bool {
+        self.is_deleted() && self.reply_count == 0
+    }
+
+    /// The rendered body, rendering it now if it was never stored.
+    pub fn html(&self) -> Cow<'_, str> {
+        match &self.content_html {
+            Some(html) => Cow::Borrowed(html),
+            None => Cow::Owned(markdown::render(&self.content, Flavor::Comment)),
+        }
+    }
+
+    pub fn cursor_position(&self) -> CursorPosition {
+        CursorPosition {
+            created_at: self.created_at,
+            id: self.id,
+        }
+    }
+}
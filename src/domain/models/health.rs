@@ -0,0 +1,55 @@
+/// Connection pool occupancy at the moment of a readiness check.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStats {
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub max_size: u32,
+    pub in_flight: usize,
+    pub max_concurrency: usize,
+}
+
+impl PoolStats {
+    /// Every executor slot is taken, so new queries have to queue.
+    pub fn saturated(&self) -> bool {
+        self.max_concurrency > 0 && self.in_flight >= self.max_concurrency
+    }
+}
+
+/// Outcome of probing the database. `pending_migrations` is `None` when the
+/// database could not be reached to ask.
+///
+/// A reachable database whose pool is saturated still counts as up: the
+/// probe has a connection of its own, and requests over capacity are shed
+/// one by one rather than taking the whole instance out of rotation.
+#[derive(Debug, Clone)]
+pub struct DatabaseCheck {
+    pub reachable: bool,
+    pub latency_ms: u64,
+    pub pending_migrations: Option<Vec<String>>,
+    pub pool: PoolStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadinessStatus {
+    Ready,
+    /// A dependency is down or the schema is behind the binary.
+    Degraded,
+    /// Shutdown has begun; traffic should be routed elsewhere.
+    Draining,
+}
+
+impl ReadinessStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReadinessStatus::Ready => "ready",
+            ReadinessStatus::Degraded => "degraded",
+            ReadinessStatus::Draining => "draining",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReadinessReport {
+    pub status: ReadinessStatus,
+    pub database: DatabaseCheck,
+}
@@ -0,0 +1,150 @@
+//! Rendering of post and comment bodies, which are written in CommonMark.
+//!
+//! Bodies are rendered when they are saved and the HTML is stored next to
+//! the source, so reads never parse Markdown. Everything the parser emits
+//! goes through a sanitizer first; raw HTML in a post is kept only as far
+//! as the sanitizer allows.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use ammonia::Builder;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd, html};
+
+use crate::domain::models::slug;
+
+/// Which Markdown features a body may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    /// Tables, footnotes, images and raw HTML, with an anchor on every
+    /// heading.
+    Post,
+    /// Tables but no footnotes or images; raw HTML is shown as text. Links
+    /// are marked `nofollow`.
+    Comment,
+}
+
+static POST_SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = sanitizer();
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, &["id"]);
+    }
+    builder
+        .add_tag_attributes("div", &["id"])
+        .add_allowed_classes("div", &["footnote-definition"])
+        .add_allowed_classes("sup", &["footnote-reference", "footnote-definition-label"]);
+    builder
+});
+
+static COMMENT_SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = sanitizer();
+    builder
+        .rm_tags(&["img"])
+        .link_rel(Some("nofollow noopener noreferrer"));
+    builder
+});
+
+/// `source` as sanitized HTML.
+pub fn render(source: &str, flavor: Flavor) -> String {
+    let mut options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    if flavor == Flavor::Post {
+        options |= Options::ENABLE_FOOTNOTES;
+    }
+    let events = Parser::new_ext(source, options);
+
+    let mut html = String::with_capacity(source.len() * 3 / 2);
+    let sanitizer = match flavor {
+        Flavor::Post => {
+            html::push_html(&mut html, anchored(events).into_iter());
+            &POST_SANITIZER
+        }
+        Flavor::Comment => {
+            html::push_html(&mut html, events.filter_map(restricted));
+            &COMMENT_SANITIZER
+        }
+    };
+    sanitizer.clean(&html).to_string()
+}
+
+/// What both flavors allow beyond ammonia's defaults: language classes on
+/// code blocks and column alignment in tables.
+fn sanitizer() -> Builder<'static> {
+    let mut builder = Builder::default();
+    builder
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("th", &["style"])
+        .add_tag_attributes("td", &["style"])
+        .filter_style_properties(HashSet::from(["text-align"]))
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("code", "class") => is_language_class(value).then_some(Cow::Borrowed(value)),
+            _ => Some(Cow::Borrowed(value)),
+        });
+    builder
+}
+
+/// A `language-*` class as the parser writes for fenced code, which
+/// syntax highlighters pick up on the client.
+fn is_language_class(class: &str) -> bool {
+    class.strip_prefix("language-").is_some_and(|language| {
+        !language.is_empty()
+            && language
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '#' | '.'))
+    })
+}
+
+/// `events` with an `id` on every heading, made from its text and unique
+/// within the document. Footnote ids get a prefix so they cannot take a
+/// heading's.
+fn anchored<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut events: Vec<Event<'a>> = events
+        .map(|event| match event {
+            Event::FootnoteReference(name) => Event::FootnoteReference(footnote_id(&name)),
+            Event::Start(Tag::FootnoteDefinition(name)) => {
+                Event::Start(Tag::FootnoteDefinition(footnote_id(&name)))
+            }
+            event => event,
+        })
+        .collect();
+
+    let mut taken = HashSet::new();
+    for i in 0..events.len() {
+        if !matches!(events[i], Event::Start(Tag::Heading { .. })) {
+            continue;
+        }
+
+        let text: String = events[i + 1..]
+            .iter()
+            .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_))))
+            .filter_map(|event| match event {
+                Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                _ => None,
+            })
+            .collect();
+        let base = slug::slugify(&text);
+        let anchor = (1..)
+            .map(|n| slug::candidate(&base, n))
+            .find(|anchor| !taken.contains(anchor))
+            .expect("candidates are unbounded");
+        taken.insert(anchor.clone());
+
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+            *id = Some(anchor.into());
+        }
+    }
+    events
+}
+
+fn footnote_id(name: &str) -> CowStr<'static> {
+    format!("fn-{}", name).into()
+}
+
+/// Drops images, keeping their alt text, and turns raw HTML into text.
+fn restricted(event: Event<'_>) -> Option<Event<'_>> {
+    match event {
+        Event::Start(Tag::Image { .. }) | Event::End(TagEnd::Image) => None,
+        Event::Html(html) | Event::InlineHtml(html) => Some(Event::Text(html)),
+        event => Some(event),
+    }
+}
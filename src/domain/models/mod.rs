@@ -1,3 +1,13 @@
+pub mod auth;
+pub mod category;
 pub mod comment;
+pub mod health;
+pub mod markdown;
+pub mod pagination;
 pub mod post;
+pub mod post_revision;
+pub mod refresh_token;
+pub mod search;
+pub mod slug;
+pub mod tag;
 pub mod user;
@@ -1,9 +1,21 @@
 use chrono::NaiveDateTime;
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use diesel::{
+    AsChangeset, AsExpression, FromSqlRow, Identifiable, Insertable, Queryable, Selectable,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
 use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt, io::Write, str::FromStr};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::domain::models::{
+    markdown::{self, Flavor},
+    pagination::{PageRequest, SortDirection},
+    tag::Tag,
+};
 use crate::infrastructure::database::schema::posts;
 
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
@@ -13,9 +25,113 @@ pub struct Post {
     pub title: String,
     pub content: String,
     pub author_id: Uuid,
-    pub published: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub slug: String,
+    pub status: PostStatus,
+    /// When a scheduled post goes live; set only while `status` is
+    /// `Scheduled`.
+    pub publish_at: Option<NaiveDateTime>,
+    /// When the post last went live. Kept through archiving, cleared when it
+    /// returns to draft.
+    pub published_at: Option<NaiveDateTime>,
+    pub category_id: Option<Uuid>,
+    /// `content` as HTML, rendered when the post was saved. `None` for posts
+    /// last saved before rendering existed; see [`Post::html`].
+    pub content_html: Option<String>,
+}
+
+impl Post {
+    /// The rendered body, rendering it now if it was never stored.
+    pub fn html(&self) -> Cow<'_, str> {
+        match &self.content_html {
+            Some(html) => Cow::Borrowed(html),
+            None => Cow::Owned(markdown::render(&self.content, Flavor::Post)),
+        }
+    }
+}
+
+/// A post with the tags it carries, ordered by name.
+#[derive(Debug, Clone)]
+pub struct TaggedPost {
+    pub post: Post,
+    pub tags: Vec<Tag>,
+}
+
+/// Where a post is in its lifecycle. Only `Published` posts are public.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum PostStatus {
+    Draft,
+    InReview,
+    Scheduled,
+    Published,
+    Archived,
+}
+
+impl PostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::InReview => "in_review",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Published => "published",
+            PostStatus::Archived => "archived",
+        }
+    }
+
+    /// Whether a post may move from `self` to `next`. Rescheduling a
+    /// scheduled post is the only transition to the same state.
+    pub fn can_transition_to(self, next: PostStatus) -> bool {
+        use PostStatus::*;
+
+        matches!(
+            (self, next),
+            (Draft, InReview | Scheduled | Published)
+                | (InReview, Draft | Scheduled | Published)
+                | (Scheduled, Draft | Scheduled | Published)
+                | (Published, Draft | Archived)
+                | (Archived, Draft | Published)
+        )
+    }
+}
+
+impl fmt::Display for PostStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PostStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "draft" => Ok(PostStatus::Draft),
+            "in_review" => Ok(PostStatus::InReview),
+            "scheduled" => Ok(PostStatus::Scheduled),
+            "published" => Ok(PostStatus::Published),
+            "archived" => Ok(PostStatus::Archived),
+            other => Err(format!("unknown post status: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for PostStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for PostStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        value.parse().map_err(Into::into)
+    }
 }
 
 #[derive(Debug, Insertable, Deserialize)]
@@ -24,7 +140,12 @@ pub struct NewPost {
     pub title: String,
     pub content: String,
     pub author_id: Uuid,
-    pub published: bool,
+    pub slug: String,
+    pub status: PostStatus,
+    pub publish_at: Option<NaiveDateTime>,
+    pub published_at: Option<NaiveDateTime>,
+    pub category_id: Option<Uuid>,
+    pub content_html: Option<String>,
 }
 
 #[derive(Debug, AsChangeset, Deserialize)]
@@ -32,9 +153,14 @@ pub struct NewPost {
 pub struct UpdatePostData {
     pub title: Option<String>,
     pub content: Option<String>,
-    pub published: Option<bool>,
     #[diesel(column_name = "updated_at")]
     pub updated_at: Option<NaiveDateTime>,
+    pub slug: Option<String>,
+    pub status: Option<PostStatus>,
+    pub publish_at: Option<Option<NaiveDateTime>>,
+    pub published_at: Option<Option<NaiveDateTime>>,
+    pub category_id: Option<Option<Uuid>>,
+    pub content_html: Option<Option<String>>,
 }
 
 // Your existing CreatePost and UpdatePost structs remain the same
@@ -44,7 +170,9 @@ pub struct CreatePost {
     pub title: String,
     #[validate(length(min = 1))]
     pub content: String,
-    pub author_id: Uuid,
+    pub category_id: Option<Uuid>,
+    /// Slugs of existing tags.
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -53,5 +181,45 @@ pub struct UpdatePost {
     pub title: Option<String>,
     #[validate(length(min = 1))]
     pub content: Option<String>,
-    pub published: Option<bool>,
+    /// `Some(None)` takes the post out of its category.
+    pub category_id: Option<Option<Uuid>>,
+    /// Replaces the post's tags when present.
+    pub tags: Option<Vec<String>>,
+}
+
+/// A requested lifecycle transition. `publish_at` is required when
+/// scheduling and rejected otherwise.
+#[derive(Debug, Deserialize)]
+pub struct ChangePostStatus {
+    pub status: PostStatus,
+    pub publish_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    PublishedAt,
+    Title,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PostFilter {
+    pub status: Option<PostStatus>,
+    pub author_id: Option<Uuid>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub tag_id: Option<Uuid>,
+    /// Posts filed under any of these categories.
+    pub category_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PostQuery {
+    pub filter: PostFilter,
+    pub sort: PostSortField,
+    pub direction: SortDirection,
+    pub page: PageRequest,
 }
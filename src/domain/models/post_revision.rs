@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use uuid::Uuid;
+
+use crate::domain::models::post::{Post, PostStatus};
+use crate::infrastructure::database::schema::post_revisions;
+
+/// Unchanged lines kept around each change in a diff hunk.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A post as it was after one change. Revisions are numbered from 1 per
+/// post and never modified.
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = post_revisions)]
+pub struct PostRevision {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub revision: i32,
+    /// Who made the change; `None` for the scheduler or a deleted user.
+    pub editor_id: Option<Uuid>,
+    pub title: String,
+    pub content: String,
+    pub status: PostStatus,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = post_revisions)]
+pub struct NewPostRevision {
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub editor_id: Option<Uuid>,
+    pub title: String,
+    pub content: String,
+    pub status: PostStatus,
+}
+
+impl NewPostRevision {
+    pub fn of(post: &Post, revision: i32, editor_id: Option<Uuid>) -> Self {
+        Self {
+            post_id: post.id,
+            revision,
+            editor_id,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            status: post.status,
+        }
+    }
+}
+
+/// What changed between two revisions of a post.
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub title: Vec<DiffLine>,
+    pub content: Vec<DiffHunk>,
+    pub status: Option<StatusChange>,
+}
+
+/// A run of changed lines with some surrounding context, like a hunk of a
+/// unified diff. Line numbers start at 1.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub op: LineOp,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChange {
+    pub from: PostStatus,
+    pub to: PostStatus,
+}
+
+impl PostRevision {
+    /// Line-level changes from `self` to `other`.
+    pub fn diff(&self, other: &PostRevision) -> RevisionDiff {
+        let (old_content, new_content) = (terminated(&self.content), terminated(&other.content));
+        let title = TextDiff::from_lines(&self.title, &other.title);
+        let content = TextDiff::from_lines(&old_content, &new_content);
+
+        RevisionDiff {
+            from: self.revision,
+            to: other.revision,
+            title: title.iter_all_changes().map(diff_line).collect(),
+            content: content
+                .grouped_ops(DIFF_CONTEXT_LINES)
+                .iter()
+                .map(|group| {
+                    let (first, last) = (&group[0], &group[group.len() - 1]);
+                    let old = first.old_range().start..last.old_range().end;
+                    let new = first.new_range().start..last.new_range().end;
+                    DiffHunk {
+                        old_start: old.start + 1,
+                        old_lines: old.len(),
+                        new_start: new.start + 1,
+                        new_lines: new.len(),
+                        lines: group
+                            .iter()
+                            .flat_map(|op| content.iter_changes(op))
+                            .map(diff_line)
+                            .collect(),
+                    }
+                })
+                .collect(),
+            status: (self.status != other.status).then_some(StatusChange {
+                from: self.status,
+                to: other.status,
+            }),
+        }
+    }
+}
+
+/// `text` ending in a newline, so appending a line does not also show the
+/// previous last line as changed.
+fn terminated(text: &str) -> Cow<'_, str> {
+    if text.is_empty() || text.ends_with('\n') {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{}\n", text))
+    }
+}
+
+fn diff_line(change: similar::Change<&str>) -> DiffLine {
+    DiffLine {
+        op: match change.tag() {
+            ChangeTag::Equal => LineOp::Equal,
+            ChangeTag::Insert => LineOp::Insert,
+            ChangeTag::Delete => LineOp::Delete,
+        },
+        text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+    }
+}
@@ -0,0 +1,265 @@
+//! Full-text search over published posts and the comments on them.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::pagination::PageRequest;
+
+/// Brackets a matched word in a snippet before it is escaped; see
+/// [`highlight`].
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_STOP: char = '\u{3}';
+
+/// What a search looks through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    /// Titles and bodies of published posts.
+    #[default]
+    Posts,
+    /// Comments on published posts.
+    Comments,
+}
+
+/// A search as it was asked for.
+#[derive(Debug, Validate)]
+pub struct Search {
+    #[validate(length(min = 1, max = 200))]
+    pub q: String,
+    pub scope: SearchScope,
+    pub author_id: Option<Uuid>,
+    /// Slug of a tag the post carries; for comments, the post they are on.
+    pub tag: Option<String>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub page: PageRequest,
+}
+
+/// A search ready for a repository, with `q` parsed and the tag resolved.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub terms: SearchTerms,
+    pub scope: SearchScope,
+    pub author_id: Option<Uuid>,
+    pub tag_id: Option<Uuid>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub page: PageRequest,
+}
+
+/// A post or comment that matched, best matches first.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// The post, or the comment when searching comments.
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub post_slug: String,
+    pub post_title: String,
+    pub author_id: Uuid,
+    /// Text around the matches as HTML, matched words in `<mark>`.
+    pub snippet: String,
+    pub rank: f32,
+    pub created_at: NaiveDateTime,
+}
+
+/// The words a search looks for. Every group has to match, and a group
+/// matches when any one of its terms does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTerms {
+    pub groups: Vec<Vec<SearchTerm>>,
+}
+
+/// A word, or a phrase of words that must appear in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTerm {
+    /// Lowercase and alphanumeric only.
+    pub words: Vec<String>,
+    /// The last word may be the start of a longer one.
+    pub prefix: bool,
+    /// Matches what does not contain the term.
+    pub negated: bool,
+}
+
+impl SearchTerms {
+    /// Parses web-search style input: `"quoted phrases"`, `word*` for
+    /// prefixes, `-word` to exclude and `or` between alternatives; anything
+    /// else is a word that must appear. `None` when nothing is left to look
+    /// for, since a search that only excludes would match everything.
+    pub fn parse(q: &str) -> Option<Self> {
+        let mut groups: Vec<Vec<SearchTerm>> = Vec::new();
+        let mut alternative = false;
+
+        for token in tokens(q) {
+            if !token.quoted && token.text.eq_ignore_ascii_case("or") {
+                alternative = !groups.is_empty();
+                continue;
+            }
+            let Some(term) = SearchTerm::from_token(&token) else {
+                continue;
+            };
+
+            // An excluded term cannot be an alternative, nor have one.
+            let joins_previous = alternative
+                && !term.negated
+                && groups
+                    .last()
+                    .is_some_and(|group| group.iter().all(|term| !term.negated));
+            match groups.last_mut() {
+                Some(group) if joins_previous => group.push(term),
+                _ => groups.push(vec![term]),
+            }
+            alternative = false;
+        }
+
+        groups
+            .iter()
+            .any(|group| group.iter().all(|term| !term.negated))
+            .then_some(Self { groups })
+    }
+
+    /// Whether text split into `fields` by [`words`] matches. Phrases do not
+    /// run from one field into the next.
+    pub fn matches(&self, fields: &[&[String]]) -> bool {
+        self.groups.iter().all(|group| {
+            group.iter().any(|term| {
+                let found = fields
+                    .iter()
+                    .any(|words| !term.occurrences(words).is_empty());
+                found != term.negated
+            })
+        })
+    }
+
+    /// Terms a match is looked for, as opposed to excluded ones.
+    pub fn wanted(&self) -> impl Iterator<Item = &SearchTerm> {
+        self.groups.iter().flatten().filter(|term| !term.negated)
+    }
+}
+
+impl SearchTerm {
+    fn from_token(token: &Token) -> Option<Self> {
+        let mut text = token.text.as_str();
+        let negated = text.starts_with('-') && !token.quoted;
+        if negated {
+            text = &text[1..];
+        }
+        let prefix = !token.quoted && text.ends_with('*');
+
+        let words = words(text);
+        (!words.is_empty()).then_some(Self {
+            words,
+            prefix,
+            negated: negated || token.negated,
+        })
+    }
+
+    /// Where in `words` the term starts, every time it appears.
+    pub fn occurrences(&self, words: &[String]) -> Vec<usize> {
+        let len = self.words.len();
+        if words.len() < len {
+            return Vec::new();
+        }
+        (0..=words.len() - len)
+            .filter(|&start| {
+                self.words.iter().enumerate().all(|(i, wanted)| {
+                    let word = &words[start + i];
+                    if self.prefix && i == len - 1 {
+                        word.starts_with(wanted.as_str())
+                    } else {
+                        word == wanted
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+struct Token {
+    text: String,
+    quoted: bool,
+    /// A quoted phrase preceded by `-`.
+    negated: bool,
+}
+
+fn tokens(q: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = q.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let negated = c == '-';
+        if negated {
+            chars.next();
+        }
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let text = chars.by_ref().take_while(|&c| c != '"').collect();
+            tokens.push(Token {
+                text,
+                quoted: true,
+                negated,
+            });
+        } else {
+            let mut text = String::from(if negated { "-" } else { "" });
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+                negated: false,
+            });
+        }
+    }
+    tokens
+}
+
+/// `text` as lowercase words, split on anything that is not a letter or a
+/// digit.
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// `fragment` as HTML: the text is escaped and each run between
+/// [`HIGHLIGHT_START`] and [`HIGHLIGHT_STOP`] is wrapped in `<mark>`. Stray
+/// markers, which the text itself may contain, never unbalance the tags.
+pub fn highlight(fragment: &str) -> String {
+    let mut html = String::with_capacity(fragment.len() + 32);
+    let mut marked = false;
+    for c in fragment.chars() {
+        match c {
+            HIGHLIGHT_START if !marked => {
+                html.push_str("<mark>");
+                marked = true;
+            }
+            HIGHLIGHT_STOP if marked => {
+                html.push_str("</mark>");
+                marked = false;
+            }
+            HIGHLIGHT_START | HIGHLIGHT_STOP => {}
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    if marked {
+        html.push_str("</mark>");
+    }
+    html
+}
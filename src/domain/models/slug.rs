@@ -0,0 +1,141 @@
+//! Human-readable identifiers derived from post titles and tag and category
+//! names.
+
+use std::collections::HashSet;
+
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+/// Longest slug [`slugify`] returns. The column leaves room beyond this for
+/// a collision suffix.
+pub const MAX_SLUG_LENGTH: usize = 100;
+
+/// How many times a write is tried with a fresh slug after losing the one it
+/// picked to a concurrent write. Each round settles at least one of the
+/// writes racing for a name, so this is how many can race at once.
+pub const CLAIM_ATTEMPTS: u32 = 10;
+
+/// Used when nothing in a title survives transliteration.
+const FALLBACK_SLUG: &str = "post";
+
+/// Lowercase ASCII words joined by hyphens. Accents are stripped and a few
+/// common non-ASCII letters (including Cyrillic) are spelled out in Latin;
+/// anything else separates words.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    let mut separate = false;
+
+    for c in text.nfkd().filter(|c| !is_combining_mark(*c)) {
+        if matches!(c, '\'' | '\u{2019}') {
+            continue;
+        }
+
+        for c in c.to_lowercase() {
+            let mut buf = [0; 4];
+            let spelled: &str = if c.is_ascii_alphanumeric() {
+                c.encode_utf8(&mut buf)
+            } else if let Some(spelled) = transliterate(c) {
+                spelled
+            } else {
+                separate = true;
+                continue;
+            };
+
+            if spelled.is_empty() {
+                continue;
+            }
+            if separate && !slug.is_empty() {
+                slug.push('-');
+            }
+            separate = false;
+            slug.push_str(spelled);
+        }
+    }
+
+    if slug.len() > MAX_SLUG_LENGTH {
+        slug.truncate(MAX_SLUG_LENGTH);
+        slug.truncate(slug.trim_end_matches('-').len());
+    }
+    if slug.is_empty() {
+        return FALLBACK_SLUG.to_string();
+    }
+    slug
+}
+
+/// The `n`th candidate for `base`: `base` itself, then `base-2`, `base-3`...
+pub fn candidate(base: &str, n: u32) -> String {
+    if n <= 1 {
+        base.to_string()
+    } else {
+        format!("{}-{}", base, n)
+    }
+}
+
+/// The first candidate for `base` that is not among `taken`.
+pub fn first_free<'a>(base: &str, taken: impl IntoIterator<Item = &'a str>) -> String {
+    let taken: HashSet<u32> = taken
+        .into_iter()
+        .filter_map(|slug| candidate_number(base, slug))
+        .collect();
+    let n = (1..).find(|n| !taken.contains(n)).unwrap_or(1);
+    candidate(base, n)
+}
+
+/// Where `slug` falls in `base`'s candidate series: 1 for `base` itself,
+/// `n` for `base-n`, `None` for anything else.
+pub fn candidate_number(base: &str, slug: &str) -> Option<u32> {
+    if slug == base {
+        return Some(1);
+    }
+    let suffix = slug.strip_prefix(base)?.strip_prefix('-')?;
+    if suffix.starts_with('0') || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    suffix.parse().ok().filter(|n| *n >= 2)
+}
+
+/// Latin spellings for lowercase letters that have no ASCII decomposition.
+/// `Some("")` means the letter is dropped without splitting the word.
+fn transliterate(c: char) -> Option<&'static str> {
+    let spelled = match c {
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        'ø' => "o",
+        'đ' | 'ð' => "d",
+        'ł' => "l",
+        'þ' => "th",
+        'ı' => "i",
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' | 'ґ' => "g",
+        'д' => "d",
+        'е' | 'э' => "e",
+        'є' => "ye",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'ю' => "yu",
+        'я' => "ya",
+        _ => return None,
+    };
+    Some(spelled)
+}
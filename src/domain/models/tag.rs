@@ -0,0 +1,45 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::infrastructure::database::schema::tags;
+
+/// A label posts can carry any number of. Tags are addressed by slug, which
+/// follows the name.
+#[derive(Debug, Clone, PartialEq, Eq, Queryable, Identifiable, Selectable, Serialize)]
+#[diesel(table_name = tags)]
+pub struct Tag {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = tags)]
+pub struct NewTag {
+    pub name: String,
+    pub slug: String,
+}
+
+/// A tag and how many published posts carry it.
+#[derive(Debug, Clone)]
+pub struct TagCount {
+    pub tag: Tag,
+    pub post_count: i64,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct CreateTag {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct UpdateTag {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+}
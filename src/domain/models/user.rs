@@ -3,13 +3,24 @@ use argon2::{
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
 };
 use chrono::NaiveDateTime;
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use diesel::{
+    AsChangeset, AsExpression, FromSqlRow, Identifiable, Insertable, Queryable, Selectable,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
+use std::{fmt, io::Write, str::FromStr};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::{infrastructure::database::schema::users, shared::error::ApiError};
+use crate::{
+    domain::models::pagination::{PageRequest, SortDirection},
+    infrastructure::database::schema::users,
+    shared::error::ApiError,
+};
 
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable, Serialize)]
 #[diesel(table_name = users)]
@@ -20,6 +31,75 @@ pub struct User {
     pub password_hash: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub role: Role,
+}
+
+/// Roles are ordered by privilege, so `role >= Role::Editor` reads naturally.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Reader,
+    Author,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Author => "author",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "reader" => Ok(Role::Reader),
+            "author" => Ok(Role::Author),
+            "editor" => Ok(Role::Editor),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for Role {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for Role {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        value.parse().map_err(Into::into)
+    }
 }
 
 #[derive(Debug, Insertable, Deserialize)]
@@ -28,6 +108,7 @@ pub struct NewUser {
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    pub role: Role,
 }
 
 #[derive(Debug, AsChangeset, Deserialize)]
@@ -36,6 +117,7 @@ pub struct UpdateUserData {
     pub username: Option<String>,
     pub email: Option<String>,
     pub password_hash: Option<String>,
+    pub role: Option<Role>,
     #[diesel(column_name = "updated_at")]
     pub updated_at: Option<NaiveDateTime>,
 }
@@ -58,6 +140,31 @@ pub struct UpdateUser {
     pub email: Option<String>,
     #[validate(length(min = 8))]
     pub password: Option<String>,
+    pub role: Option<Role>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    Username,
+    Email,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    pub role: Option<Role>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UserQuery {
+    pub filter: UserFilter,
+    pub sort: UserSortField,
+    pub direction: SortDirection,
+    pub page: PageRequest,
 }
 
 impl User {
@@ -76,6 +183,7 @@ impl User {
             password_hash,
             created_at: chrono::Local::now().naive_local(),
             updated_at: chrono::Local::now().naive_local(),
+            role: Role::Author,
         })
     }
 
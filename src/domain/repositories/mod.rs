@@ -1,22 +1,118 @@
+use std::collections::HashMap;
+use std::future::Future;
+
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 
-use crate::domain::models::{comment::Comment, post::Post, user::User};
+use crate::domain::models::{
+    category::Category,
+    comment::{
+        Comment, CommentQuery, CommentStatus, CommentStreamQuery, ModerationOverrides,
+        ModerationQueueQuery,
+    },
+    health::DatabaseCheck,
+    pagination::{CursorPage, Page, PageRequest},
+    post::{Post, PostQuery},
+    post_revision::PostRevision,
+    refresh_token::RefreshToken,
+    search::{SearchHit, SearchQuery},
+    tag::Tag,
+    user::{User, UserQuery},
+};
 use crate::shared::error::ApiError;
 
 #[async_trait]
 pub trait PostRepository: Send + Sync {
     async fn find(&self, id: Uuid) -> Result<Post, ApiError>;
-    async fn find_all(&self) -> Result<Vec<Post>, ApiError>;
+    /// As [`PostRepository::find`], but inside a unit of work no other
+    /// transaction may change the post until this one ends.
+    async fn find_for_update(&self, id: Uuid) -> Result<Post, ApiError>;
+    /// The post whose current or any former slug is `slug`.
+    async fn find_by_slug(&self, slug: &str) -> Result<Post, ApiError>;
+    /// Every slug in `base`'s candidate series (`base`, `base-2`, ...) that a
+    /// post has or once had, with that post.
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError>;
+    async fn find_all(&self, query: &PostQuery) -> Result<Page<Post>, ApiError>;
     async fn create(&self, post: Post) -> Result<Post, ApiError>;
+    /// Saves `post`. A changed slug moves the old one into the post's slug
+    /// history.
     async fn update(&self, id: Uuid, post: Post) -> Result<Post, ApiError>;
     async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    /// Moves every post by `from` to `to`, returning how many moved.
+    async fn reassign_author(&self, from: Uuid, to: Uuid) -> Result<u64, ApiError>;
+    /// Publishes every scheduled post whose `publish_at` is at or before
+    /// `now`, stamping `published_at` with the scheduled time. Returns the
+    /// posts it published.
+    async fn publish_due(&self, now: NaiveDateTime) -> Result<Vec<Post>, ApiError>;
+}
+
+#[async_trait]
+pub trait PostRevisionRepository: Send + Sync {
+    /// Appends a snapshot of `post` as its next revision.
+    async fn record(&self, post: &Post, editor_id: Option<Uuid>) -> Result<PostRevision, ApiError>;
+    async fn find(&self, post_id: Uuid, revision: i32) -> Result<PostRevision, ApiError>;
+    /// A post's revisions, newest first.
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError>;
+}
+
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    async fn find_by_slug(&self, slug: &str) -> Result<Tag, ApiError>;
+    /// The tags with any of `slugs`. Unknown slugs are skipped.
+    async fn find_by_slugs(&self, slugs: &[String]) -> Result<Vec<Tag>, ApiError>;
+    /// Every slug in `base`'s candidate series (`base`, `base-2`, ...) that a
+    /// tag has, with that tag.
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError>;
+    /// All tags, ordered by name.
+    async fn find_all(&self, page: PageRequest) -> Result<Page<Tag>, ApiError>;
+    async fn create(&self, tag: Tag) -> Result<Tag, ApiError>;
+    async fn update(&self, id: Uuid, tag: Tag) -> Result<Tag, ApiError>;
+    /// Deletes the tag and takes it off every post.
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    /// How many published posts carry each of `tag_ids`. Tags on none are
+    /// left out.
+    async fn count_published(&self, tag_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>, ApiError>;
+    /// The tags on each of `post_ids`, ordered by name. Posts without tags
+    /// are left out.
+    async fn find_by_posts(&self, post_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Tag>>, ApiError>;
+    /// Replaces the tags on a post.
+    async fn set_for_post(&self, post_id: Uuid, tag_ids: &[Uuid]) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+pub trait CategoryRepository: Send + Sync {
+    async fn find(&self, id: Uuid) -> Result<Category, ApiError>;
+    async fn find_by_slug(&self, slug: &str) -> Result<Category, ApiError>;
+    /// Every category; there are few enough to build the tree in memory.
+    async fn find_all(&self) -> Result<Vec<Category>, ApiError>;
+    /// As [`CategoryRepository::find_all`], but inside a unit of work no
+    /// other transaction may change any of them until this one ends.
+    async fn find_all_for_update(&self) -> Result<Vec<Category>, ApiError>;
+    async fn create(&self, category: Category) -> Result<Category, ApiError>;
+    async fn update(&self, id: Uuid, category: Category) -> Result<Category, ApiError>;
+    /// Deletes the category. Its subcategories move up to its parent and
+    /// its posts are left without one.
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    /// How many published posts are filed directly under each category.
+    /// Categories with none are left out.
+    async fn count_published(&self) -> Result<HashMap<Uuid, i64>, ApiError>;
+}
+
+#[async_trait]
+pub trait SearchRepository: Send + Sync {
+    /// Published posts, or comments on them, that match, best first.
+    async fn search(&self, query: &SearchQuery) -> Result<Page<SearchHit>, ApiError>;
 }
 
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn find(&self, id: Uuid) -> Result<User, ApiError>;
-    async fn find_all(&self) -> Result<Vec<User>, ApiError>;
+    async fn find_all(&self, query: &UserQuery) -> Result<Page<User>, ApiError>;
     async fn find_by_email(&self, email: &str) -> Result<User, ApiError>;
     async fn create(&self, user: User) -> Result<User, ApiError>;
     async fn update(&self, id: Uuid, user: User) -> Result<User, ApiError>;
@@ -26,8 +122,103 @@ pub trait UserRepository: Send + Sync {
 #[async_trait]
 pub trait CommentRepository: Send + Sync {
     async fn find(&self, id: Uuid) -> Result<Comment, ApiError>;
-    async fn find_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, ApiError>;
+    /// Tombstones are left out once none of their replies are public.
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentQuery,
+    ) -> Result<Page<Comment>, ApiError>;
+    async fn stream_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError>;
+    /// A page of a post's top-level comments with `status`, oldest first.
+    /// Tombstones are included while they have public replies.
+    async fn find_top_level(
+        &self,
+        post_id: Uuid,
+        status: CommentStatus,
+        page: PageRequest,
+    ) -> Result<Page<Comment>, ApiError>;
+    /// Every reply with `status` beneath `parent_ids`, however deep,
+    /// tombstones included while they have public replies. Replies to one
+    /// left out are left out too.
+    async fn find_replies(
+        &self,
+        parent_ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError>;
+    async fn find_queue(&self, query: &ModerationQueueQuery) -> Result<Page<Comment>, ApiError>;
+    /// Whether the user has a comment that is approved.
+    async fn has_approved(&self, author_id: Uuid) -> Result<bool, ApiError>;
+    /// `parent_id` must name an existing comment. That it is on the same
+    /// post and not nested too deeply is left to the caller.
     async fn create(&self, comment: Comment) -> Result<Comment, ApiError>;
     async fn update(&self, id: Uuid, comment: Comment) -> Result<Comment, ApiError>;
+    /// Removes the comment, or turns it into a tombstone if it has replies,
+    /// held ones included. Tombstones left without replies are removed with
+    /// it, up the thread.
     async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    /// Moves the comments among `ids` that exist and are not tombstones to
+    /// `status`, returning them oldest first.
+    async fn set_status(
+        &self,
+        ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError>;
+    /// The post's overrides, all `None` if it has none. `NotFound` if the
+    /// post does not exist.
+    async fn find_moderation(&self, post_id: Uuid) -> Result<ModerationOverrides, ApiError>;
+    async fn save_moderation(
+        &self,
+        overrides: ModerationOverrides,
+    ) -> Result<ModerationOverrides, ApiError>;
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    async fn find(&self, id: Uuid) -> Result<RefreshToken, ApiError>;
+    async fn create(&self, token: RefreshToken) -> Result<RefreshToken, ApiError>;
+    async fn revoke(&self, id: Uuid, replaced_by: Option<Uuid>) -> Result<bool, ApiError>;
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+pub trait HealthRepository: Send + Sync {
+    /// Checks out a connection and runs a trivial query, without queueing
+    /// behind request traffic. Never fails; an unreachable database is
+    /// reported in the result instead.
+    async fn check_database(&self) -> DatabaseCheck;
+}
+
+/// Repositories whose operations all take part in the same transaction.
+/// Clones are further handles on that transaction, not new ones.
+pub trait TransactionScope: Clone + Send + Sync + 'static {
+    type Users: UserRepository;
+    type Posts: PostRepository;
+    type PostRevisions: PostRevisionRepository;
+    type Tags: TagRepository;
+    type Categories: CategoryRepository;
+    type Comments: CommentRepository;
+
+    fn users(&self) -> &Self::Users;
+    fn posts(&self) -> &Self::Posts;
+    fn post_revisions(&self) -> &Self::PostRevisions;
+    fn tags(&self) -> &Self::Tags;
+    fn categories(&self) -> &Self::Categories;
+    fn comments(&self) -> &Self::Comments;
+}
+
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    type Scope: TransactionScope;
+
+    /// Runs `work` in a transaction that is committed if it returns `Ok` and
+    /// rolled back otherwise. The scope must not be used once `work` returns.
+    async fn transaction<F, Fut, T>(&self, work: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Self::Scope) -> Fut + Send,
+        Fut: Future<Output = Result<T, ApiError>> + Send,
+        T: Send;
 }
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::{
+        auth::{AuthSession, Claims, Credentials, TokenType},
+        refresh_token::RefreshToken,
+        user::User,
+    },
+    repositories::{RefreshTokenRepository, UserRepository},
+};
+use crate::shared::{config::AuthSettings, error::ApiError};
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+    pub access_token_ttl: Duration,
+    pub refresh_token_ttl: Duration,
+}
+
+impl From<&AuthSettings> for AuthConfig {
+    fn from(settings: &AuthSettings) -> Self {
+        Self {
+            jwt_secret: settings.jwt_secret.clone(),
+            access_token_ttl: Duration::from_secs(settings.access_token_ttl_secs),
+            refresh_token_ttl: Duration::from_secs(settings.refresh_token_ttl_secs),
+        }
+    }
+}
+
+/// Signs and verifies the JWTs handed out by the auth endpoints.
+#[derive(Clone)]
+pub struct TokenCodec {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    access_token_ttl: Duration,
+    refresh_token_ttl: Duration,
+}
+
+impl TokenCodec {
+    pub fn new(config: &AuthConfig) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            access_token_ttl: config.access_token_ttl,
+            refresh_token_ttl: config.refresh_token_ttl,
+        }
+    }
+
+    pub fn issue(&self, user: &User, jti: Uuid, typ: TokenType) -> Result<String, ApiError> {
+        let ttl = match typ {
+            TokenType::Access => self.access_token_ttl,
+            TokenType::Refresh => self.refresh_token_ttl,
+        };
+        let iat = chrono::Utc::now().timestamp();
+        let claims = Claims {
+            sub: user.id,
+            role: user.role,
+            jti,
+            typ,
+            iat,
+            exp: iat + ttl.as_secs() as i64,
+        };
+
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|_| ApiError::InternalServerError)
+    }
+
+    pub fn verify(&self, token: &str, expected: TokenType) -> Result<Claims, ApiError> {
+        let claims = decode::<Claims>(
+            token,
+            &self.decoding_key,
+            &Validation::new(Algorithm::HS256),
+        )
+        .map_err(|_| ApiError::Unauthorized)?
+        .claims;
+
+        if claims.typ != expected {
+            return Err(ApiError::Unauthorized);
+        }
+
+        Ok(claims)
+    }
+
+    pub fn access_token_ttl(&self) -> Duration {
+        self.access_token_ttl
+    }
+
+    pub fn refresh_token_ttl(&self) -> Duration {
+        self.refresh_token_ttl
+    }
+}
+
+#[async_trait]
+pub trait AuthService: Send + Sync {
+    async fn login(&self, credentials: Credentials) -> Result<AuthSession, ApiError>;
+    async fn refresh(&self, refresh_token: &str) -> Result<AuthSession, ApiError>;
+    async fn logout(&self, refresh_token: &str) -> Result<(), ApiError>;
+    fn token_codec(&self) -> TokenCodec;
+}
+
+#[derive(Clone)]
+pub struct AuthServiceImpl<U, T>
+where
+    U: UserRepository + Send + Sync + 'static,
+    T: RefreshTokenRepository + Send + Sync + 'static,
+{
+    user_repository: Arc<U>,
+    token_repository: Arc<T>,
+    codec: TokenCodec,
+}
+
+impl<U, T> AuthServiceImpl<U, T>
+where
+    U: UserRepository + Send + Sync + 'static,
+    T: RefreshTokenRepository + Send + Sync + 'static,
+{
+    pub fn new(user_repository: Arc<U>, token_repository: Arc<T>, codec: TokenCodec) -> Self {
+        Self {
+            user_repository,
+            token_repository,
+            codec,
+        }
+    }
+
+    async fn start_session(&self, user: User, refresh_id: Uuid) -> Result<AuthSession, ApiError> {
+        let now = chrono::Local::now().naive_local();
+        let refresh_ttl = self.codec.refresh_token_ttl();
+
+        self.token_repository
+            .create(RefreshToken {
+                id: refresh_id,
+                user_id: user.id,
+                expires_at: now
+                    + chrono::Duration::from_std(refresh_ttl)
+                        .map_err(|_| ApiError::InternalServerError)?,
+                revoked_at: None,
+                replaced_by: None,
+                created_at: now,
+            })
+            .await?;
+
+        let access_token = self.codec.issue(&user, Uuid::new_v4(), TokenType::Access)?;
+        let refresh_token = self.codec.issue(&user, refresh_id, TokenType::Refresh)?;
+
+        Ok(AuthSession {
+            user,
+            access_token,
+            access_token_expires_in: self.codec.access_token_ttl().as_secs() as i64,
+            refresh_token,
+            refresh_token_expires_in: refresh_ttl.as_secs() as i64,
+        })
+    }
+}
+
+#[async_trait]
+impl<U, T> AuthService for Arc<AuthServiceImpl<U, T>>
+where
+    U: UserRepository + Send + Sync + 'static,
+    T: RefreshTokenRepository + Send + Sync + 'static,
+{
+    async fn login(&self, credentials: Credentials) -> Result<AuthSession, ApiError> {
+        let user = match self.user_repository.find_by_email(&credentials.email).await {
+            Ok(user) => user,
+            Err(ApiError::NotFound) => return Err(ApiError::Unauthorized),
+            Err(e) => return Err(e),
+        };
+
+        if !user.verify_password(&credentials.password)? {
+            return Err(ApiError::Unauthorized);
+        }
+
+        self.start_session(user, Uuid::new_v4()).await
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<AuthSession, ApiError> {
+        let claims = self.codec.verify(refresh_token, TokenType::Refresh)?;
+
+        let stored = match self.token_repository.find(claims.jti).await {
+            Ok(token) => token,
+            Err(ApiError::NotFound) => return Err(ApiError::Unauthorized),
+            Err(e) => return Err(e),
+        };
+
+        if stored.user_id != claims.sub {
+            return Err(ApiError::Unauthorized);
+        }
+
+        // Rotation: the presented token is consumed here. If it had already been
+        // consumed, someone is replaying it, so the whole family is revoked.
+        let replacement_id = Uuid::new_v4();
+        let rotated = self
+            .token_repository
+            .revoke(stored.id, Some(replacement_id))
+            .await?;
+
+        if !rotated {
+            self.token_repository
+                .revoke_all_for_user(stored.user_id)
+                .await?;
+            return Err(ApiError::Unauthorized);
+        }
+
+        if !stored.is_active(chrono::Local::now().naive_local()) {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let user = match self.user_repository.find(stored.user_id).await {
+            Ok(user) => user,
+            Err(ApiError::NotFound) => return Err(ApiError::Unauthorized),
+            Err(e) => return Err(e),
+        };
+
+        self.start_session(user, replacement_id).await
+    }
+
+    async fn logout(&self, refresh_token: &str) -> Result<(), ApiError> {
+        let claims = self.codec.verify(refresh_token, TokenType::Refresh)?;
+        self.token_repository.revoke(claims.jti, None).await?;
+        Ok(())
+    }
+
+    fn token_codec(&self) -> TokenCodec {
+        self.codec.clone()
+    }
+}
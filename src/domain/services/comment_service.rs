@@ -4,59 +4,337 @@ use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::domain::{
-    models::comment::{Comment, CreateComment, UpdateComment},
-    repositories::CommentRepository,
+    models::{
+        auth::Actor,
+        comment::{
+            Comment, CommentNode, CommentQuery, CommentStatus, CommentStreamQuery, CreateComment,
+            MAX_REPLY_DEPTH, ModerateComments, ModerationOverrides, ModerationQueueQuery,
+            ModerationSettings, PostModeration, UpdateComment, UpdateModeration,
+        },
+        markdown::{self, Flavor},
+        pagination::{CursorPage, Page, PageRequest},
+        post::{Post, PostStatus},
+    },
+    repositories::{CommentRepository, PostRepository},
+    services::{policy, post_service},
 };
 use crate::shared::error::ApiError;
 
 #[async_trait]
 pub trait CommentService: Send + Sync {
-    async fn find(&self, id: Uuid) -> Result<Comment, ApiError>;
-    async fn find_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, ApiError>;
-    async fn create(&self, comment: CreateComment) -> Result<Comment, ApiError>;
-    async fn update(&self, id: Uuid, comment: UpdateComment) -> Result<Comment, ApiError>;
-    async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    /// Comments that are not approved are only found by their author and
+    /// moderators. Comments on a post the actor may not see are not found
+    /// at all, nor are tombstones with no public replies left.
+    async fn find(&self, actor: Option<&Actor>, id: Uuid) -> Result<Comment, ApiError>;
+    /// Approved comments only, whoever asks; moderators see the rest in
+    /// [`CommentService::queue`]. The post must be visible to the actor.
+    async fn find_by_post(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        query: CommentQuery,
+    ) -> Result<Page<Comment>, ApiError>;
+    /// Approved comments only, as for [`CommentService::find_by_post`].
+    async fn stream_by_post(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        query: CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError>;
+    /// A page of a post's approved top-level comments, each with every
+    /// approved reply beneath it. Deleted comments that still have replies
+    /// are left in place as tombstones.
+    async fn thread(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<CommentNode>, ApiError>;
+    /// Only published posts take comments. A reply must be to an approved
+    /// comment on the same post that is not deleted and not already at
+    /// [`MAX_REPLY_DEPTH`]. The comment is held for a moderator if the
+    /// post's moderation settings say so.
+    async fn create(&self, actor: &Actor, comment: CreateComment) -> Result<Comment, ApiError>;
+    /// New content goes back to the queue if the post's moderation settings
+    /// would hold it from the actor.
+    async fn update(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        comment: UpdateComment,
+    ) -> Result<Comment, ApiError>;
+    /// A comment with replies leaves a tombstone; see
+    /// [`CommentRepository::delete`].
+    async fn delete(&self, actor: &Actor, id: Uuid) -> Result<(), ApiError>;
+    async fn queue(
+        &self,
+        actor: &Actor,
+        query: ModerationQueueQuery,
+    ) -> Result<Page<Comment>, ApiError>;
+    /// Applies the decision to every listed comment that exists and is not
+    /// a tombstone, returning those.
+    async fn moderate(
+        &self,
+        actor: &Actor,
+        decision: ModerateComments,
+    ) -> Result<Vec<Comment>, ApiError>;
+    async fn moderation(&self, actor: &Actor, post_id: Uuid) -> Result<PostModeration, ApiError>;
+    async fn update_moderation(
+        &self,
+        actor: &Actor,
+        post_id: Uuid,
+        moderation: UpdateModeration,
+    ) -> Result<PostModeration, ApiError>;
 }
 
 #[derive(Clone)]
-pub struct CommentServiceImpl<R: CommentRepository + Send + Sync + 'static> {
+pub struct CommentServiceImpl<R, P>
+where
+    R: CommentRepository + Send + Sync + 'static,
+    P: PostRepository + Send + Sync + 'static,
+{
     repository: Arc<R>,
+    posts: Arc<P>,
+    moderation: ModerationSettings,
 }
 
-impl<R: CommentRepository + Send + Sync + 'static> CommentServiceImpl<R> {
-    pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+impl<R, P> CommentServiceImpl<R, P>
+where
+    R: CommentRepository + Send + Sync + 'static,
+    P: PostRepository + Send + Sync + 'static,
+{
+    /// `moderation` applies to posts without overrides of their own.
+    pub fn new(repository: Arc<R>, posts: Arc<P>, moderation: ModerationSettings) -> Self {
+        Self {
+            repository,
+            posts,
+            moderation,
+        }
+    }
+
+    /// The post comments are being read from, if `actor` may see it.
+    async fn visible_post(&self, actor: Option<&Actor>, post_id: Uuid) -> Result<Post, ApiError> {
+        let post = self.posts.find(post_id).await?;
+        post_service::ensure_visible(actor, post)
+    }
+
+    /// Checks that `actor` may comment on `post_id`. A post they cannot see
+    /// is reported as missing.
+    async fn ensure_open_for_comments(&self, actor: &Actor, post_id: Uuid) -> Result<(), ApiError> {
+        let post = match self.visible_post(Some(actor), post_id).await {
+            Err(ApiError::NotFound) => {
+                return Err(ApiError::InvalidReference {
+                    field: "post_id".to_string(),
+                });
+            }
+            result => result?,
+        };
+        if post.status != PostStatus::Published {
+            return Err(ApiError::invalid_field(
+                "post_id",
+                "published",
+                "only published posts take comments",
+            ));
+        }
+        Ok(())
+    }
+
+    fn post_moderation(&self, overrides: ModerationOverrides) -> PostModeration {
+        PostModeration {
+            effective: self.moderation.overridden_by(&overrides),
+            overrides,
+        }
+    }
+
+    /// The status a new comment by `actor` on `post_id` starts in.
+    async fn initial_status(
+        &self,
+        actor: &Actor,
+        post_id: Uuid,
+    ) -> Result<CommentStatus, ApiError> {
+        let overrides = match self.repository.find_moderation(post_id).await {
+            Err(ApiError::NotFound) => {
+                return Err(ApiError::InvalidReference {
+                    field: "post_id".to_string(),
+                });
+            }
+            result => result?,
+        };
+        let settings = self.moderation.overridden_by(&overrides);
+        if settings.trusts(actor.role) {
+            return Ok(CommentStatus::Approved);
+        }
+
+        let held = settings.require_approval
+            || (settings.hold_first_time && !self.repository.has_approved(actor.user_id).await?);
+        Ok(if held {
+            CommentStatus::Pending
+        } else {
+            CommentStatus::Approved
+        })
+    }
+
+    /// Checks that `parent_id` can be replied to on `post_id`.
+    async fn ensure_can_reply(&self, post_id: Uuid, parent_id: Uuid) -> Result<(), ApiError> {
+        // Replies to comments the public cannot see would give them away.
+        let parent = match self.repository.find(parent_id).await {
+            Ok(parent) if parent.status == CommentStatus::Approved => parent,
+            Ok(_) | Err(ApiError::NotFound) => {
+                return Err(ApiError::InvalidReference {
+                    field: "parent_id".to_string(),
+                });
+            }
+            Err(error) => return Err(error),
+        };
+        if parent.post_id != post_id {
+            return Err(ApiError::invalid_field(
+                "parent_id",
+                "same_post",
+                "must be a comment on the same post",
+            ));
+        }
+        if parent.is_deleted() {
+            return Err(ApiError::invalid_field(
+                "parent_id",
+                "deleted",
+                "cannot reply to a deleted comment",
+            ));
+        }
+
+        let mut depth = 0;
+        let mut ancestor = parent.parent_id;
+        while let Some(id) = ancestor {
+            depth += 1;
+            if depth >= MAX_REPLY_DEPTH {
+                return Err(ApiError::invalid_field(
+                    "parent_id",
+                    "depth",
+                    "replies are nested too deeply",
+                ));
+            }
+            ancestor = self.repository.find(id).await?.parent_id;
+        }
+        Ok(())
+    }
+}
+
+fn ensure_visible(actor: Option<&Actor>, comment: Comment) -> Result<Comment, ApiError> {
+    if comment.is_hidden_tombstone() {
+        return Err(ApiError::NotFound);
+    }
+    if comment.status == CommentStatus::Approved {
+        return Ok(comment);
+    }
+    match actor {
+        Some(actor) if policy::ensure_can_view_unapproved(actor, &comment).is_ok() => Ok(comment),
+        _ => Err(ApiError::NotFound),
     }
 }
 
 #[async_trait]
-impl<R: CommentRepository + Send + Sync + 'static> CommentService for Arc<CommentServiceImpl<R>> {
-    async fn find(&self, id: Uuid) -> Result<Comment, ApiError> {
-        self.repository.find(id).await
+impl<R, P> CommentService for Arc<CommentServiceImpl<R, P>>
+where
+    R: CommentRepository + Send + Sync + 'static,
+    P: PostRepository + Send + Sync + 'static,
+{
+    async fn find(&self, actor: Option<&Actor>, id: Uuid) -> Result<Comment, ApiError> {
+        let comment = ensure_visible(actor, self.repository.find(id).await?)?;
+        self.visible_post(actor, comment.post_id).await?;
+        Ok(comment)
+    }
+
+    async fn find_by_post(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        mut query: CommentQuery,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.visible_post(actor, post_id).await?;
+        query.filter.status = Some(CommentStatus::Approved);
+        self.repository.find_by_post(post_id, &query).await
+    }
+
+    async fn stream_by_post(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        mut query: CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError> {
+        self.visible_post(actor, post_id).await?;
+        query.filter.status = Some(CommentStatus::Approved);
+        self.repository.stream_by_post(post_id, &query).await
     }
 
-    async fn find_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, ApiError> {
-        self.repository.find_by_post(post_id).await
+    async fn thread(
+        &self,
+        actor: Option<&Actor>,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<CommentNode>, ApiError> {
+        self.visible_post(actor, post_id).await?;
+        let roots = self
+            .repository
+            .find_top_level(post_id, CommentStatus::Approved, page)
+            .await?;
+        let root_ids: Vec<Uuid> = roots.items.iter().map(|root| root.id).collect();
+        let replies = self
+            .repository
+            .find_replies(&root_ids, CommentStatus::Approved)
+            .await?;
+
+        let mut comments = roots.items;
+        comments.extend(replies);
+        Ok(Page::new(CommentNode::threads(comments), roots.total, page))
     }
 
-    async fn create(&self, comment: CreateComment) -> Result<Comment, ApiError> {
+    async fn create(&self, actor: &Actor, comment: CreateComment) -> Result<Comment, ApiError> {
+        self.ensure_open_for_comments(actor, comment.post_id)
+            .await?;
+        if let Some(parent_id) = comment.parent_id {
+            self.ensure_can_reply(comment.post_id, parent_id).await?;
+        }
+        let status = self.initial_status(actor, comment.post_id).await?;
+
         let new_comment = Comment {
             id: Uuid::new_v4(),
+            content_html: Some(markdown::render(&comment.content, Flavor::Comment)),
             content: comment.content,
             post_id: comment.post_id,
-            author_id: comment.author_id,
+            author_id: actor.user_id,
             created_at: chrono::Local::now().naive_local(),
             updated_at: chrono::Local::now().naive_local(),
+            parent_id: comment.parent_id,
+            reply_count: 0,
+            deleted_at: None,
+            status,
         };
 
         self.repository.create(new_comment).await
     }
 
-    async fn update(&self, id: Uuid, comment: UpdateComment) -> Result<Comment, ApiError> {
+    async fn update(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        comment: UpdateComment,
+    ) -> Result<Comment, ApiError> {
         let mut existing_comment = self.repository.find(id).await?;
+        if existing_comment.is_deleted() {
+            return Err(ApiError::NotFound);
+        }
+        policy::ensure_can_modify_comment(actor, &existing_comment)?;
 
         if let Some(content) = comment.content {
+            existing_comment.content_html = Some(markdown::render(&content, Flavor::Comment));
             existing_comment.content = content;
+            // New content is moderated like a new comment would be, but an
+            // edit never lets a held or rejected comment through.
+            if existing_comment.status == CommentStatus::Approved
+                && self.initial_status(actor, existing_comment.post_id).await?
+                    == CommentStatus::Pending
+            {
+                existing_comment.status = CommentStatus::Pending;
+            }
         }
 
         existing_comment.updated_at = chrono::Local::now().naive_local();
@@ -64,7 +342,59 @@ impl<R: CommentRepository + Send + Sync + 'static> CommentService for Arc<Commen
         self.repository.update(id, existing_comment).await
     }
 
-    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+    async fn delete(&self, actor: &Actor, id: Uuid) -> Result<(), ApiError> {
+        let existing_comment = self.repository.find(id).await?;
+        if existing_comment.is_deleted() {
+            return Err(ApiError::NotFound);
+        }
+        policy::ensure_can_modify_comment(actor, &existing_comment)?;
+
         self.repository.delete(id).await
     }
+
+    async fn queue(
+        &self,
+        actor: &Actor,
+        query: ModerationQueueQuery,
+    ) -> Result<Page<Comment>, ApiError> {
+        policy::ensure_can_moderate_comments(actor)?;
+        self.repository.find_queue(&query).await
+    }
+
+    async fn moderate(
+        &self,
+        actor: &Actor,
+        decision: ModerateComments,
+    ) -> Result<Vec<Comment>, ApiError> {
+        policy::ensure_can_moderate_comments(actor)?;
+        self.repository
+            .set_status(&decision.ids, decision.status)
+            .await
+    }
+
+    async fn moderation(&self, actor: &Actor, post_id: Uuid) -> Result<PostModeration, ApiError> {
+        policy::ensure_can_moderate_comments(actor)?;
+        let overrides = self.repository.find_moderation(post_id).await?;
+        Ok(self.post_moderation(overrides))
+    }
+
+    async fn update_moderation(
+        &self,
+        actor: &Actor,
+        post_id: Uuid,
+        moderation: UpdateModeration,
+    ) -> Result<PostModeration, ApiError> {
+        policy::ensure_can_moderate_comments(actor)?;
+        let overrides = ModerationOverrides {
+            post_id,
+            require_approval: moderation.require_approval,
+            auto_approve_trusted: moderation.auto_approve_trusted,
+            hold_first_time: moderation.hold_first_time,
+        };
+        let overrides = match self.repository.save_moderation(overrides).await {
+            Err(ApiError::InvalidReference { .. }) => return Err(ApiError::NotFound),
+            result => result?,
+        };
+        Ok(self.post_moderation(overrides))
+    }
 }
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::health::{ReadinessReport, ReadinessStatus},
+    repositories::HealthRepository,
+};
+use crate::shared::shutdown::Shutdown;
+
+#[async_trait]
+pub trait HealthService: Send + Sync {
+    async fn readiness(&self) -> ReadinessReport;
+}
+
+#[derive(Clone)]
+pub struct HealthServiceImpl<R: HealthRepository + Send + Sync + 'static> {
+    repository: Arc<R>,
+    shutdown: Shutdown,
+}
+
+impl<R: HealthRepository + Send + Sync + 'static> HealthServiceImpl<R> {
+    pub fn new(repository: Arc<R>, shutdown: Shutdown) -> Self {
+        Self {
+            repository,
+            shutdown,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: HealthRepository + Send + Sync + 'static> HealthService for Arc<HealthServiceImpl<R>> {
+    async fn readiness(&self) -> ReadinessReport {
+        let database = self.repository.check_database().await;
+
+        // A schema behind the binary is as bad as no database: queries for
+        // new columns would fail on every request.
+        let schema_current = database
+            .pending_migrations
+            .as_ref()
+            .is_some_and(|pending| pending.is_empty());
+
+        let status = if !self.shutdown.is_ready() {
+            ReadinessStatus::Draining
+        } else if !database.reachable || !schema_current {
+            ReadinessStatus::Degraded
+        } else {
+            ReadinessStatus::Ready
+        };
+
+        ReadinessReport { status, database }
+    }
+}
@@ -1,3 +1,8 @@
+pub mod auth_service;
 pub mod comment_service;
+pub mod health_service;
+pub mod policy;
 pub mod post_service;
+pub mod search_service;
+pub mod taxonomy_service;
 pub mod user_service;
@@ -0,0 +1,74 @@
+//! Authorization rules shared by the domain services. Every check returns
+//! `ApiError::Forbidden` when the actor is authenticated but not allowed.
+
+use uuid::Uuid;
+
+use crate::domain::models::{auth::Actor, comment::Comment, post::Post, user::Role};
+use crate::shared::error::ApiError;
+
+pub fn ensure_can_create_post(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role >= Role::Author)
+}
+
+pub fn ensure_can_modify_post(actor: &Actor, post: &Post) -> Result<(), ApiError> {
+    allow(actor.user_id == post.author_id || actor.role >= Role::Editor)
+}
+
+/// Authors may submit their own posts for review, but only editors decide
+/// when a post goes live.
+pub fn ensure_can_publish_post(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role >= Role::Editor)
+}
+
+/// Posts that are not published are visible to their author and editors.
+pub fn ensure_can_view_unpublished(actor: &Actor, author_id: Uuid) -> Result<(), ApiError> {
+    allow(actor.user_id == author_id || actor.role >= Role::Editor)
+}
+
+/// Authors may add the tags they need while writing.
+pub fn ensure_can_create_tag(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role >= Role::Author)
+}
+
+/// Renaming or removing a tag, and any change to categories, affects every
+/// post filed under it.
+pub fn ensure_can_manage_taxonomy(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role >= Role::Editor)
+}
+
+/// Editors and admins act as comment moderators.
+pub fn ensure_can_modify_comment(actor: &Actor, comment: &Comment) -> Result<(), ApiError> {
+    allow(actor.user_id == comment.author_id || actor.role >= Role::Editor)
+}
+
+/// Comments awaiting or denied approval are visible to their author and
+/// moderators.
+pub fn ensure_can_view_unapproved(actor: &Actor, comment: &Comment) -> Result<(), ApiError> {
+    allow(actor.user_id == comment.author_id || actor.role >= Role::Editor)
+}
+
+pub fn ensure_can_moderate_comments(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role >= Role::Editor)
+}
+
+pub fn ensure_can_modify_user(actor: &Actor, user_id: Uuid) -> Result<(), ApiError> {
+    allow(actor.user_id == user_id || actor.role == Role::Admin)
+}
+
+pub fn ensure_can_assign_role(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role == Role::Admin)
+}
+
+/// Handing a user's posts to someone else changes who they are credited to,
+/// so only admins may do it.
+pub fn ensure_can_reassign_posts(actor: &Actor) -> Result<(), ApiError> {
+    allow(actor.role == Role::Admin)
+}
+
+fn allow(allowed: bool) -> Result<(), ApiError> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden)
+    }
+}
@@ -1,79 +1,518 @@
+use std::future::Future;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::domain::{
-    models::post::{CreatePost, Post, UpdatePost},
-    repositories::PostRepository,
+    models::{
+        auth::Actor,
+        markdown::{self, Flavor},
+        pagination::{Page, PageRequest},
+        post::{ChangePostStatus, CreatePost, Post, PostQuery, PostStatus, TaggedPost, UpdatePost},
+        post_revision::{PostRevision, RevisionDiff},
+        slug,
+        user::Role,
+    },
+    repositories::{
+        PostRepository, PostRevisionRepository, TagRepository, TransactionScope, UnitOfWork,
+    },
+    services::policy,
 };
 use crate::shared::error::ApiError;
 
 #[async_trait]
 pub trait PostService: Send + Sync {
-    async fn get_post(&self, id: Uuid) -> Result<Post, ApiError>;
-    async fn get_posts(&self) -> Result<Vec<Post>, ApiError>;
-    async fn create_post(&self, post: CreatePost) -> Result<Post, ApiError>;
-    async fn update_post(&self, id: Uuid, post: UpdatePost) -> Result<Post, ApiError>;
-    async fn delete_post(&self, id: Uuid) -> Result<(), ApiError>;
+    /// Posts that are not published look missing to anyone who may not see
+    /// them.
+    async fn get_post(&self, actor: Option<&Actor>, id: Uuid) -> Result<TaggedPost, ApiError>;
+    /// Looks a post up by its current or a former slug; compare the result's
+    /// `slug` to tell which.
+    async fn get_post_by_slug(
+        &self,
+        actor: Option<&Actor>,
+        slug: &str,
+    ) -> Result<TaggedPost, ApiError>;
+    /// Lists published posts unless the query asks for another status, which
+    /// only editors may list across authors.
+    async fn get_posts(
+        &self,
+        actor: Option<&Actor>,
+        query: PostQuery,
+    ) -> Result<Page<TaggedPost>, ApiError>;
+    /// Tags are given by slug and must already exist.
+    async fn create_post(&self, actor: &Actor, post: CreatePost) -> Result<TaggedPost, ApiError>;
+    async fn update_post(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        post: UpdatePost,
+    ) -> Result<TaggedPost, ApiError>;
+    async fn change_status(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        change: ChangePostStatus,
+    ) -> Result<TaggedPost, ApiError>;
+    async fn delete_post(&self, actor: &Actor, id: Uuid) -> Result<(), ApiError>;
+    /// Publishes scheduled posts whose time has come.
+    async fn publish_due_posts(&self) -> Result<Vec<Post>, ApiError>;
+    /// Revision history is visible to whoever may edit the post.
+    async fn get_revisions(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError>;
+    async fn get_revision(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        revision: i32,
+    ) -> Result<PostRevision, ApiError>;
+    async fn diff_revisions(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        from: i32,
+        to: i32,
+    ) -> Result<RevisionDiff, ApiError>;
+    /// Brings back a revision's title and content as a new revision. The
+    /// status is left alone; that only changes through `change_status`.
+    async fn restore_revision(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        revision: i32,
+    ) -> Result<TaggedPost, ApiError>;
 }
 
 #[derive(Clone)]
-pub struct PostServiceImpl<R: PostRepository + Send + Sync + 'static> {
+pub struct PostServiceImpl<R, V, T, W>
+where
+    R: PostRepository + Send + Sync + 'static,
+    V: PostRevisionRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
     repository: Arc<R>,
+    revisions: Arc<V>,
+    tags: Arc<T>,
+    unit_of_work: Arc<W>,
 }
 
-impl<R: PostRepository + Send + Sync + 'static> PostServiceImpl<R> {
-    pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+impl<R, V, T, W> PostServiceImpl<R, V, T, W>
+where
+    R: PostRepository + Send + Sync + 'static,
+    V: PostRevisionRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    pub fn new(repository: Arc<R>, revisions: Arc<V>, tags: Arc<T>, unit_of_work: Arc<W>) -> Self {
+        Self {
+            repository,
+            revisions,
+            tags,
+            unit_of_work,
+        }
+    }
+
+    /// The post, if `actor` may edit it.
+    async fn editable(&self, actor: &Actor, id: Uuid) -> Result<Post, ApiError> {
+        ensure_editable(actor, self.repository.find(id).await?)
+    }
+
+    /// Applies `edit` to the post as it stands once locked, so a concurrent
+    /// change is built on rather than overwritten, then saves it, and
+    /// `tag_ids` as its tags when given, and records the result as its next
+    /// revision, all together. A `title` other than the post's current one
+    /// comes with a slug of its own.
+    async fn save<E>(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        title: Option<String>,
+        tag_ids: Option<Vec<Uuid>>,
+        edit: E,
+    ) -> Result<TaggedPost, ApiError>
+    where
+        E: Fn(&mut Post) -> Result<(), ApiError> + Send + Sync,
+    {
+        let actor = *actor;
+        let edit = &edit;
+        let write = |renamed: Option<(String, String)>| {
+            let tag_ids = tag_ids.clone();
+            self.unit_of_work.transaction(move |tx| async move {
+                let mut post = ensure_editable(&actor, tx.posts().find_for_update(id).await?)?;
+                if let Some((title, slug)) = renamed
+                    && title != post.title
+                {
+                    post.title = title;
+                    post.slug = slug;
+                }
+                edit(&mut post)?;
+                post.content_html = Some(markdown::render(&post.content, Flavor::Post));
+                post.updated_at = chrono::Local::now().naive_local();
+
+                let saved = tx.posts().update(id, post).await?;
+                if let Some(tag_ids) = tag_ids {
+                    tx.tags().set_for_post(id, &tag_ids).await?;
+                }
+                tx.post_revisions()
+                    .record(&saved, Some(actor.user_id))
+                    .await?;
+                Ok(saved)
+            })
+        };
+
+        let saved = match title {
+            Some(title) => {
+                let slug = self.unique_slug(&title, Some(id)).await?;
+                self.claiming_slug(&title, slug, Some(id), |slug| {
+                    write(Some((title.clone(), slug)))
+                })
+                .await?
+            }
+            None => write(None).await?,
+        };
+        self.tagged(saved).await
+    }
+
+    async fn tagged(&self, post: Post) -> Result<TaggedPost, ApiError> {
+        let tags = self
+            .tags
+            .find_by_posts(&[post.id])
+            .await?
+            .remove(&post.id)
+            .unwrap_or_default();
+        Ok(TaggedPost { post, tags })
+    }
+
+    /// The ids of the tags with `slugs`, which must all exist.
+    async fn tag_ids(&self, mut slugs: Vec<String>) -> Result<Vec<Uuid>, ApiError> {
+        slugs.sort_unstable();
+        slugs.dedup();
+        let tags = self.tags.find_by_slugs(&slugs).await?;
+        if tags.len() != slugs.len() {
+            return Err(ApiError::InvalidReference {
+                field: "tags".to_string(),
+            });
+        }
+        Ok(tags.into_iter().map(|tag| tag.id).collect())
+    }
+
+    /// The first of `title`'s slug candidates that no other post has or has
+    /// had. `post_id` may keep a slug it already owns.
+    async fn unique_slug(&self, title: &str, post_id: Option<Uuid>) -> Result<String, ApiError> {
+        let base = slug::slugify(title);
+        let series = self.repository.slug_series(&base).await?;
+        let taken = series
+            .iter()
+            .filter(|(_, owner)| Some(*owner) != post_id)
+            .map(|(taken, _)| taken.as_str());
+        Ok(slug::first_free(&base, taken))
+    }
+
+    /// Runs `write` with `slug`. If a concurrent write took the slug first,
+    /// `write` runs again with the next free one for `title`.
+    async fn claiming_slug<F, Fut, Out>(
+        &self,
+        title: &str,
+        mut slug: String,
+        post_id: Option<Uuid>,
+        write: F,
+    ) -> Result<Out, ApiError>
+    where
+        F: Fn(String) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<Out, ApiError>> + Send,
+    {
+        let mut attempt = 1;
+        loop {
+            match write(slug.clone()).await {
+                Err(ApiError::Conflict { field })
+                    if field == "slug" && attempt < slug::CLAIM_ATTEMPTS =>
+                {
+                    attempt += 1;
+                    slug = self.unique_slug(title, post_id).await?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Posts that are not published look missing to anyone who may not see them.
+pub(crate) fn ensure_visible(actor: Option<&Actor>, post: Post) -> Result<Post, ApiError> {
+    if post.status == PostStatus::Published {
+        return Ok(post);
+    }
+    match actor {
+        Some(actor) if policy::ensure_can_view_unpublished(actor, post.author_id).is_ok() => {
+            Ok(post)
+        }
+        _ => Err(ApiError::NotFound),
+    }
+}
+
+/// Checks that `actor` may edit `post`. A post they cannot see is reported
+/// as missing, as it would be when read, rather than as forbidden.
+fn ensure_editable(actor: &Actor, post: Post) -> Result<Post, ApiError> {
+    let post = ensure_visible(Some(actor), post)?;
+    policy::ensure_can_modify_post(actor, &post)?;
+    Ok(post)
+}
+
+/// Moves `post` to `change.status`, setting or clearing its publication
+/// dates to match.
+fn transition(post: &mut Post, change: &ChangePostStatus) -> Result<(), ApiError> {
+    if !post.status.can_transition_to(change.status) {
+        return Err(ApiError::InvalidTransition {
+            from: post.status.to_string(),
+            to: change.status.to_string(),
+        });
     }
+
+    let now = chrono::Local::now().naive_local();
+    match (change.status, change.publish_at) {
+        (PostStatus::Scheduled, None) => {
+            return Err(ApiError::invalid_field(
+                "publish_at",
+                "required",
+                "is required when scheduling",
+            ));
+        }
+        (PostStatus::Scheduled, Some(publish_at)) if publish_at <= now => {
+            return Err(ApiError::invalid_field(
+                "publish_at",
+                "future",
+                "must be in the future",
+            ));
+        }
+        (PostStatus::Scheduled, Some(publish_at)) => {
+            post.publish_at = Some(publish_at);
+            post.published_at = None;
+        }
+        (_, Some(_)) => {
+            return Err(ApiError::invalid_field(
+                "publish_at",
+                "scheduled_only",
+                "may only be set when scheduling",
+            ));
+        }
+        (PostStatus::Published, None) => {
+            post.publish_at = None;
+            // Restoring an archived post keeps its original date.
+            post.published_at.get_or_insert(now);
+        }
+        (PostStatus::Draft, None) => {
+            post.publish_at = None;
+            post.published_at = None;
+        }
+        (PostStatus::InReview | PostStatus::Archived, None) => {}
+    }
+
+    post.status = change.status;
+    Ok(())
 }
 
 #[async_trait]
-impl<R: PostRepository + Send + Sync + 'static> PostService for Arc<PostServiceImpl<R>> {
-    async fn get_post(&self, id: Uuid) -> Result<Post, ApiError> {
-        self.repository.find(id).await
+impl<R, V, T, W> PostService for Arc<PostServiceImpl<R, V, T, W>>
+where
+    R: PostRepository + Send + Sync + 'static,
+    V: PostRevisionRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    async fn get_post(&self, actor: Option<&Actor>, id: Uuid) -> Result<TaggedPost, ApiError> {
+        let post = self.repository.find(id).await?;
+        self.tagged(ensure_visible(actor, post)?).await
     }
 
-    async fn get_posts(&self) -> Result<Vec<Post>, ApiError> {
-        self.repository.find_all().await
+    async fn get_post_by_slug(
+        &self,
+        actor: Option<&Actor>,
+        slug: &str,
+    ) -> Result<TaggedPost, ApiError> {
+        let post = self.repository.find_by_slug(slug).await?;
+        self.tagged(ensure_visible(actor, post)?).await
     }
 
-    async fn create_post(&self, post: CreatePost) -> Result<Post, ApiError> {
+    async fn get_posts(
+        &self,
+        actor: Option<&Actor>,
+        mut query: PostQuery,
+    ) -> Result<Page<TaggedPost>, ApiError> {
+        match query.filter.status {
+            None => query.filter.status = Some(PostStatus::Published),
+            Some(PostStatus::Published) => {}
+            Some(_) => {
+                let actor = actor.ok_or(ApiError::Unauthorized)?;
+                match query.filter.author_id {
+                    Some(author_id) => policy::ensure_can_view_unpublished(actor, author_id)?,
+                    // Without an author filter, non-editors see their own.
+                    None if actor.role < Role::Editor => {
+                        query.filter.author_id = Some(actor.user_id)
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        let page = self.repository.find_all(&query).await?;
+        let ids: Vec<Uuid> = page.items.iter().map(|post| post.id).collect();
+        let mut tags = self.tags.find_by_posts(&ids).await?;
+        Ok(page.map(|post| TaggedPost {
+            tags: tags.remove(&post.id).unwrap_or_default(),
+            post,
+        }))
+    }
+
+    async fn create_post(&self, actor: &Actor, post: CreatePost) -> Result<TaggedPost, ApiError> {
+        policy::ensure_can_create_post(actor)?;
+
+        let tag_ids = self.tag_ids(post.tags).await?;
+        let slug = self.unique_slug(&post.title, None).await?;
         let new_post = Post {
             id: Uuid::new_v4(),
+            slug,
             title: post.title,
+            content_html: Some(markdown::render(&post.content, Flavor::Post)),
             content: post.content,
-            author_id: post.author_id,
-            published: false,
+            author_id: actor.user_id,
+            status: PostStatus::Draft,
+            publish_at: None,
+            published_at: None,
+            category_id: post.category_id,
             created_at: chrono::Local::now().naive_local(),
             updated_at: chrono::Local::now().naive_local(),
         };
 
-        self.repository.create(new_post).await
+        let editor_id = actor.user_id;
+        let created = self
+            .claiming_slug(&new_post.title, new_post.slug.clone(), None, |slug| {
+                let new_post = Post {
+                    slug,
+                    ..new_post.clone()
+                };
+                let tag_ids = tag_ids.clone();
+                self.unit_of_work.transaction(move |tx| async move {
+                    let created = tx.posts().create(new_post).await?;
+                    tx.tags().set_for_post(created.id, &tag_ids).await?;
+                    tx.post_revisions()
+                        .record(&created, Some(editor_id))
+                        .await?;
+                    Ok(created)
+                })
+            })
+            .await?;
+        self.tagged(created).await
     }
 
-    async fn update_post(&self, id: Uuid, post: UpdatePost) -> Result<Post, ApiError> {
-        let mut existing_post = self.repository.find(id).await?;
+    async fn update_post(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        post: UpdatePost,
+    ) -> Result<TaggedPost, ApiError> {
+        self.editable(actor, id).await?;
+        let tag_ids = match post.tags {
+            Some(slugs) => Some(self.tag_ids(slugs).await?),
+            None => None,
+        };
 
-        if let Some(title) = post.title {
-            existing_post.title = title;
-        }
+        self.save(actor, id, post.title, tag_ids, |existing_post| {
+            if let Some(content) = &post.content {
+                existing_post.content = content.clone();
+            }
+            if let Some(category_id) = post.category_id {
+                existing_post.category_id = category_id;
+            }
+            Ok(())
+        })
+        .await
+    }
 
-        if let Some(content) = post.content {
-            existing_post.content = content;
+    async fn change_status(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        change: ChangePostStatus,
+    ) -> Result<TaggedPost, ApiError> {
+        self.editable(actor, id).await?;
+        if matches!(change.status, PostStatus::Scheduled | PostStatus::Published) {
+            policy::ensure_can_publish_post(actor)?;
         }
 
-        if let Some(published) = post.published {
-            existing_post.published = published;
-        }
+        // Checked against the locked post, so two transitions racing from
+        // one state cannot both apply.
+        self.save(actor, id, None, None, |post| transition(post, &change))
+            .await
+    }
 
-        existing_post.updated_at = chrono::Local::now().naive_local();
+    async fn delete_post(&self, actor: &Actor, id: Uuid) -> Result<(), ApiError> {
+        self.editable(actor, id).await?;
+        self.repository.delete(id).await
+    }
 
-        self.repository.update(id, existing_post).await
+    async fn publish_due_posts(&self) -> Result<Vec<Post>, ApiError> {
+        let now = chrono::Local::now().naive_local();
+        self.unit_of_work
+            .transaction(move |tx| async move {
+                let published = tx.posts().publish_due(now).await?;
+                for post in &published {
+                    tx.post_revisions().record(post, None).await?;
+                }
+                Ok(published)
+            })
+            .await
     }
 
-    async fn delete_post(&self, id: Uuid) -> Result<(), ApiError> {
-        self.repository.delete(id).await
+    async fn get_revisions(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        self.editable(actor, id).await?;
+        self.revisions.find_by_post(id, page).await
+    }
+
+    async fn get_revision(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        revision: i32,
+    ) -> Result<PostRevision, ApiError> {
+        self.editable(actor, id).await?;
+        self.revisions.find(id, revision).await
+    }
+
+    async fn diff_revisions(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        from: i32,
+        to: i32,
+    ) -> Result<RevisionDiff, ApiError> {
+        self.editable(actor, id).await?;
+        let from = self.revisions.find(id, from).await?;
+        let to = self.revisions.find(id, to).await?;
+        Ok(from.diff(&to))
+    }
+
+    async fn restore_revision(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        revision: i32,
+    ) -> Result<TaggedPost, ApiError> {
+        self.editable(actor, id).await?;
+        let revision = self.revisions.find(id, revision).await?;
+
+        self.save(actor, id, Some(revision.title), None, |post| {
+            post.content = revision.content.clone();
+            Ok(())
+        })
+        .await
     }
 }
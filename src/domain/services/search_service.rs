@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    models::{
+        pagination::Page,
+        search::{Search, SearchHit, SearchQuery, SearchTerms},
+    },
+    repositories::{SearchRepository, TagRepository},
+};
+use crate::shared::error::ApiError;
+
+/// Full-text search over published posts and the comments on them. Nothing
+/// unpublished is ever found, whoever asks.
+#[async_trait]
+pub trait SearchService: Send + Sync {
+    /// Best matches first. `q` must contain at least one word that is not
+    /// excluded; a tag that does not exist matches nothing.
+    async fn search(&self, search: Search) -> Result<Page<SearchHit>, ApiError>;
+}
+
+#[derive(Clone)]
+pub struct SearchServiceImpl<S, T>
+where
+    S: SearchRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+{
+    search: Arc<S>,
+    tags: Arc<T>,
+}
+
+impl<S, T> SearchServiceImpl<S, T>
+where
+    S: SearchRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+{
+    pub fn new(search: Arc<S>, tags: Arc<T>) -> Self {
+        Self { search, tags }
+    }
+}
+
+#[async_trait]
+impl<S, T> SearchService for Arc<SearchServiceImpl<S, T>>
+where
+    S: SearchRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+{
+    async fn search(&self, search: Search) -> Result<Page<SearchHit>, ApiError> {
+        let terms = SearchTerms::parse(&search.q).ok_or_else(|| {
+            ApiError::invalid_field("q", "searchable", "must contain a word to search for")
+        })?;
+
+        let tag_id = match &search.tag {
+            Some(slug) => match self.tags.find_by_slug(slug).await {
+                Ok(tag) => Some(tag.id),
+                Err(ApiError::NotFound) => return Ok(Page::new(Vec::new(), 0, search.page)),
+                Err(error) => return Err(error),
+            },
+            None => None,
+        };
+
+        let query = SearchQuery {
+            terms,
+            scope: search.scope,
+            author_id: search.author_id,
+            tag_id,
+            created_after: search.created_after,
+            created_before: search.created_before,
+            page: search.page,
+        };
+        self.search.search(&query).await
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{
+    models::{
+        auth::Actor,
+        category::{Category, CategoryNode, CreateCategory, UpdateCategory},
+        pagination::{Page, PageRequest},
+        slug,
+        tag::{CreateTag, Tag, TagCount, UpdateTag},
+    },
+    repositories::{CategoryRepository, TagRepository, TransactionScope, UnitOfWork},
+    services::policy,
+};
+use crate::shared::error::ApiError;
+
+/// Tags and categories. Both are addressed by slug, which follows the name
+/// and is numbered (`rust-2`) when another already has it, and both count
+/// only published posts.
+#[async_trait]
+pub trait TaxonomyService: Send + Sync {
+    async fn get_tags(&self, page: PageRequest) -> Result<Page<TagCount>, ApiError>;
+    async fn get_tag(&self, slug: &str) -> Result<TagCount, ApiError>;
+    async fn create_tag(&self, actor: &Actor, tag: CreateTag) -> Result<TagCount, ApiError>;
+    async fn update_tag(
+        &self,
+        actor: &Actor,
+        slug: &str,
+        tag: UpdateTag,
+    ) -> Result<TagCount, ApiError>;
+    /// Deleting a tag takes it off every post.
+    async fn delete_tag(&self, actor: &Actor, slug: &str) -> Result<(), ApiError>;
+    /// The category tree, top-level categories first.
+    async fn get_categories(&self) -> Result<Vec<CategoryNode>, ApiError>;
+    /// A category with everything beneath it.
+    async fn get_category(&self, slug: &str) -> Result<CategoryNode, ApiError>;
+    async fn create_category(
+        &self,
+        actor: &Actor,
+        category: CreateCategory,
+    ) -> Result<CategoryNode, ApiError>;
+    /// A category cannot be moved beneath itself.
+    async fn update_category(
+        &self,
+        actor: &Actor,
+        slug: &str,
+        category: UpdateCategory,
+    ) -> Result<CategoryNode, ApiError>;
+    /// Subcategories move up to the deleted category's parent; its posts are
+    /// left without a category.
+    async fn delete_category(&self, actor: &Actor, slug: &str) -> Result<(), ApiError>;
+}
+
+#[derive(Clone)]
+pub struct TaxonomyServiceImpl<T, C, W>
+where
+    T: TagRepository + Send + Sync + 'static,
+    C: CategoryRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    tags: Arc<T>,
+    categories: Arc<C>,
+    unit_of_work: Arc<W>,
+}
+
+impl<T, C, W> TaxonomyServiceImpl<T, C, W>
+where
+    T: TagRepository + Send + Sync + 'static,
+    C: CategoryRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    pub fn new(tags: Arc<T>, categories: Arc<C>, unit_of_work: Arc<W>) -> Self {
+        Self {
+            tags,
+            categories,
+            unit_of_work,
+        }
+    }
+
+    async fn counted(&self, tag: Tag) -> Result<TagCount, ApiError> {
+        let counts = self.tags.count_published(&[tag.id]).await?;
+        Ok(TagCount {
+            post_count: counts.get(&tag.id).copied().unwrap_or(0),
+            tag,
+        })
+    }
+
+    /// The first free slug for a tag named `name`. `tag_id`'s own slug
+    /// counts as free.
+    async fn tag_slug(&self, name: &str, tag_id: Option<Uuid>) -> Result<String, ApiError> {
+        let base = slug::slugify(name);
+        let series = self.tags.slug_series(&base).await?;
+        let taken = series
+            .iter()
+            .filter(|(_, owner)| Some(*owner) != tag_id)
+            .map(|(taken, _)| taken.as_str());
+        Ok(slug::first_free(&base, taken))
+    }
+
+    async fn tree(&self) -> Result<Vec<CategoryNode>, ApiError> {
+        let categories = self.categories.find_all().await?;
+        let counts = self.categories.count_published().await?;
+        Ok(CategoryNode::forest(categories, &counts))
+    }
+
+    async fn node(&self, slug: &str) -> Result<CategoryNode, ApiError> {
+        CategoryNode::find(self.tree().await?, slug).ok_or(ApiError::NotFound)
+    }
+}
+
+/// The first free slug among `categories` for one named `name`.
+/// `category_id`'s own slug counts as free.
+fn category_slug(categories: &[Category], name: &str, category_id: Option<Uuid>) -> String {
+    let taken = categories
+        .iter()
+        .filter(|category| Some(category.id) != category_id)
+        .map(|category| category.slug.as_str());
+    slug::first_free(&slug::slugify(name), taken)
+}
+
+/// Runs `write`, which picks its slug afresh each time, again if a
+/// concurrent write took that slug first.
+async fn claiming_slug<F, Fut, Out>(write: F) -> Result<Out, ApiError>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Out, ApiError>> + Send,
+{
+    let mut attempt = 1;
+    loop {
+        match write().await {
+            Err(ApiError::Conflict { field })
+                if field == "slug" && attempt < slug::CLAIM_ATTEMPTS =>
+            {
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether `id` is `parent_id` or one of its ancestors, which would make
+/// the tree a loop if `id` were moved under `parent_id`.
+fn is_ancestor_or_self(categories: &[Category], id: Uuid, parent_id: Uuid) -> bool {
+    let parents: HashMap<Uuid, Option<Uuid>> = categories
+        .iter()
+        .map(|category| (category.id, category.parent_id))
+        .collect();
+
+    let mut current = Some(parent_id);
+    while let Some(ancestor) = current {
+        if ancestor == id {
+            return true;
+        }
+        current = parents.get(&ancestor).copied().flatten();
+    }
+    false
+}
+
+#[async_trait]
+impl<T, C, W> TaxonomyService for Arc<TaxonomyServiceImpl<T, C, W>>
+where
+    T: TagRepository + Send + Sync + 'static,
+    C: CategoryRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    async fn get_tags(&self, page: PageRequest) -> Result<Page<TagCount>, ApiError> {
+        let page = self.tags.find_all(page).await?;
+        let ids: Vec<Uuid> = page.items.iter().map(|tag| tag.id).collect();
+        let counts = self.tags.count_published(&ids).await?;
+        Ok(page.map(|tag| TagCount {
+            post_count: counts.get(&tag.id).copied().unwrap_or(0),
+            tag,
+        }))
+    }
+
+    async fn get_tag(&self, slug: &str) -> Result<TagCount, ApiError> {
+        let tag = self.tags.find_by_slug(slug).await?;
+        self.counted(tag).await
+    }
+
+    async fn create_tag(&self, actor: &Actor, tag: CreateTag) -> Result<TagCount, ApiError> {
+        policy::ensure_can_create_tag(actor)?;
+
+        let name = &tag.name;
+        let created = claiming_slug(|| async move {
+            let new_tag = Tag {
+                id: Uuid::new_v4(),
+                slug: self.tag_slug(name, None).await?,
+                name: name.clone(),
+                created_at: chrono::Local::now().naive_local(),
+                updated_at: chrono::Local::now().naive_local(),
+            };
+            self.tags.create(new_tag).await
+        })
+        .await?;
+        Ok(TagCount {
+            tag: created,
+            post_count: 0,
+        })
+    }
+
+    async fn update_tag(
+        &self,
+        actor: &Actor,
+        slug: &str,
+        tag: UpdateTag,
+    ) -> Result<TagCount, ApiError> {
+        policy::ensure_can_manage_taxonomy(actor)?;
+
+        let existing = &self.tags.find_by_slug(slug).await?;
+        let name = &tag.name;
+        let updated = claiming_slug(|| async move {
+            let mut renamed = existing.clone();
+            renamed.slug = self.tag_slug(name, Some(existing.id)).await?;
+            renamed.name = name.clone();
+            renamed.updated_at = chrono::Local::now().naive_local();
+            self.tags.update(existing.id, renamed).await
+        })
+        .await?;
+        self.counted(updated).await
+    }
+
+    async fn delete_tag(&self, actor: &Actor, slug: &str) -> Result<(), ApiError> {
+        policy::ensure_can_manage_taxonomy(actor)?;
+
+        let existing = self.tags.find_by_slug(slug).await?;
+        self.tags.delete(existing.id).await
+    }
+
+    async fn get_categories(&self) -> Result<Vec<CategoryNode>, ApiError> {
+        self.tree().await
+    }
+
+    async fn get_category(&self, slug: &str) -> Result<CategoryNode, ApiError> {
+        self.node(slug).await
+    }
+
+    async fn create_category(
+        &self,
+        actor: &Actor,
+        category: CreateCategory,
+    ) -> Result<CategoryNode, ApiError> {
+        policy::ensure_can_manage_taxonomy(actor)?;
+
+        let category = &category;
+        let created = claiming_slug(|| async move {
+            let categories = self.categories.find_all().await?;
+            let new_category = Category {
+                id: Uuid::new_v4(),
+                parent_id: category.parent_id,
+                slug: category_slug(&categories, &category.name, None),
+                name: category.name.clone(),
+                description: category.description.clone(),
+                created_at: chrono::Local::now().naive_local(),
+                updated_at: chrono::Local::now().naive_local(),
+            };
+            self.categories.create(new_category).await
+        })
+        .await?;
+        self.node(&created.slug).await
+    }
+
+    async fn update_category(
+        &self,
+        actor: &Actor,
+        slug: &str,
+        category: UpdateCategory,
+    ) -> Result<CategoryNode, ApiError> {
+        policy::ensure_can_manage_taxonomy(actor)?;
+
+        // The tree stays locked from the cycle check until the move is
+        // written, so two moves cannot each pass against the tree the other
+        // is about to change.
+        let updated = claiming_slug(|| {
+            let slug = slug.to_owned();
+            let category = category.clone();
+            self.unit_of_work.transaction(move |tx| async move {
+                let categories = tx.categories().find_all_for_update().await?;
+                let mut existing = categories
+                    .iter()
+                    .find(|existing| existing.slug == slug)
+                    .cloned()
+                    .ok_or(ApiError::NotFound)?;
+
+                if let Some(name) = category.name {
+                    existing.slug = category_slug(&categories, &name, Some(existing.id));
+                    existing.name = name;
+                }
+
+                if let Some(description) = category.description {
+                    existing.description = description;
+                }
+
+                if let Some(parent_id) = category.parent_id {
+                    if parent_id
+                        .is_some_and(|parent| is_ancestor_or_self(&categories, existing.id, parent))
+                    {
+                        return Err(ApiError::invalid_field(
+                            "parent_id",
+                            "cycle",
+                            "cannot be the category itself or one of its subcategories",
+                        ));
+                    }
+                    existing.parent_id = parent_id;
+                }
+
+                existing.updated_at = chrono::Local::now().naive_local();
+
+                tx.categories().update(existing.id, existing).await
+            })
+        })
+        .await?;
+        self.node(&updated.slug).await
+    }
+
+    async fn delete_category(&self, actor: &Actor, slug: &str) -> Result<(), ApiError> {
+        policy::ensure_can_manage_taxonomy(actor)?;
+
+        let existing = self.categories.find_by_slug(slug).await?;
+        self.categories.delete(existing.id).await
+    }
+}
@@ -4,36 +4,64 @@ use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::domain::{
-    models::user::{CreateUser, UpdateUser, User},
-    repositories::UserRepository,
+    models::{
+        auth::Actor,
+        pagination::Page,
+        user::{CreateUser, UpdateUser, User, UserQuery},
+    },
+    repositories::{PostRepository, TransactionScope, UnitOfWork, UserRepository},
+    services::policy,
 };
 use crate::shared::error::ApiError;
 
 #[async_trait]
 pub trait UserService: Send + Sync {
     async fn find(&self, id: Uuid) -> Result<User, ApiError>;
-    async fn find_all(&self) -> Result<Vec<User>, ApiError>;
+    async fn find_all(&self, query: UserQuery) -> Result<Page<User>, ApiError>;
     async fn find_by_email(&self, email: &str) -> Result<User, ApiError>;
     async fn create(&self, user: CreateUser) -> Result<User, ApiError>;
-    async fn update(&self, id: Uuid, user: UpdateUser) -> Result<User, ApiError>;
-    async fn delete(&self, id: Uuid) -> Result<(), ApiError>;
+    async fn update(&self, actor: &Actor, id: Uuid, user: UpdateUser) -> Result<User, ApiError>;
+    /// Deletes a user. With `reassign_posts_to`, their posts are handed to
+    /// that user in the same transaction instead of being deleted with them.
+    async fn delete(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        reassign_posts_to: Option<Uuid>,
+    ) -> Result<(), ApiError>;
 }
 
 #[derive(Clone)]
-pub struct UserServiceImpl<R: UserRepository + Send + Sync + 'static> {
+pub struct UserServiceImpl<R, W>
+where
+    R: UserRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
     repository: Arc<R>,
+    unit_of_work: Arc<W>,
 }
 
-impl<R: UserRepository + Send + Sync + 'static> UserServiceImpl<R> {
-    pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+impl<R, W> UserServiceImpl<R, W>
+where
+    R: UserRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    pub fn new(repository: Arc<R>, unit_of_work: Arc<W>) -> Self {
+        Self {
+            repository,
+            unit_of_work,
+        }
     }
 }
 
 #[async_trait]
-impl<R: UserRepository + Send + Sync + 'static> UserService for Arc<UserServiceImpl<R>> {
-    async fn find_all(&self) -> Result<Vec<User>, ApiError> {
-        self.repository.find_all().await
+impl<R, W> UserService for Arc<UserServiceImpl<R, W>>
+where
+    R: UserRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
+    async fn find_all(&self, query: UserQuery) -> Result<Page<User>, ApiError> {
+        self.repository.find_all(&query).await
     }
 
     async fn find(&self, id: Uuid) -> Result<User, ApiError> {
@@ -49,7 +77,8 @@ impl<R: UserRepository + Send + Sync + 'static> UserService for Arc<UserServiceI
         self.repository.create(new_user).await
     }
 
-    async fn update(&self, id: Uuid, user: UpdateUser) -> Result<User, ApiError> {
+    async fn update(&self, actor: &Actor, id: Uuid, user: UpdateUser) -> Result<User, ApiError> {
+        policy::ensure_can_modify_user(actor, id)?;
         let mut existing_user = self.repository.find(id).await?;
 
         if let Some(username) = user.username {
@@ -64,11 +93,49 @@ impl<R: UserRepository + Send + Sync + 'static> UserService for Arc<UserServiceI
             existing_user.update_password(password)?;
         }
 
+        if let Some(role) = user.role {
+            policy::ensure_can_assign_role(actor)?;
+            existing_user.role = role;
+        }
+
         existing_user.updated_at = chrono::Local::now().naive_local();
         self.repository.update(id, existing_user).await
     }
 
-    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
-        self.repository.delete(id).await
+    async fn delete(
+        &self,
+        actor: &Actor,
+        id: Uuid,
+        reassign_posts_to: Option<Uuid>,
+    ) -> Result<(), ApiError> {
+        policy::ensure_can_modify_user(actor, id)?;
+
+        let Some(heir) = reassign_posts_to else {
+            return self.repository.delete(id).await;
+        };
+        policy::ensure_can_reassign_posts(actor)?;
+        if heir == id {
+            return Err(ApiError::BadRequest(
+                "posts cannot be reassigned to the user being deleted".to_string(),
+            ));
+        }
+
+        self.unit_of_work
+            .transaction(move |tx| async move {
+                tx.users().find(id).await?;
+                match tx.users().find(heir).await {
+                    Ok(_) => {}
+                    Err(ApiError::NotFound) => {
+                        return Err(ApiError::InvalidReference {
+                            field: "reassign_posts_to".to_string(),
+                        });
+                    }
+                    Err(e) => return Err(e),
+                }
+
+                tx.posts().reassign_author(id, heir).await?;
+                tx.users().delete(id).await
+            })
+            .await
     }
 }
@@ -2,20 +2,21 @@ use diesel::{
     pg::PgConnection,
     r2d2::{ConnectionManager, Pool},
 };
-use std::env;
+
+use crate::shared::config::DatabaseConfig;
 
 pub type PgPool = Pool<ConnectionManager<PgConnection>>;
 
-pub fn init_pool() -> anyhow::Result<PgPool> {
-    let database_url =
-        env::var("DATABASE_URL").map_err(|_| anyhow::anyhow!("DATABASE_URL must be set"))?;
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
+pub fn init_pool(config: &DatabaseConfig) -> anyhow::Result<PgPool> {
+    let manager = ConnectionManager::<PgConnection>::new(&config.url);
 
+    // One connection beyond `max_size` is held back for the readiness probe,
+    // so a pool busy with requests still has one to answer it with.
     Pool::builder()
-        .max_size(10)
-        .min_idle(Some(2))
+        .max_size(config.max_size + 1)
+        .min_idle(config.min_idle)
         .test_on_check_out(true)
-        .connection_timeout(std::time::Duration::from_secs(5))
+        .connection_timeout(config.connection_timeout())
         .build(manager)
         .map_err(|e| anyhow::anyhow!("Failed to create connection pool: {}", e))
 }
@@ -0,0 +1,236 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use diesel::{
+    connection::{AnsiTransactionManager, TransactionManager},
+    pg::PgConnection,
+    r2d2::{ConnectionManager, PooledConnection},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::domain::models::health::PoolStats;
+use crate::infrastructure::database::connection::PgPool;
+use crate::shared::{config::DatabaseConfig, error::ApiError};
+
+/// Runs Diesel work on Tokio's blocking thread pool.
+///
+/// Diesel and r2d2 are synchronous, so calling them from an `async fn` would
+/// park a runtime worker for the whole round trip. Every closure handed to
+/// [`DbExecutor::run`] executes on a blocking thread instead, and a semaphore
+/// sized to the connection pool caps how many are in flight. Callers that
+/// cannot get a slot within `acquire_timeout` fail fast with
+/// `ApiError::ServiceUnavailable` rather than queueing without bound.
+///
+/// [`DbExecutor::probe`] does not take one of those slots. It has a single
+/// slot of its own, which overlapping probes queue for, and a short
+/// deadline, so health checks keep answering while request traffic has
+/// every other slot.
+///
+/// [`DbExecutor::begin`] returns an executor pinned to one connection with an
+/// open transaction; everything run through it, or through repositories built
+/// on it, joins that transaction until [`DbExecutor::commit`] or
+/// [`DbExecutor::rollback`].
+#[derive(Clone)]
+pub struct DbExecutor {
+    pool: PgPool,
+    permits: Arc<Semaphore>,
+    max_concurrency: usize,
+    acquire_timeout: Duration,
+    probe_permit: Arc<Semaphore>,
+    probe_timeout: Duration,
+    pinned: Option<Arc<PinnedConnection>>,
+}
+
+/// A connection reserved for one transaction. It keeps its executor slot for
+/// as long as it lives. If it is dropped mid-transaction, r2d2 sees the open
+/// transaction and discards the connection instead of reusing it.
+struct PinnedConnection {
+    conn: Mutex<PooledConnection<ConnectionManager<PgConnection>>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl DbExecutor {
+    pub fn new(pool: PgPool, max_concurrency: usize, acquire_timeout: Duration) -> Self {
+        Self {
+            pool,
+            permits: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+            acquire_timeout,
+            probe_permit: Arc::new(Semaphore::new(1)),
+            probe_timeout: Duration::from_secs(1),
+            pinned: None,
+        }
+    }
+
+    pub fn from_config(pool: PgPool, config: &DatabaseConfig) -> Self {
+        Self::new(pool, config.max_concurrency(), config.queue_timeout())
+            .with_probe_timeout(config.probe_timeout())
+    }
+
+    pub fn with_probe_timeout(mut self, probe_timeout: Duration) -> Self {
+        self.probe_timeout = probe_timeout;
+        self
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = self.pool.state();
+        PoolStats {
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            max_size: self.pool.max_size(),
+            in_flight: self
+                .max_concurrency
+                .saturating_sub(self.permits.available_permits()),
+            max_concurrency: self.max_concurrency,
+        }
+    }
+
+    /// Stops accepting work and waits up to `deadline` for queries already
+    /// running to finish. Returns `false` if some were still in flight.
+    ///
+    /// The pool's connections are released once the last clone of the pool
+    /// is dropped.
+    pub async fn close(&self, deadline: Duration) -> bool {
+        let drained = tokio::time::timeout(
+            deadline,
+            self.permits.acquire_many(self.max_concurrency as u32),
+        )
+        .await
+        .is_ok_and(|permits| permits.is_ok());
+        self.permits.close();
+        drained
+    }
+
+    /// Starts a transaction on a dedicated connection. Calling it on an
+    /// executor that is already in a transaction opens a savepoint instead.
+    pub async fn begin(&self) -> Result<DbExecutor, ApiError> {
+        if self.pinned.is_some() {
+            self.run(|conn| {
+                AnsiTransactionManager::begin_transaction(conn).map_err(ApiError::from)
+            })
+            .await?;
+            return Ok(self.clone());
+        }
+
+        let permit = self.acquire().await?;
+        let pool = self.pool.clone();
+        let conn = tokio::task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            AnsiTransactionManager::begin_transaction(&mut *conn).map_err(ApiError::from)?;
+            Ok::<_, ApiError>(conn)
+        })
+        .await
+        .map_err(|_| ApiError::InternalServerError)??;
+
+        Ok(Self {
+            pinned: Some(Arc::new(PinnedConnection {
+                conn: Mutex::new(conn),
+                _permit: permit,
+            })),
+            ..self.clone()
+        })
+    }
+
+    /// Commits the innermost open transaction or savepoint.
+    pub async fn commit(&self) -> Result<(), ApiError> {
+        self.finish(AnsiTransactionManager::commit_transaction)
+            .await
+    }
+
+    /// Rolls back the innermost open transaction or savepoint.
+    pub async fn rollback(&self) -> Result<(), ApiError> {
+        self.finish(AnsiTransactionManager::rollback_transaction)
+            .await
+    }
+
+    async fn finish<F>(&self, f: F) -> Result<(), ApiError>
+    where
+        F: FnOnce(&mut PgConnection) -> diesel::QueryResult<()> + Send + 'static,
+    {
+        if self.pinned.is_none() {
+            return Err(ApiError::InternalServerError);
+        }
+        self.run(|conn| f(conn).map_err(ApiError::from)).await
+    }
+
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit, ApiError> {
+        tokio::time::timeout(
+            self.acquire_timeout,
+            Arc::clone(&self.permits).acquire_owned(),
+        )
+        .await
+        .map_err(|_| ApiError::ServiceUnavailable)?
+        .map_err(|_| ApiError::ServiceUnavailable)
+    }
+
+    /// Runs `f` for a health check, outside the slots request traffic uses.
+    ///
+    /// Probes run one at a time; one arriving meanwhile waits its turn.
+    /// Whatever does not finish within the probe timeout, the wait and the
+    /// checkout included, fails with `ApiError::ServiceUnavailable`.
+    pub async fn probe<F, T>(&self, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(&mut PgConnection) -> Result<T, ApiError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let deadline = Instant::now() + self.probe_timeout;
+        let probe = async {
+            let permit = Arc::clone(&self.probe_permit)
+                .acquire_owned()
+                .await
+                .map_err(|_| ApiError::ServiceUnavailable)?;
+            // The checkout gets what is left of the budget, so a probe that
+            // is given up on lets go of the slot about when it would have
+            // timed out anyway.
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let pool = self.pool.clone();
+            tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                let mut conn = pool
+                    .get_timeout(remaining)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                f(&mut conn)
+            })
+            .await
+            .map_err(|_| ApiError::InternalServerError)?
+        };
+
+        tokio::time::timeout_at(deadline.into(), probe)
+            .await
+            .map_err(|_| ApiError::ServiceUnavailable)?
+    }
+
+    pub async fn run<F, T>(&self, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(&mut PgConnection) -> Result<T, ApiError> + Send + 'static,
+        T: Send + 'static,
+    {
+        if let Some(pinned) = &self.pinned {
+            let pinned = Arc::clone(pinned);
+            return tokio::task::spawn_blocking(move || {
+                let mut conn = pinned.conn.lock().unwrap_or_else(PoisonError::into_inner);
+                f(&mut conn)
+            })
+            .await
+            .map_err(|_| ApiError::InternalServerError)?;
+        }
+
+        let permit = self.acquire().await?;
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let mut conn = pool
+                .get()
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|_| ApiError::InternalServerError)?
+    }
+}
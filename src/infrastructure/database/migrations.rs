@@ -0,0 +1,27 @@
+use diesel::pg::PgConnection;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+/// Every migration under `migrations/`, compiled into the binary so a deploy
+/// does not need the source tree next to it.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Applies all pending migrations and returns the versions that ran.
+pub fn run_pending(conn: &mut PgConnection) -> anyhow::Result<Vec<String>> {
+    let applied = conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow::anyhow!("Failed to run migrations: {}", e))?;
+
+    Ok(applied.iter().map(ToString::to_string).collect())
+}
+
+/// Lists the versions that have not been applied yet, oldest first.
+pub fn pending(conn: &mut PgConnection) -> anyhow::Result<Vec<String>> {
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow::anyhow!("Failed to read migration status: {}", e))?;
+
+    Ok(pending
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
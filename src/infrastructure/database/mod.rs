@@ -1,2 +1,4 @@
 pub mod connection;
-pub mod schema;
\ No newline at end of file
+pub mod executor;
+pub mod migrations;
+pub mod schema;
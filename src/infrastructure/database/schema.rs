@@ -1,6 +1,38 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
+diesel::table! {
+    categories (id) {
+        id -> Uuid,
+        parent_id -> Nullable<Uuid>,
+        #[max_length = 50]
+        name -> Varchar,
+        #[max_length = 100]
+        slug -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
+    comment_moderation (post_id) {
+        post_id -> Uuid,
+        require_approval -> Nullable<Bool>,
+        auto_approve_trusted -> Nullable<Bool>,
+        hold_first_time -> Nullable<Bool>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
     comments (id) {
         id -> Uuid,
         content -> Text,
@@ -8,17 +40,89 @@ diesel::table! {
         author_id -> Uuid,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        content_html -> Nullable<Text>,
+        search_vector -> Tsvector,
+        parent_id -> Nullable<Uuid>,
+        reply_count -> Int4,
+        deleted_at -> Nullable<Timestamp>,
+        #[max_length = 20]
+        status -> Varchar,
+    }
+}
+
+diesel::table! {
+    post_revisions (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        revision -> Int4,
+        editor_id -> Nullable<Uuid>,
+        #[max_length = 100]
+        title -> Varchar,
+        content -> Text,
+        #[max_length = 20]
+        status -> Varchar,
+        created_at -> Timestamp,
     }
 }
 
 diesel::table! {
+    post_slugs (slug) {
+        #[max_length = 120]
+        slug -> Varchar,
+        post_id -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_tags (post_id, tag_id) {
+        post_id -> Uuid,
+        tag_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
     posts (id) {
         id -> Uuid,
         #[max_length = 100]
         title -> Varchar,
         content -> Text,
         author_id -> Uuid,
-        published -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        #[max_length = 120]
+        slug -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        publish_at -> Nullable<Timestamp>,
+        published_at -> Nullable<Timestamp>,
+        category_id -> Nullable<Uuid>,
+        content_html -> Nullable<Text>,
+        search_vector -> Tsvector,
+    }
+}
+
+diesel::table! {
+    refresh_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        replaced_by -> Nullable<Uuid>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Uuid,
+        #[max_length = 50]
+        name -> Varchar,
+        #[max_length = 100]
+        slug -> Varchar,
         created_at -> Timestamp,
         updated_at -> Timestamp,
     }
@@ -35,15 +139,32 @@ diesel::table! {
         password_hash -> Varchar,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        #[max_length = 20]
+        role -> Varchar,
     }
 }
 
+diesel::joinable!(comment_moderation -> posts (post_id));
 diesel::joinable!(comments -> posts (post_id));
 diesel::joinable!(comments -> users (author_id));
+diesel::joinable!(post_revisions -> posts (post_id));
+diesel::joinable!(post_revisions -> users (editor_id));
+diesel::joinable!(post_slugs -> posts (post_id));
+diesel::joinable!(post_tags -> posts (post_id));
+diesel::joinable!(post_tags -> tags (tag_id));
+diesel::joinable!(posts -> categories (category_id));
 diesel::joinable!(posts -> users (author_id));
+diesel::joinable!(refresh_tokens -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    categories,
+    comment_moderation,
     comments,
+    post_revisions,
+    post_slugs,
+    post_tags,
     posts,
+    refresh_tokens,
+    tags,
     users,
 );
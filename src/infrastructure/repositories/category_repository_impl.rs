@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    domain::models::category::{Category, NewCategory},
+    domain::models::post::PostStatus,
+    domain::repositories::CategoryRepository,
+    infrastructure::database::{
+        executor::DbExecutor,
+        schema::{categories, posts},
+    },
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct CategoryRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl CategoryRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for CategoryRepositoryImpl {
+    async fn find(&self, category_id: Uuid) -> Result<Category, ApiError> {
+        use crate::infrastructure::database::schema::categories::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                categories
+                    .filter(id.eq(category_id))
+                    .select(Category::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_by_slug(&self, category_slug: &str) -> Result<Category, ApiError> {
+        use crate::infrastructure::database::schema::categories::dsl::*;
+
+        let category_slug = category_slug.to_owned();
+        self.db
+            .run(move |conn| {
+                categories
+                    .filter(slug.eq(category_slug))
+                    .select(Category::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Category>, ApiError> {
+        self.db
+            .run(move |conn| {
+                categories::table
+                    .order((categories::name.asc(), categories::id.asc()))
+                    .select(Category::as_select())
+                    .load(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_all_for_update(&self) -> Result<Vec<Category>, ApiError> {
+        self.db
+            .run(move |conn| {
+                categories::table
+                    .order((categories::name.asc(), categories::id.asc()))
+                    .select(Category::as_select())
+                    .for_update()
+                    .load(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn create(&self, category: Category) -> Result<Category, ApiError> {
+        use crate::infrastructure::database::schema::categories::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let new_category = NewCategory {
+                    parent_id: category.parent_id,
+                    name: category.name,
+                    slug: category.slug,
+                    description: category.description,
+                };
+
+                diesel::insert_into(categories)
+                    .values(&new_category)
+                    .returning(Category::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn update(&self, category_id: Uuid, category: Category) -> Result<Category, ApiError> {
+        use crate::infrastructure::database::schema::categories::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                diesel::update(categories.filter(id.eq(category_id)))
+                    .set((
+                        parent_id.eq(category.parent_id),
+                        name.eq(category.name),
+                        slug.eq(category.slug),
+                        description.eq(category.description),
+                        updated_at.eq(chrono::Local::now().naive_local()),
+                    ))
+                    .returning(Category::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn delete(&self, category_id: Uuid) -> Result<(), ApiError> {
+        self.db
+            .run(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    let parent: Option<Uuid> = categories::table
+                        .find(category_id)
+                        .select(categories::parent_id)
+                        .for_update()
+                        .first(conn)?;
+
+                    diesel::update(categories::table.filter(categories::parent_id.eq(category_id)))
+                        .set(categories::parent_id.eq(parent))
+                        .execute(conn)?;
+                    diesel::delete(categories::table.find(category_id)).execute(conn)?;
+                    Ok(())
+                })
+                .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn count_published(&self) -> Result<HashMap<Uuid, i64>, ApiError> {
+        self.db
+            .run(move |conn| {
+                let counts: Vec<(Option<Uuid>, i64)> = posts::table
+                    .filter(posts::category_id.is_not_null())
+                    .filter(posts::status.eq(PostStatus::Published))
+                    .group_by(posts::category_id)
+                    .select((posts::category_id, diesel::dsl::count_star()))
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+                Ok(counts
+                    .into_iter()
+                    .filter_map(|(category_id, count)| Some((category_id?, count)))
+                    .collect())
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for Arc<CategoryRepositoryImpl> {
+    async fn find(&self, id: Uuid) -> Result<Category, ApiError> {
+        self.as_ref().find(id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Category, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Category>, ApiError> {
+        self.as_ref().find_all().await
+    }
+
+    async fn find_all_for_update(&self) -> Result<Vec<Category>, ApiError> {
+        self.as_ref().find_all_for_update().await
+    }
+
+    async fn create(&self, category: Category) -> Result<Category, ApiError> {
+        self.as_ref().create(category).await
+    }
+
+    async fn update(&self, category_id: Uuid, category: Category) -> Result<Category, ApiError> {
+        self.as_ref().update(category_id, category).await
+    }
+
+    async fn delete(&self, category_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(category_id).await
+    }
+
+    async fn count_published(&self) -> Result<HashMap<Uuid, i64>, ApiError> {
+        self.as_ref().count_published().await
+    }
+}
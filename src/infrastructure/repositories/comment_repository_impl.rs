@@ -1,113 +1,472 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use diesel::{
+    QueryDsl, RunQueryDsl,
+    dsl::sql,
+    pg::Pg,
+    prelude::*,
+    sql_types::{Bool, Timestamp},
+};
 use uuid::Uuid;
 
 use crate::{
-    domain::models::comment::{Comment, NewComment, UpdateCommentData},
+    domain::models::comment::{
+        Comment, CommentFilter, CommentQuery, CommentSortField, CommentStatus, CommentStreamQuery,
+        ModerationOverrides, ModerationQueueQuery, NewComment, UpdateCommentData,
+    },
+    domain::models::pagination::{CursorPage, Page, PageRequest, SortDirection},
     domain::repositories::CommentRepository,
-    infrastructure::database::connection::PgPool,
+    infrastructure::database::{
+        executor::DbExecutor,
+        schema::{comment_moderation, comments, posts},
+    },
     shared::error::ApiError,
 };
 
 #[derive(Clone)]
 pub struct CommentRepositoryImpl {
-    pool: PgPool,
+    db: DbExecutor,
 }
 
 impl CommentRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+
+    fn filtered(post: Uuid, filter: &CommentFilter) -> comments::BoxedQuery<'static, Pg> {
+        let mut query = comments::table
+            .filter(comments::post_id.eq(post))
+            .filter(Self::shown())
+            .into_boxed();
+
+        if let Some(author_id) = filter.author_id {
+            query = query.filter(comments::author_id.eq(author_id));
+        }
+        if let Some(status) = filter.status {
+            query = query.filter(comments::status.eq(status));
+        }
+        if let Some(created_after) = filter.created_after {
+            query = query.filter(comments::created_at.ge(created_after));
+        }
+        if let Some(created_before) = filter.created_before {
+            query = query.filter(comments::created_at.lt(created_before));
+        }
+
+        query
+    }
+
+    /// Leaves out tombstones none of whose replies are left to show.
+    fn shown() -> diesel::dsl::Or<
+        diesel::dsl::IsNull<comments::deleted_at>,
+        diesel::dsl::Gt<comments::reply_count, i32>,
+    > {
+        comments::deleted_at
+            .is_null()
+            .or(comments::reply_count.gt(0))
+    }
+
+    fn queued(query: &ModerationQueueQuery) -> comments::BoxedQuery<'static, Pg> {
+        let mut rows = comments::table
+            .filter(comments::status.eq(query.status))
+            .filter(comments::deleted_at.is_null())
+            .into_boxed();
+
+        if let Some(post) = query.post_id {
+            rows = rows.filter(comments::post_id.eq(post));
+        }
+
+        rows
     }
 }
 
 #[async_trait]
 impl CommentRepository for CommentRepositoryImpl {
-    async fn find(&self, _id: Uuid) -> Result<Comment, ApiError> {
+    async fn find(&self, comment_id: Uuid) -> Result<Comment, ApiError> {
+        use crate::infrastructure::database::schema::comments::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                comments
+                    .filter(id.eq(comment_id))
+                    .select(Comment::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_by_post(
+        &self,
+        post: Uuid,
+        query: &CommentQuery,
+    ) -> Result<Page<Comment>, ApiError> {
         use crate::infrastructure::database::schema::comments::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let total = Self::filtered(post, &query.filter)
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
 
-        comments
-            .filter(id.eq(id))
-            .select(Comment::as_select())
-            .first(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                let items = Self::filtered(post, &query.filter).select(Comment::as_select());
+                let items = match (query.sort, query.direction) {
+                    (CommentSortField::CreatedAt, SortDirection::Asc) => {
+                        items.order(created_at.asc())
+                    }
+                    (CommentSortField::CreatedAt, SortDirection::Desc) => {
+                        items.order(created_at.desc())
+                    }
+                    (CommentSortField::UpdatedAt, SortDirection::Asc) => {
+                        items.order(updated_at.asc())
+                    }
+                    (CommentSortField::UpdatedAt, SortDirection::Desc) => {
+                        items.order(updated_at.desc())
+                    }
+                };
+                let items = match query.direction {
+                    SortDirection::Asc => items.then_order_by(id.asc()),
+                    SortDirection::Desc => items.then_order_by(id.desc()),
+                };
+
+                let items = items
+                    .limit(query.page.limit)
+                    .offset(query.page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, query.page))
+            })
+            .await
     }
 
-    async fn find_by_post(&self, _post_id: Uuid) -> Result<Vec<Comment>, ApiError> {
+    async fn stream_by_post(
+        &self,
+        post: Uuid,
+        query: &CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError> {
         use crate::infrastructure::database::schema::comments::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let mut items = Self::filtered(post, &query.filter).select(Comment::as_select());
+
+                // Seek past the cursor instead of skipping rows so concurrent inserts
+                // cannot shift a page. The row comparison lets Postgres start a range
+                // scan of the `(post_id, status, created_at, id)` index at the cursor,
+                // which is what keeps deep pages as cheap as the first one.
+                if let Some(after) = query.after {
+                    let operator = match query.direction {
+                        SortDirection::Asc => ">",
+                        SortDirection::Desc => "<",
+                    };
+                    items = items.filter(
+                        sql::<Bool>(&format!(
+                            "(comments.created_at, comments.id) {} (",
+                            operator
+                        ))
+                        .bind::<Timestamp, _>(after.created_at)
+                        .sql(", ")
+                        .bind::<diesel::sql_types::Uuid, _>(after.id)
+                        .sql(")"),
+                    );
+                }
+
+                let items = match query.direction {
+                    SortDirection::Asc => items.order((created_at.asc(), id.asc())),
+                    SortDirection::Desc => items.order((created_at.desc(), id.desc())),
+                };
 
-        comments
-            .filter(post_id.eq(post_id))
-            .select(Comment::as_select())
-            .load(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                // Fetch one extra row to learn whether another page exists.
+                let mut items: Vec<Comment> = items
+                    .limit(query.limit + 1)
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                let next = if items.len() as i64 > query.limit {
+                    items.truncate(query.limit as usize);
+                    items.last().map(Comment::cursor_position)
+                } else {
+                    None
+                };
+
+                Ok(CursorPage {
+                    items,
+                    next,
+                    limit: query.limit,
+                })
+            })
+            .await
+    }
+
+    async fn find_top_level(
+        &self,
+        post: Uuid,
+        status: CommentStatus,
+        page: PageRequest,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.db
+            .run(move |conn| {
+                let top_level = || {
+                    comments::table
+                        .filter(comments::post_id.eq(post))
+                        .filter(comments::parent_id.is_null())
+                        .filter(comments::status.eq(status))
+                        .filter(Self::shown())
+                };
+                let total = top_level()
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
+
+                let items = top_level()
+                    .select(Comment::as_select())
+                    .order((comments::created_at.asc(), comments::id.asc()))
+                    .limit(page.limit)
+                    .offset(page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, page))
+            })
+            .await
+    }
+
+    async fn find_replies(
+        &self,
+        parent_ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        let mut parents = parent_ids.to_vec();
+        self.db
+            .run(move |conn| {
+                // One level per pass; replies nest at most MAX_REPLY_DEPTH deep.
+                let mut replies = Vec::new();
+                while !parents.is_empty() {
+                    let level: Vec<Comment> = comments::table
+                        .filter(comments::parent_id.eq_any(&parents))
+                        .filter(comments::status.eq(status))
+                        .filter(Self::shown())
+                        .select(Comment::as_select())
+                        .load(conn)
+                        .map_err(ApiError::from)?;
+                    parents = level.iter().map(|reply| reply.id).collect();
+                    replies.extend(level);
+                }
+                Ok(replies)
+            })
+            .await
+    }
+
+    async fn find_queue(&self, query: &ModerationQueueQuery) -> Result<Page<Comment>, ApiError> {
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let total = Self::queued(&query)
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
+
+                let items = Self::queued(&query)
+                    .select(Comment::as_select())
+                    .order((comments::created_at.asc(), comments::id.asc()))
+                    .limit(query.page.limit)
+                    .offset(query.page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, query.page))
+            })
+            .await
+    }
+
+    async fn has_approved(&self, author: Uuid) -> Result<bool, ApiError> {
+        self.db
+            .run(move |conn| {
+                diesel::select(diesel::dsl::exists(
+                    comments::table
+                        .filter(comments::author_id.eq(author))
+                        .filter(comments::status.eq(CommentStatus::Approved)),
+                ))
+                .get_result(conn)
+                .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn create(&self, comment: Comment) -> Result<Comment, ApiError> {
         use crate::infrastructure::database::schema::comments::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        let new_comment = NewComment {
-            content: comment.content,
-            post_id: comment.post_id,
-            author_id: comment.author_id,
-        };
+        self.db
+            .run(move |conn| {
+                let new_comment = NewComment {
+                    content: comment.content,
+                    post_id: comment.post_id,
+                    author_id: comment.author_id,
+                    content_html: comment.content_html,
+                    parent_id: comment.parent_id,
+                    status: comment.status,
+                };
 
-        diesel::insert_into(comments)
-            .values(&new_comment)
-            .returning(Comment::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                diesel::insert_into(comments)
+                    .values(&new_comment)
+                    .returning(Comment::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn update(&self, comment_id: Uuid, comment: Comment) -> Result<Comment, ApiError> {
         use crate::infrastructure::database::schema::comments::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        self.db
+            .run(move |conn| {
+                let update_data = UpdateCommentData {
+                    content: Some(comment.content),
+                    updated_at: Some(chrono::Local::now().naive_local()),
+                    content_html: Some(comment.content_html),
+                    status: Some(comment.status),
+                };
 
-        let update_data = UpdateCommentData {
-            content: Some(comment.content),
-            updated_at: Some(chrono::Local::now().naive_local()),
-        };
-
-        diesel::update(comments.filter(id.eq(comment_id)))
-            .set(&update_data)
-            .returning(Comment::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                diesel::update(comments.filter(id.eq(comment_id)))
+                    .set(&update_data)
+                    .returning(Comment::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn delete(&self, comment_id: Uuid) -> Result<(), ApiError> {
-        use crate::infrastructure::database::schema::comments::dsl::*;
+        self.db
+            .run(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    // The lock keeps replies from arriving until this is done.
+                    let mut parent: Option<Uuid> = comments::table
+                        .find(comment_id)
+                        .select(comments::parent_id)
+                        .for_update()
+                        .first(conn)?;
+                    // Held replies count too: the tombstone stays for them,
+                    // though readers only see it once one is approved.
+                    let has_replies = |conn: &mut PgConnection, comment_id: Uuid| {
+                        diesel::select(diesel::dsl::exists(
+                            comments::table.filter(comments::parent_id.eq(comment_id)),
+                        ))
+                        .get_result::<bool>(conn)
+                    };
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                    if has_replies(conn, comment_id)? {
+                        let now = chrono::Local::now().naive_local();
+                        diesel::update(comments::table.find(comment_id))
+                            .set((
+                                comments::content.eq(""),
+                                comments::content_html.eq(Some("")),
+                                comments::deleted_at.eq(Some(now)),
+                                comments::updated_at.eq(now),
+                            ))
+                            .execute(conn)?;
+                        return Ok(());
+                    }
+
+                    diesel::delete(comments::table.find(comment_id)).execute(conn)?;
+                    // Tombstones only stand in for their replies, so any left
+                    // without one go too. The trigger has already updated the
+                    // parent's count.
+                    while let Some(parent_id) = parent {
+                        if has_replies(conn, parent_id)? {
+                            break;
+                        }
+                        parent = match diesel::delete(
+                            comments::table
+                                .find(parent_id)
+                                .filter(comments::deleted_at.is_not_null()),
+                        )
+                        .returning(comments::parent_id)
+                        .get_result(conn)
+                        .optional()?
+                        {
+                            Some(grandparent) => grandparent,
+                            None => break,
+                        };
+                    }
+                    Ok(())
+                })
+                .map_err(ApiError::from)
+            })
+            .await
+    }
 
-        diesel::delete(comments.filter(id.eq(comment_id)))
-            .execute(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    async fn set_status(
+        &self,
+        ids: &[Uuid],
+        new_status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        let ids = ids.to_vec();
+        self.db
+            .run(move |conn| {
+                let mut updated: Vec<Comment> = diesel::update(
+                    comments::table
+                        .filter(comments::id.eq_any(ids))
+                        .filter(comments::deleted_at.is_null()),
+                )
+                .set(comments::status.eq(new_status))
+                .returning(Comment::as_returning())
+                .get_results(conn)
+                .map_err(ApiError::from)?;
 
-        Ok(())
+                updated.sort_by_key(|comment| (comment.created_at, comment.id));
+                Ok(updated)
+            })
+            .await
+    }
+
+    async fn find_moderation(&self, post: Uuid) -> Result<ModerationOverrides, ApiError> {
+        self.db
+            .run(move |conn| {
+                let (post_id, require_approval, auto_approve_trusted, hold_first_time) =
+                    posts::table
+                        .left_join(comment_moderation::table)
+                        .filter(posts::id.eq(post))
+                        .select((
+                            posts::id,
+                            comment_moderation::require_approval.nullable(),
+                            comment_moderation::auto_approve_trusted.nullable(),
+                            comment_moderation::hold_first_time.nullable(),
+                        ))
+                        .first(conn)
+                        .map_err(ApiError::from)?;
+
+                Ok(ModerationOverrides {
+                    post_id,
+                    require_approval,
+                    auto_approve_trusted,
+                    hold_first_time,
+                })
+            })
+            .await
+    }
+
+    async fn save_moderation(
+        &self,
+        overrides: ModerationOverrides,
+    ) -> Result<ModerationOverrides, ApiError> {
+        self.db
+            .run(move |conn| {
+                diesel::insert_into(comment_moderation::table)
+                    .values(&overrides)
+                    .on_conflict(comment_moderation::post_id)
+                    .do_update()
+                    .set(&overrides)
+                    .returning(ModerationOverrides::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 }
 
@@ -117,8 +476,45 @@ impl CommentRepository for Arc<CommentRepositoryImpl> {
         self.as_ref().find(id).await
     }
 
-    async fn find_by_post(&self, post_id: Uuid) -> Result<Vec<Comment>, ApiError> {
-        self.as_ref().find_by_post(post_id).await
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentQuery,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_by_post(post_id, query).await
+    }
+
+    async fn stream_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError> {
+        self.as_ref().stream_by_post(post_id, query).await
+    }
+
+    async fn find_top_level(
+        &self,
+        post_id: Uuid,
+        status: CommentStatus,
+        page: PageRequest,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_top_level(post_id, status, page).await
+    }
+
+    async fn find_replies(
+        &self,
+        parent_ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        self.as_ref().find_replies(parent_ids, status).await
+    }
+
+    async fn find_queue(&self, query: &ModerationQueueQuery) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_queue(query).await
+    }
+
+    async fn has_approved(&self, author_id: Uuid) -> Result<bool, ApiError> {
+        self.as_ref().has_approved(author_id).await
     }
 
     async fn create(&self, comment: Comment) -> Result<Comment, ApiError> {
@@ -132,4 +528,23 @@ impl CommentRepository for Arc<CommentRepositoryImpl> {
     async fn delete(&self, comment_id: Uuid) -> Result<(), ApiError> {
         self.as_ref().delete(comment_id).await
     }
+
+    async fn set_status(
+        &self,
+        ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        self.as_ref().set_status(ids, status).await
+    }
+
+    async fn find_moderation(&self, post_id: Uuid) -> Result<ModerationOverrides, ApiError> {
+        self.as_ref().find_moderation(post_id).await
+    }
+
+    async fn save_moderation(
+        &self,
+        overrides: ModerationOverrides,
+    ) -> Result<ModerationOverrides, ApiError> {
+        self.as_ref().save_moderation(overrides).await
+    }
 }
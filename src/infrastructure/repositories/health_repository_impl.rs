@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use diesel::RunQueryDsl;
+
+use crate::{
+    domain::models::health::DatabaseCheck,
+    domain::repositories::HealthRepository,
+    infrastructure::database::{executor::DbExecutor, migrations},
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct HealthRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl HealthRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl HealthRepository for HealthRepositoryImpl {
+    async fn check_database(&self) -> DatabaseCheck {
+        let started = Instant::now();
+
+        let result = self
+            .db
+            .probe(|conn| {
+                diesel::sql_query("SELECT 1")
+                    .execute(conn)
+                    .map_err(ApiError::from)?;
+
+                migrations::pending(conn).map_err(|e| ApiError::DatabaseError(e.to_string()))
+            })
+            .await;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let pool = self.db.stats();
+
+        match result {
+            Ok(pending) => DatabaseCheck {
+                reachable: true,
+                latency_ms,
+                pending_migrations: Some(pending),
+                pool,
+            },
+            Err(e) => {
+                eprintln!("Readiness check failed: {}", e);
+                DatabaseCheck {
+                    reachable: false,
+                    latency_ms,
+                    pending_migrations: None,
+                    pool,
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl HealthRepository for Arc<HealthRepositoryImpl> {
+    async fn check_database(&self) -> DatabaseCheck {
+        self.as_ref().check_database().await
+    }
+}
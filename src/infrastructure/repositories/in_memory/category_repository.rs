@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, Tables, now};
+use crate::{
+    domain::models::category::Category, domain::models::post::PostStatus,
+    domain::repositories::CategoryRepository, shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryCategoryRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryCategoryRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Enforces the `UNIQUE` constraint on `categories.slug` and the foreign key
+/// on `categories.parent_id`.
+fn ensure_valid(
+    tables: &Tables,
+    category: &Category,
+    except: Option<Uuid>,
+) -> Result<(), ApiError> {
+    if tables
+        .categories
+        .values()
+        .any(|other| Some(other.id) != except && other.slug == category.slug)
+    {
+        return Err(ApiError::Conflict {
+            field: "slug".to_string(),
+        });
+    }
+    if category
+        .parent_id
+        .is_some_and(|parent| !tables.categories.contains_key(&parent))
+    {
+        return Err(ApiError::InvalidReference {
+            field: "parent_id".to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl CategoryRepository for InMemoryCategoryRepository {
+    async fn find(&self, id: Uuid) -> Result<Category, ApiError> {
+        self.store
+            .read()
+            .categories
+            .get(&id)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Category, ApiError> {
+        self.store
+            .read()
+            .categories
+            .values()
+            .find(|category| category.slug == slug)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_all(&self) -> Result<Vec<Category>, ApiError> {
+        let mut rows: Vec<Category> = self.store.read().categories.values().cloned().collect();
+        rows.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        Ok(rows)
+    }
+
+    async fn find_all_for_update(&self) -> Result<Vec<Category>, ApiError> {
+        // Units of work already run one at a time.
+        self.find_all().await
+    }
+
+    async fn create(&self, category: Category) -> Result<Category, ApiError> {
+        let mut tables = self.store.write();
+        ensure_valid(&tables, &category, None)?;
+
+        let created_at = now();
+        let category = Category {
+            id: Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            ..category
+        };
+        tables.categories.insert(category.id, category.clone());
+        Ok(category)
+    }
+
+    async fn update(&self, id: Uuid, category: Category) -> Result<Category, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.categories.contains_key(&id) {
+            return Err(ApiError::NotFound);
+        }
+        ensure_valid(&tables, &category, Some(id))?;
+
+        let existing = tables.categories.get_mut(&id).ok_or(ApiError::NotFound)?;
+        existing.parent_id = category.parent_id;
+        existing.name = category.name;
+        existing.slug = category.slug;
+        existing.description = category.description;
+        existing.updated_at = now();
+        Ok(existing.clone())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+        let mut tables = self.store.write();
+        let removed = tables.categories.remove(&id).ok_or(ApiError::NotFound)?;
+
+        for child in tables.categories.values_mut() {
+            if child.parent_id == Some(id) {
+                child.parent_id = removed.parent_id;
+            }
+        }
+        for post in tables.posts.values_mut() {
+            if post.category_id == Some(id) {
+                post.category_id = None;
+            }
+        }
+        Ok(())
+    }
+
+    async fn count_published(&self) -> Result<HashMap<Uuid, i64>, ApiError> {
+        let mut counts = HashMap::new();
+        for post in self.store.read().posts.values() {
+            if let Some(category_id) = post.category_id
+                && post.status == PostStatus::Published
+            {
+                *counts.entry(category_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for Arc<InMemoryCategoryRepository> {
+    async fn find(&self, id: Uuid) -> Result<Category, ApiError> {
+        self.as_ref().find(id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Category, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Category>, ApiError> {
+        self.as_ref().find_all().await
+    }
+
+    async fn find_all_for_update(&self) -> Result<Vec<Category>, ApiError> {
+        self.as_ref().find_all_for_update().await
+    }
+
+    async fn create(&self, category: Category) -> Result<Category, ApiError> {
+        self.as_ref().create(category).await
+    }
+
+    async fn update(&self, category_id: Uuid, category: Category) -> Result<Category, ApiError> {
+        self.as_ref().update(category_id, category).await
+    }
+
+    async fn delete(&self, category_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(category_id).await
+    }
+
+    async fn count_published(&self) -> Result<HashMap<Uuid, i64>, ApiError> {
+        self.as_ref().count_published().await
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, in_range, now, paginate, sort_rows};
+use crate::{
+    domain::models::comment::{
+        Comment, CommentFilter, CommentQuery, CommentSortField, CommentStatus, CommentStreamQuery,
+        ModerationOverrides, ModerationQueueQuery,
+    },
+    domain::models::pagination::{CursorPage, Page, PageRequest, SortDirection},
+    domain::repositories::CommentRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryCommentRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryCommentRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+
+    fn filtered(&self, post_id: Uuid, filter: &CommentFilter) -> Vec<Comment> {
+        self.store
+            .read()
+            .comments
+            .values()
+            .filter(|comment| comment.post_id == post_id && !comment.is_hidden_tombstone())
+            .filter(|comment| {
+                filter
+                    .author_id
+                    .is_none_or(|author| comment.author_id == author)
+            })
+            .filter(|comment| filter.status.is_none_or(|status| comment.status == status))
+            .filter(|comment| {
+                in_range(
+                    comment.created_at,
+                    filter.created_after,
+                    filter.created_before,
+                )
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CommentRepository for InMemoryCommentRepository {
+    async fn find(&self, id: Uuid) -> Result<Comment, ApiError> {
+        self.store
+            .read()
+            .comments
+            .get(&id)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentQuery,
+    ) -> Result<Page<Comment>, ApiError> {
+        let mut rows = self.filtered(post_id, &query.filter);
+
+        sort_rows(
+            &mut rows,
+            query.direction,
+            |comment| comment.id,
+            |a, b| match query.sort {
+                CommentSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                CommentSortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            },
+        );
+
+        Ok(paginate(rows, query.page))
+    }
+
+    async fn stream_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError> {
+        let mut rows = self.filtered(post_id, &query.filter);
+
+        if let Some(after) = query.after {
+            rows.retain(|comment| {
+                let position = (comment.created_at, comment.id);
+                let after = (after.created_at, after.id);
+                match query.direction {
+                    SortDirection::Asc => position > after,
+                    SortDirection::Desc => position < after,
+                }
+            });
+        }
+
+        sort_rows(
+            &mut rows,
+            query.direction,
+            |comment| comment.id,
+            |a, b| a.created_at.cmp(&b.created_at),
+        );
+
+        let limit = query.limit.max(0) as usize;
+        let next = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(Comment::cursor_position)
+        } else {
+            None
+        };
+
+        Ok(CursorPage {
+            items: rows,
+            next,
+            limit: query.limit,
+        })
+    }
+
+    async fn find_top_level(
+        &self,
+        post_id: Uuid,
+        status: CommentStatus,
+        page: PageRequest,
+    ) -> Result<Page<Comment>, ApiError> {
+        let filter = CommentFilter {
+            status: Some(status),
+            ..CommentFilter::default()
+        };
+        let mut rows = self.filtered(post_id, &filter);
+        rows.retain(|comment| comment.parent_id.is_none());
+        sort_rows(
+            &mut rows,
+            SortDirection::Asc,
+            |comment| comment.id,
+            |a, b| a.created_at.cmp(&b.created_at),
+        );
+        Ok(paginate(rows, page))
+    }
+
+    async fn find_replies(
+        &self,
+        parent_ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        let tables = self.store.read();
+        let mut parents: HashSet<Uuid> = parent_ids.iter().copied().collect();
+        let mut replies = Vec::new();
+        while !parents.is_empty() {
+            let level: Vec<Comment> = tables
+                .comments
+                .values()
+                .filter(|comment| comment.parent_id.is_some_and(|id| parents.contains(&id)))
+                .filter(|comment| comment.status == status && !comment.is_hidden_tombstone())
+                .cloned()
+                .collect();
+            parents = level.iter().map(|reply| reply.id).collect();
+            replies.extend(level);
+        }
+        Ok(replies)
+    }
+
+    async fn find_queue(&self, query: &ModerationQueueQuery) -> Result<Page<Comment>, ApiError> {
+        let mut rows: Vec<Comment> = self
+            .store
+            .read()
+            .comments
+            .values()
+            .filter(|comment| comment.status == query.status && comment.deleted_at.is_none())
+            .filter(|comment| query.post_id.is_none_or(|post| comment.post_id == post))
+            .cloned()
+            .collect();
+
+        sort_rows(
+            &mut rows,
+            SortDirection::Asc,
+            |comment| comment.id,
+            |a, b| a.created_at.cmp(&b.created_at),
+        );
+
+        Ok(paginate(rows, query.page))
+    }
+
+    async fn has_approved(&self, author_id: Uuid) -> Result<bool, ApiError> {
+        Ok(self.store.read().comments.values().any(|comment| {
+            comment.author_id == author_id && comment.status == CommentStatus::Approved
+        }))
+    }
+
+    async fn create(&self, comment: Comment) -> Result<Comment, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.posts.contains_key(&comment.post_id) {
+            return Err(ApiError::InvalidReference {
+                field: "post_id".to_string(),
+            });
+        }
+        if !tables.users.contains_key(&comment.author_id) {
+            return Err(ApiError::InvalidReference {
+                field: "author_id".to_string(),
+            });
+        }
+
+        if comment
+            .parent_id
+            .is_some_and(|parent_id| !tables.comments.contains_key(&parent_id))
+        {
+            return Err(ApiError::InvalidReference {
+                field: "parent_id".to_string(),
+            });
+        }
+
+        let created_at = now();
+        let comment = Comment {
+            id: Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            reply_count: 0,
+            deleted_at: None,
+            ..comment
+        };
+        tables.comments.insert(comment.id, comment.clone());
+        tables.count_replies(comment.parent_id);
+        Ok(comment)
+    }
+
+    async fn update(&self, id: Uuid, comment: Comment) -> Result<Comment, ApiError> {
+        let mut tables = self.store.write();
+        let existing = tables.comments.get_mut(&id).ok_or(ApiError::NotFound)?;
+        existing.content = comment.content;
+        existing.content_html = comment.content_html;
+        existing.status = comment.status;
+        existing.updated_at = now();
+        let updated = existing.clone();
+        tables.count_replies(updated.parent_id);
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+        let mut tables = self.store.write();
+        // Held replies count too: the tombstone stays for them, though
+        // readers only see it once one is approved.
+        let has_replies = tables.has_replies(id);
+        let comment = tables.comments.get_mut(&id).ok_or(ApiError::NotFound)?;
+        if has_replies {
+            let deleted_at = now();
+            comment.content = String::new();
+            comment.content_html = Some(String::new());
+            comment.deleted_at = Some(deleted_at);
+            comment.updated_at = deleted_at;
+            let parent = comment.parent_id;
+            tables.count_replies(parent);
+            return Ok(());
+        }
+
+        let mut parent = tables
+            .remove_comment(id)
+            .and_then(|comment| comment.parent_id);
+        while let Some(parent_id) = parent {
+            let orphaned = tables
+                .comments
+                .get(&parent_id)
+                .is_some_and(|parent| parent.deleted_at.is_some())
+                && !tables.has_replies(parent_id);
+            if !orphaned {
+                break;
+            }
+            parent = tables
+                .remove_comment(parent_id)
+                .and_then(|comment| comment.parent_id);
+        }
+        Ok(())
+    }
+
+    async fn set_status(
+        &self,
+        ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        let ids: HashSet<&Uuid> = ids.iter().collect();
+        let mut tables = self.store.write();
+        let mut updated: Vec<Comment> = tables
+            .comments
+            .values_mut()
+            .filter(|comment| ids.contains(&comment.id) && comment.deleted_at.is_none())
+            .map(|comment| {
+                comment.status = status;
+                comment.clone()
+            })
+            .collect();
+        for comment in &updated {
+            tables.count_replies(comment.parent_id);
+        }
+
+        updated.sort_by_key(|comment| (comment.created_at, comment.id));
+        Ok(updated)
+    }
+
+    async fn find_moderation(&self, post_id: Uuid) -> Result<ModerationOverrides, ApiError> {
+        let tables = self.store.read();
+        if !tables.posts.contains_key(&post_id) {
+            return Err(ApiError::NotFound);
+        }
+
+        Ok(tables
+            .comment_moderation
+            .get(&post_id)
+            .cloned()
+            .unwrap_or(ModerationOverrides {
+                post_id,
+                ..ModerationOverrides::default()
+            }))
+    }
+
+    async fn save_moderation(
+        &self,
+        overrides: ModerationOverrides,
+    ) -> Result<ModerationOverrides, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.posts.contains_key(&overrides.post_id) {
+            return Err(ApiError::InvalidReference {
+                field: "post_id".to_string(),
+            });
+        }
+
+        tables
+            .comment_moderation
+            .insert(overrides.post_id, overrides.clone());
+        Ok(overrides)
+    }
+}
+
+#[async_trait]
+impl CommentRepository for Arc<InMemoryCommentRepository> {
+    async fn find(&self, id: Uuid) -> Result<Comment, ApiError> {
+        self.as_ref().find(id).await
+    }
+
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentQuery,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_by_post(post_id, query).await
+    }
+
+    async fn stream_by_post(
+        &self,
+        post_id: Uuid,
+        query: &CommentStreamQuery,
+    ) -> Result<CursorPage<Comment>, ApiError> {
+        self.as_ref().stream_by_post(post_id, query).await
+    }
+
+    async fn find_top_level(
+        &self,
+        post_id: Uuid,
+        status: CommentStatus,
+        page: PageRequest,
+    ) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_top_level(post_id, status, page).await
+    }
+
+    async fn find_replies(
+        &self,
+        parent_ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        self.as_ref().find_replies(parent_ids, status).await
+    }
+
+    async fn find_queue(&self, query: &ModerationQueueQuery) -> Result<Page<Comment>, ApiError> {
+        self.as_ref().find_queue(query).await
+    }
+
+    async fn has_approved(&self, author_id: Uuid) -> Result<bool, ApiError> {
+        self.as_ref().has_approved(author_id).await
+    }
+
+    async fn create(&self, comment: Comment) -> Result<Comment, ApiError> {
+        self.as_ref().create(comment).await
+    }
+
+    async fn update(&self, comment_id: Uuid, comment: Comment) -> Result<Comment, ApiError> {
+        self.as_ref().update(comment_id, comment).await
+    }
+
+    async fn delete(&self, comment_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(comment_id).await
+    }
+
+    async fn set_status(
+        &self,
+        ids: &[Uuid],
+        status: CommentStatus,
+    ) -> Result<Vec<Comment>, ApiError> {
+        self.as_ref().set_status(ids, status).await
+    }
+
+    async fn find_moderation(&self, post_id: Uuid) -> Result<ModerationOverrides, ApiError> {
+        self.as_ref().find_moderation(post_id).await
+    }
+
+    async fn save_moderation(
+        &self,
+        overrides: ModerationOverrides,
+    ) -> Result<ModerationOverrides, ApiError> {
+        self.as_ref().save_moderation(overrides).await
+    }
+}
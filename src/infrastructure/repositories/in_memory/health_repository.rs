@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    domain::models::health::{DatabaseCheck, PoolStats},
+    domain::repositories::HealthRepository,
+};
+
+/// Always healthy: there is no connection to lose and no schema to migrate.
+#[derive(Clone, Default)]
+pub struct InMemoryHealthRepository;
+
+impl InMemoryHealthRepository {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl HealthRepository for InMemoryHealthRepository {
+    async fn check_database(&self) -> DatabaseCheck {
+        DatabaseCheck {
+            reachable: true,
+            latency_ms: 0,
+            pending_migrations: Some(Vec::new()),
+            pool: PoolStats {
+                connections: 0,
+                idle_connections: 0,
+                max_size: 0,
+                in_flight: 0,
+                max_concurrency: 0,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl HealthRepository for Arc<InMemoryHealthRepository> {
+    async fn check_database(&self) -> DatabaseCheck {
+        self.as_ref().check_database().await
+    }
+}
@@ -0,0 +1,203 @@
+//! Repositories backed by process memory instead of Postgres.
+//!
+//! They mirror what the Diesel implementations do: ids and timestamps are
+//! assigned on insert, usernames and emails are unique, foreign keys are
+//! checked and deletes cascade the way the migrations' `ON DELETE CASCADE`
+//! clauses do. Every repository built from the same [`InMemoryStore`] shares
+//! its tables, which is what lets those rules hold across repositories.
+
+pub mod category_repository;
+pub mod comment_repository;
+pub mod health_repository;
+pub mod post_repository;
+pub mod post_revision_repository;
+pub mod refresh_token_repository;
+pub mod search_repository;
+pub mod tag_repository;
+pub mod unit_of_work;
+pub mod user_repository;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use chrono::{NaiveDateTime, SubsecRound};
+use uuid::Uuid;
+
+use crate::domain::models::{
+    category::Category,
+    comment::{Comment, ModerationOverrides},
+    pagination::{Page, PageRequest, SortDirection},
+    post::Post,
+    post_revision::PostRevision,
+    refresh_token::RefreshToken,
+    tag::Tag,
+    user::User,
+};
+
+#[derive(Clone, Default)]
+pub struct InMemoryStore {
+    tables: Arc<RwLock<Tables>>,
+    /// Held for the whole of a transaction, so only one runs at a time.
+    transactions: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A panic while holding the lock cannot leave a table half-written: every
+    // mutation is a single insert or remove, so the data is still usable.
+    fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Tables {
+    users: HashMap<Uuid, User>,
+    posts: HashMap<Uuid, Post>,
+    /// Former slugs, mapped to the post they redirect to.
+    post_slugs: HashMap<String, Uuid>,
+    post_revisions: HashMap<Uuid, PostRevision>,
+    categories: HashMap<Uuid, Category>,
+    tags: HashMap<Uuid, Tag>,
+    /// `(post_id, tag_id)` pairs.
+    post_tags: HashSet<(Uuid, Uuid)>,
+    comments: HashMap<Uuid, Comment>,
+    /// Keyed by post.
+    comment_moderation: HashMap<Uuid, ModerationOverrides>,
+    refresh_tokens: HashMap<Uuid, RefreshToken>,
+}
+
+impl Tables {
+    fn delete_user(&mut self, id: Uuid) -> bool {
+        if self.users.remove(&id).is_none() {
+            return false;
+        }
+
+        let posts: Vec<Uuid> = self
+            .posts
+            .values()
+            .filter(|post| post.author_id == id)
+            .map(|post| post.id)
+            .collect();
+        for post in posts {
+            self.delete_post(post);
+        }
+        let comments: Vec<Uuid> = self
+            .comments
+            .values()
+            .filter(|comment| comment.author_id == id)
+            .map(|comment| comment.id)
+            .collect();
+        for comment in comments {
+            self.remove_comment(comment);
+        }
+        for revision in self.post_revisions.values_mut() {
+            if revision.editor_id == Some(id) {
+                revision.editor_id = None;
+            }
+        }
+        self.refresh_tokens.retain(|_, token| token.user_id != id);
+        true
+    }
+
+    fn delete_post(&mut self, id: Uuid) -> bool {
+        if self.posts.remove(&id).is_none() {
+            return false;
+        }
+
+        self.post_slugs.retain(|_, post_id| *post_id != id);
+        self.post_revisions
+            .retain(|_, revision| revision.post_id != id);
+        self.post_tags.retain(|(post_id, _)| *post_id != id);
+        self.comments.retain(|_, comment| comment.post_id != id);
+        self.comment_moderation.remove(&id);
+        true
+    }
+
+    /// Removes a comment the way a `DELETE` does: its parent loses a reply
+    /// and its replies move to the top level.
+    fn remove_comment(&mut self, id: Uuid) -> Option<Comment> {
+        let comment = self.comments.remove(&id)?;
+        for reply in self.comments.values_mut() {
+            if reply.parent_id == Some(id) {
+                reply.parent_id = None;
+            }
+        }
+        self.count_replies(comment.parent_id);
+        Some(comment)
+    }
+
+    /// Recounts the public replies of `parent` the way the trigger does, and
+    /// of each comment above it, since that can change whether it is public.
+    fn count_replies(&mut self, mut parent: Option<Uuid>) {
+        while let Some(parent_id) = parent {
+            let count = self
+                .comments
+                .values()
+                .filter(|reply| reply.parent_id == Some(parent_id) && reply.is_public())
+                .count() as i32;
+            let Some(comment) = self.comments.get_mut(&parent_id) else {
+                break;
+            };
+            comment.reply_count = count;
+            parent = comment.parent_id;
+        }
+    }
+
+    /// Whether any comment replies to `id`, held ones included.
+    fn has_replies(&self, id: Uuid) -> bool {
+        self.comments
+            .values()
+            .any(|comment| comment.parent_id == Some(id))
+    }
+}
+
+/// The current time at the precision Postgres stores a `TIMESTAMP` with, so
+/// values round-trip through cursors exactly as they do with the database.
+fn now() -> NaiveDateTime {
+    chrono::Local::now().naive_local().trunc_subsecs(6)
+}
+
+/// Orders by `compare`, breaking ties on the id in the same direction, as
+/// the Diesel listings do.
+fn sort_rows<T>(
+    rows: &mut [T],
+    direction: SortDirection,
+    id: impl Fn(&T) -> Uuid,
+    compare: impl Fn(&T, &T) -> Ordering,
+) {
+    rows.sort_by(|a, b| {
+        let ordering = compare(a, b).then_with(|| id(a).cmp(&id(b)));
+        match direction {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        }
+    });
+}
+
+fn paginate<T>(rows: Vec<T>, request: PageRequest) -> Page<T> {
+    let total = rows.len() as i64;
+    let items = rows
+        .into_iter()
+        .skip(request.offset() as usize)
+        .take(request.limit as usize)
+        .collect();
+
+    Page::new(items, total, request)
+}
+
+fn in_range(
+    created_at: NaiveDateTime,
+    after: Option<NaiveDateTime>,
+    before: Option<NaiveDateTime>,
+) -> bool {
+    after.is_none_or(|after| created_at >= after) && before.is_none_or(|before| created_at < before)
+}
@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::{InMemoryStore, Tables, in_range, now, paginate, sort_rows};
+use crate::{
+    domain::models::pagination::Page,
+    domain::models::post::{Post, PostQuery, PostSortField, PostStatus},
+    domain::models::slug,
+    domain::repositories::PostRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryPostRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryPostRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PostRepository for InMemoryPostRepository {
+    async fn find(&self, id: Uuid) -> Result<Post, ApiError> {
+        self.store
+            .read()
+            .posts
+            .get(&id)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_for_update(&self, id: Uuid) -> Result<Post, ApiError> {
+        // Units of work already run one at a time.
+        self.find(id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Post, ApiError> {
+        let tables = self.store.read();
+        tables
+            .posts
+            .values()
+            .find(|post| post.slug == slug)
+            .or_else(|| {
+                tables
+                    .post_slugs
+                    .get(slug)
+                    .and_then(|id| tables.posts.get(id))
+            })
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        let tables = self.store.read();
+        let current = tables.posts.values().map(|post| (&post.slug, post.id));
+        let former = tables.post_slugs.iter().map(|(slug, id)| (slug, *id));
+        Ok(current
+            .chain(former)
+            .filter(|(slug, _)| slug::candidate_number(base, slug).is_some())
+            .map(|(slug, id)| (slug.clone(), id))
+            .collect())
+    }
+
+    async fn find_all(&self, query: &PostQuery) -> Result<Page<Post>, ApiError> {
+        let filter = &query.filter;
+        let tables = self.store.read();
+        let mut rows: Vec<Post> = tables
+            .posts
+            .values()
+            .filter(|post| filter.status.is_none_or(|status| post.status == status))
+            .filter(|post| {
+                filter
+                    .author_id
+                    .is_none_or(|author| post.author_id == author)
+            })
+            .filter(|post| in_range(post.created_at, filter.created_after, filter.created_before))
+            .filter(|post| {
+                filter
+                    .tag_id
+                    .is_none_or(|tag| tables.post_tags.contains(&(post.id, tag)))
+            })
+            .filter(|post| {
+                filter.category_ids.as_ref().is_none_or(|ids| {
+                    post.category_id
+                        .is_some_and(|category| ids.contains(&category))
+                })
+            })
+            .cloned()
+            .collect();
+        drop(tables);
+
+        sort_rows(
+            &mut rows,
+            query.direction,
+            |post| post.id,
+            |a, b| match query.sort {
+                PostSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                PostSortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                PostSortField::PublishedAt => nulls_last(a.published_at, b.published_at),
+                PostSortField::Title => a.title.cmp(&b.title),
+            },
+        );
+
+        Ok(paginate(rows, query.page))
+    }
+
+    async fn create(&self, post: Post) -> Result<Post, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.users.contains_key(&post.author_id) {
+            return Err(ApiError::InvalidReference {
+                field: "author_id".to_string(),
+            });
+        }
+        ensure_category_exists(&tables, post.category_id)?;
+        if tables
+            .posts
+            .values()
+            .any(|existing| existing.slug == post.slug)
+        {
+            return Err(ApiError::Conflict {
+                field: "slug".to_string(),
+            });
+        }
+
+        let created_at = now();
+        let post = Post {
+            id: Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            ..post
+        };
+        tables.posts.insert(post.id, post.clone());
+        Ok(post)
+    }
+
+    async fn update(&self, id: Uuid, post: Post) -> Result<Post, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.posts.contains_key(&id) {
+            return Err(ApiError::NotFound);
+        }
+        ensure_category_exists(&tables, post.category_id)?;
+        if tables
+            .posts
+            .values()
+            .any(|other| other.id != id && other.slug == post.slug)
+        {
+            return Err(ApiError::Conflict {
+                field: "slug".to_string(),
+            });
+        }
+
+        let existing = tables.posts.get_mut(&id).ok_or(ApiError::NotFound)?;
+        let previous_slug = std::mem::replace(&mut existing.slug, post.slug);
+        existing.title = post.title;
+        existing.content = post.content;
+        existing.status = post.status;
+        existing.publish_at = post.publish_at;
+        existing.published_at = post.published_at;
+        existing.category_id = post.category_id;
+        existing.content_html = post.content_html;
+        existing.updated_at = now();
+        let updated = existing.clone();
+
+        if updated.slug != previous_slug {
+            tables.post_slugs.insert(previous_slug, id);
+            tables.post_slugs.remove(&updated.slug);
+        }
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+        if self.store.write().delete_post(id) {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound)
+        }
+    }
+
+    async fn reassign_author(&self, from: Uuid, to: Uuid) -> Result<u64, ApiError> {
+        let mut tables = self.store.write();
+        let owned = tables.posts.values().any(|post| post.author_id == from);
+        if owned && !tables.users.contains_key(&to) {
+            return Err(ApiError::InvalidReference {
+                field: "author_id".to_string(),
+            });
+        }
+
+        let updated_at = now();
+        let mut moved = 0;
+        for post in tables
+            .posts
+            .values_mut()
+            .filter(|post| post.author_id == from)
+        {
+            post.author_id = to;
+            post.updated_at = updated_at;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    async fn publish_due(&self, now: NaiveDateTime) -> Result<Vec<Post>, ApiError> {
+        let mut tables = self.store.write();
+        let mut published = Vec::new();
+        for post in tables.posts.values_mut() {
+            if post.status == PostStatus::Scheduled && post.publish_at.is_some_and(|at| at <= now) {
+                post.status = PostStatus::Published;
+                post.published_at = post.publish_at.take();
+                published.push(post.clone());
+            }
+        }
+        Ok(published)
+    }
+}
+
+/// Enforces the foreign key on `posts.category_id`.
+fn ensure_category_exists(tables: &Tables, category_id: Option<Uuid>) -> Result<(), ApiError> {
+    match category_id {
+        Some(id) if !tables.categories.contains_key(&id) => Err(ApiError::InvalidReference {
+            field: "category_id".to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Orders like Postgres does by default: `NULL` sorts after every value.
+fn nulls_last(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[async_trait]
+impl PostRepository for Arc<InMemoryPostRepository> {
+    async fn find(&self, id: Uuid) -> Result<Post, ApiError> {
+        self.as_ref().find(id).await
+    }
+
+    async fn find_for_update(&self, id: Uuid) -> Result<Post, ApiError> {
+        self.as_ref().find_for_update(id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Post, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        self.as_ref().slug_series(base).await
+    }
+
+    async fn find_all(&self, query: &PostQuery) -> Result<Page<Post>, ApiError> {
+        self.as_ref().find_all(query).await
+    }
+
+    async fn create(&self, post: Post) -> Result<Post, ApiError> {
+        self.as_ref().create(post).await
+    }
+
+    async fn update(&self, post_id: Uuid, post: Post) -> Result<Post, ApiError> {
+        self.as_ref().update(post_id, post).await
+    }
+
+    async fn delete(&self, post_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(post_id).await
+    }
+
+    async fn reassign_author(&self, from: Uuid, to: Uuid) -> Result<u64, ApiError> {
+        self.as_ref().reassign_author(from, to).await
+    }
+
+    async fn publish_due(&self, now: NaiveDateTime) -> Result<Vec<Post>, ApiError> {
+        self.as_ref().publish_due(now).await
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, now, paginate};
+use crate::{
+    domain::models::pagination::{Page, PageRequest},
+    domain::models::post::Post,
+    domain::models::post_revision::PostRevision,
+    domain::repositories::PostRevisionRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryPostRevisionRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryPostRevisionRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PostRevisionRepository for InMemoryPostRevisionRepository {
+    async fn record(&self, post: &Post, editor_id: Option<Uuid>) -> Result<PostRevision, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.posts.contains_key(&post.id) {
+            return Err(ApiError::InvalidReference {
+                field: "post_id".to_string(),
+            });
+        }
+        if editor_id.is_some_and(|editor| !tables.users.contains_key(&editor)) {
+            return Err(ApiError::InvalidReference {
+                field: "editor_id".to_string(),
+            });
+        }
+
+        let latest = tables
+            .post_revisions
+            .values()
+            .filter(|revision| revision.post_id == post.id)
+            .map(|revision| revision.revision)
+            .max();
+        let revision = PostRevision {
+            id: Uuid::new_v4(),
+            post_id: post.id,
+            revision: latest.unwrap_or(0) + 1,
+            editor_id,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            status: post.status,
+            created_at: now(),
+        };
+        tables.post_revisions.insert(revision.id, revision.clone());
+        Ok(revision)
+    }
+
+    async fn find(&self, post_id: Uuid, revision: i32) -> Result<PostRevision, ApiError> {
+        self.store
+            .read()
+            .post_revisions
+            .values()
+            .find(|row| row.post_id == post_id && row.revision == revision)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        let mut rows: Vec<PostRevision> = self
+            .store
+            .read()
+            .post_revisions
+            .values()
+            .filter(|row| row.post_id == post_id)
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.revision));
+
+        Ok(paginate(rows, page))
+    }
+}
+
+#[async_trait]
+impl PostRevisionRepository for Arc<InMemoryPostRevisionRepository> {
+    async fn record(&self, post: &Post, editor_id: Option<Uuid>) -> Result<PostRevision, ApiError> {
+        self.as_ref().record(post, editor_id).await
+    }
+
+    async fn find(&self, post_id: Uuid, revision: i32) -> Result<PostRevision, ApiError> {
+        self.as_ref().find(post_id, revision).await
+    }
+
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        self.as_ref().find_by_post(post_id, page).await
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, now};
+use crate::{
+    domain::models::refresh_token::RefreshToken, domain::repositories::RefreshTokenRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryRefreshTokenRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryRefreshTokenRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for InMemoryRefreshTokenRepository {
+    async fn find(&self, id: Uuid) -> Result<RefreshToken, ApiError> {
+        self.store
+            .read()
+            .refresh_tokens
+            .get(&id)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn create(&self, token: RefreshToken) -> Result<RefreshToken, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.users.contains_key(&token.user_id) {
+            return Err(ApiError::InvalidReference {
+                field: "user_id".to_string(),
+            });
+        }
+        if tables.refresh_tokens.contains_key(&token.id) {
+            return Err(ApiError::Conflict {
+                field: "id".to_string(),
+            });
+        }
+
+        let token = RefreshToken {
+            revoked_at: None,
+            replaced_by: None,
+            created_at: now(),
+            ..token
+        };
+        tables.refresh_tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn revoke(&self, id: Uuid, replaced_by: Option<Uuid>) -> Result<bool, ApiError> {
+        let mut tables = self.store.write();
+        match tables.refresh_tokens.get_mut(&id) {
+            Some(token) if token.revoked_at.is_none() => {
+                token.revoked_at = Some(now());
+                token.replaced_by = replaced_by;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), ApiError> {
+        let revoked_at = now();
+        for token in self.store.write().refresh_tokens.values_mut() {
+            if token.user_id == user_id && token.revoked_at.is_none() {
+                token.revoked_at = Some(revoked_at);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for Arc<InMemoryRefreshTokenRepository> {
+    async fn find(&self, token_id: Uuid) -> Result<RefreshToken, ApiError> {
+        self.as_ref().find(token_id).await
+    }
+
+    async fn create(&self, token: RefreshToken) -> Result<RefreshToken, ApiError> {
+        self.as_ref().create(token).await
+    }
+
+    async fn revoke(&self, token_id: Uuid, replaced_by: Option<Uuid>) -> Result<bool, ApiError> {
+        self.as_ref().revoke(token_id, replaced_by).await
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().revoke_all_for_user(user_id).await
+    }
+}
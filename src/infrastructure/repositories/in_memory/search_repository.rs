@@ -0,0 +1,174 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::{InMemoryStore, Tables, in_range, paginate};
+use crate::{
+    domain::models::comment::CommentStatus,
+    domain::models::pagination::Page,
+    domain::models::post::{Post, PostStatus},
+    domain::models::search::{
+        self, HIGHLIGHT_START, HIGHLIGHT_STOP, SearchHit, SearchQuery, SearchScope, SearchTerms,
+    },
+    domain::repositories::SearchRepository,
+    shared::error::ApiError,
+};
+
+/// The weights `ts_rank_cd` gives the `A` and `B` labels the migration puts
+/// on titles and bodies, and the `D` that unlabelled comments get.
+const TITLE_WEIGHT: f32 = 1.0;
+const BODY_WEIGHT: f32 = 0.4;
+const COMMENT_WEIGHT: f32 = 0.1;
+
+/// Whitespace-separated chunks of text a snippet shows, at most.
+const SNIPPET_CHUNKS: usize = 35;
+/// Chunks shown before the first match.
+const SNIPPET_LEAD: usize = 10;
+
+/// Matches whole words without stemming or stop words, so it finds a subset
+/// of what Postgres' `english` configuration does; ranks are only meaningful
+/// relative to each other.
+#[derive(Clone)]
+pub struct InMemorySearchRepository {
+    store: InMemoryStore,
+}
+
+impl InMemorySearchRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+fn tagged(tables: &Tables, post_id: Uuid, tag_id: Option<Uuid>) -> bool {
+    tag_id.is_none_or(|tag_id| tables.post_tags.contains(&(post_id, tag_id)))
+}
+
+/// How often the wanted terms appear in `words`, times `weight`.
+fn score(terms: &SearchTerms, words: &[String], weight: f32) -> f32 {
+    terms
+        .wanted()
+        .map(|term| term.occurrences(words).len() as f32 * weight)
+        .sum()
+}
+
+/// Up to [`SNIPPET_CHUNKS`] chunks of `text` starting a little before the
+/// first match, with every chunk containing a match between the highlight
+/// markers, as HTML.
+fn snippet(terms: &SearchTerms, text: &str) -> String {
+    let chunks: Vec<&str> = text.split_whitespace().collect();
+    let mut words = Vec::new();
+    let mut chunk_of = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        for word in search::words(chunk) {
+            words.push(word);
+            chunk_of.push(i);
+        }
+    }
+
+    let mut marked = HashSet::new();
+    for term in terms.wanted() {
+        for start in term.occurrences(&words) {
+            marked.extend(chunk_of[start..start + term.words.len()].iter().copied());
+        }
+    }
+
+    let first = marked.iter().min().copied().unwrap_or(0);
+    let fragment: Vec<String> = chunks
+        .iter()
+        .enumerate()
+        .skip(first.saturating_sub(SNIPPET_LEAD))
+        .take(SNIPPET_CHUNKS)
+        .map(|(i, chunk)| match marked.contains(&i) {
+            true => format!("{}{}{}", HIGHLIGHT_START, chunk, HIGHLIGHT_STOP),
+            false => chunk.to_string(),
+        })
+        .collect();
+    search::highlight(&fragment.join(" "))
+}
+
+fn published(tables: &Tables, post_id: Uuid) -> Option<&Post> {
+    tables
+        .posts
+        .get(&post_id)
+        .filter(|post| post.status == PostStatus::Published)
+}
+
+fn by_rank(a: &SearchHit, b: &SearchHit) -> Ordering {
+    b.rank
+        .total_cmp(&a.rank)
+        .then_with(|| b.created_at.cmp(&a.created_at))
+        .then_with(|| b.id.cmp(&a.id))
+}
+
+fn hit(post: &Post, id: Uuid, author_id: Uuid, created_at: NaiveDateTime) -> SearchHit {
+    SearchHit {
+        id,
+        post_id: post.id,
+        post_slug: post.slug.clone(),
+        post_title: post.title.clone(),
+        author_id,
+        snippet: String::new(),
+        rank: 0.0,
+        created_at,
+    }
+}
+
+#[async_trait]
+impl SearchRepository for InMemorySearchRepository {
+    async fn search(&self, query: &SearchQuery) -> Result<Page<SearchHit>, ApiError> {
+        let tables = self.store.read();
+        let terms = &query.terms;
+        let wanted = |author_id: Uuid, created_at: NaiveDateTime, post_id: Uuid| {
+            query.author_id.is_none_or(|id| id == author_id)
+                && in_range(created_at, query.created_after, query.created_before)
+                && tagged(&tables, post_id, query.tag_id)
+        };
+
+        let mut rows: Vec<SearchHit> = match query.scope {
+            SearchScope::Posts => tables
+                .posts
+                .values()
+                .filter(|post| post.status == PostStatus::Published)
+                .filter(|post| wanted(post.author_id, post.created_at, post.id))
+                .filter_map(|post| {
+                    let title = search::words(&post.title);
+                    let body = search::words(&post.content);
+                    terms.matches(&[&title, &body]).then(|| SearchHit {
+                        snippet: snippet(terms, &post.content),
+                        rank: score(terms, &title, TITLE_WEIGHT) + score(terms, &body, BODY_WEIGHT),
+                        ..hit(post, post.id, post.author_id, post.created_at)
+                    })
+                })
+                .collect(),
+            SearchScope::Comments => tables
+                .comments
+                .values()
+                .filter(|comment| comment.status == CommentStatus::Approved)
+                .filter(|comment| wanted(comment.author_id, comment.created_at, comment.post_id))
+                .filter_map(|comment| {
+                    let post = published(&tables, comment.post_id)?;
+                    let body = search::words(&comment.content);
+                    terms.matches(&[&body]).then(|| SearchHit {
+                        snippet: snippet(terms, &comment.content),
+                        rank: score(terms, &body, COMMENT_WEIGHT),
+                        ..hit(post, comment.id, comment.author_id, comment.created_at)
+                    })
+                })
+                .collect(),
+        };
+
+        rows.sort_by(by_rank);
+        Ok(paginate(rows, query.page))
+    }
+}
+
+#[async_trait]
+impl SearchRepository for Arc<InMemorySearchRepository> {
+    async fn search(&self, query: &SearchQuery) -> Result<Page<SearchHit>, ApiError> {
+        self.as_ref().search(query).await
+    }
+}
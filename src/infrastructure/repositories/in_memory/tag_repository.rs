@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, Tables, now, paginate};
+use crate::{
+    domain::models::pagination::{Page, PageRequest},
+    domain::models::post::PostStatus,
+    domain::models::slug,
+    domain::models::tag::Tag,
+    domain::repositories::TagRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryTagRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryTagRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Enforces the `UNIQUE` constraint on `tags.slug`.
+fn ensure_unique(tables: &Tables, tag: &Tag, except: Option<Uuid>) -> Result<(), ApiError> {
+    if tables
+        .tags
+        .values()
+        .any(|other| Some(other.id) != except && other.slug == tag.slug)
+    {
+        return Err(ApiError::Conflict {
+            field: "slug".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn by_name(tags: &mut [Tag]) {
+    tags.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+}
+
+#[async_trait]
+impl TagRepository for InMemoryTagRepository {
+    async fn find_by_slug(&self, slug: &str) -> Result<Tag, ApiError> {
+        self.store
+            .read()
+            .tags
+            .values()
+            .find(|tag| tag.slug == slug)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_by_slugs(&self, slugs: &[String]) -> Result<Vec<Tag>, ApiError> {
+        Ok(self
+            .store
+            .read()
+            .tags
+            .values()
+            .filter(|tag| slugs.contains(&tag.slug))
+            .cloned()
+            .collect())
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        Ok(self
+            .store
+            .read()
+            .tags
+            .values()
+            .filter(|tag| slug::candidate_number(base, &tag.slug).is_some())
+            .map(|tag| (tag.slug.clone(), tag.id))
+            .collect())
+    }
+
+    async fn find_all(&self, page: PageRequest) -> Result<Page<Tag>, ApiError> {
+        let mut rows: Vec<Tag> = self.store.read().tags.values().cloned().collect();
+        by_name(&mut rows);
+        Ok(paginate(rows, page))
+    }
+
+    async fn create(&self, tag: Tag) -> Result<Tag, ApiError> {
+        let mut tables = self.store.write();
+        ensure_unique(&tables, &tag, None)?;
+
+        let created_at = now();
+        let tag = Tag {
+            id: Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            ..tag
+        };
+        tables.tags.insert(tag.id, tag.clone());
+        Ok(tag)
+    }
+
+    async fn update(&self, id: Uuid, tag: Tag) -> Result<Tag, ApiError> {
+        let mut tables = self.store.write();
+        if !tables.tags.contains_key(&id) {
+            return Err(ApiError::NotFound);
+        }
+        ensure_unique(&tables, &tag, Some(id))?;
+
+        let existing = tables.tags.get_mut(&id).ok_or(ApiError::NotFound)?;
+        existing.name = tag.name;
+        existing.slug = tag.slug;
+        existing.updated_at = now();
+        Ok(existing.clone())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+        let mut tables = self.store.write();
+        if tables.tags.remove(&id).is_none() {
+            return Err(ApiError::NotFound);
+        }
+        tables.post_tags.retain(|(_, tag_id)| *tag_id != id);
+        Ok(())
+    }
+
+    async fn count_published(&self, tag_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>, ApiError> {
+        let tables = self.store.read();
+        let mut counts = HashMap::new();
+        for (post_id, tag_id) in &tables.post_tags {
+            let published = tables
+                .posts
+                .get(post_id)
+                .is_some_and(|post| post.status == PostStatus::Published);
+            if published && tag_ids.contains(tag_id) {
+                *counts.entry(*tag_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn find_by_posts(&self, post_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Tag>>, ApiError> {
+        let tables = self.store.read();
+        let mut by_post: HashMap<Uuid, Vec<Tag>> = HashMap::new();
+        for (post_id, tag_id) in &tables.post_tags {
+            if let Some(tag) = tables.tags.get(tag_id)
+                && post_ids.contains(post_id)
+            {
+                by_post.entry(*post_id).or_default().push(tag.clone());
+            }
+        }
+        for tags in by_post.values_mut() {
+            by_name(tags);
+        }
+        Ok(by_post)
+    }
+
+    async fn set_for_post(&self, post_id: Uuid, tag_ids: &[Uuid]) -> Result<(), ApiError> {
+        let mut tables = self.store.write();
+        if !tables.posts.contains_key(&post_id) {
+            return Err(ApiError::InvalidReference {
+                field: "post_id".to_string(),
+            });
+        }
+        if tag_ids
+            .iter()
+            .any(|tag_id| !tables.tags.contains_key(tag_id))
+        {
+            return Err(ApiError::InvalidReference {
+                field: "tag_id".to_string(),
+            });
+        }
+
+        tables.post_tags.retain(|(post, _)| *post != post_id);
+        tables
+            .post_tags
+            .extend(tag_ids.iter().map(|tag_id| (post_id, *tag_id)));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TagRepository for Arc<InMemoryTagRepository> {
+    async fn find_by_slug(&self, slug: &str) -> Result<Tag, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn find_by_slugs(&self, slugs: &[String]) -> Result<Vec<Tag>, ApiError> {
+        self.as_ref().find_by_slugs(slugs).await
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        self.as_ref().slug_series(base).await
+    }
+
+    async fn find_all(&self, page: PageRequest) -> Result<Page<Tag>, ApiError> {
+        self.as_ref().find_all(page).await
+    }
+
+    async fn create(&self, tag: Tag) -> Result<Tag, ApiError> {
+        self.as_ref().create(tag).await
+    }
+
+    async fn update(&self, tag_id: Uuid, tag: Tag) -> Result<Tag, ApiError> {
+        self.as_ref().update(tag_id, tag).await
+    }
+
+    async fn delete(&self, tag_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(tag_id).await
+    }
+
+    async fn count_published(&self, tag_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>, ApiError> {
+        self.as_ref().count_published(tag_ids).await
+    }
+
+    async fn find_by_posts(&self, post_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Tag>>, ApiError> {
+        self.as_ref().find_by_posts(post_ids).await
+    }
+
+    async fn set_for_post(&self, post_id: Uuid, tag_ids: &[Uuid]) -> Result<(), ApiError> {
+        self.as_ref().set_for_post(post_id, tag_ids).await
+    }
+}
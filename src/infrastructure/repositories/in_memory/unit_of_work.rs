@@ -0,0 +1,137 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{
+    InMemoryStore, Tables, category_repository::InMemoryCategoryRepository,
+    comment_repository::InMemoryCommentRepository, post_repository::InMemoryPostRepository,
+    post_revision_repository::InMemoryPostRevisionRepository,
+    tag_repository::InMemoryTagRepository, user_repository::InMemoryUserRepository,
+};
+use crate::{
+    domain::repositories::{TransactionScope, UnitOfWork},
+    shared::error::ApiError,
+};
+
+/// Transactions over an [`InMemoryStore`].
+///
+/// Transactions run one at a time. The tables are snapshotted when one
+/// starts and restored if it fails or is dropped before finishing. There is
+/// no isolation from writes made outside a transaction: they show up inside
+/// it straight away and are lost if it rolls back. That is fine for tests
+/// and local runs, which is all this is for.
+#[derive(Clone)]
+pub struct InMemoryUnitOfWork {
+    store: InMemoryStore,
+}
+
+impl InMemoryUnitOfWork {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryTransactionScope {
+    users: InMemoryUserRepository,
+    posts: InMemoryPostRepository,
+    post_revisions: InMemoryPostRevisionRepository,
+    tags: InMemoryTagRepository,
+    categories: InMemoryCategoryRepository,
+    comments: InMemoryCommentRepository,
+}
+
+impl TransactionScope for InMemoryTransactionScope {
+    type Users = InMemoryUserRepository;
+    type Posts = InMemoryPostRepository;
+    type PostRevisions = InMemoryPostRevisionRepository;
+    type Tags = InMemoryTagRepository;
+    type Categories = InMemoryCategoryRepository;
+    type Comments = InMemoryCommentRepository;
+
+    fn users(&self) -> &Self::Users {
+        &self.users
+    }
+
+    fn posts(&self) -> &Self::Posts {
+        &self.posts
+    }
+
+    fn post_revisions(&self) -> &Self::PostRevisions {
+        &self.post_revisions
+    }
+
+    fn tags(&self) -> &Self::Tags {
+        &self.tags
+    }
+
+    fn categories(&self) -> &Self::Categories {
+        &self.categories
+    }
+
+    fn comments(&self) -> &Self::Comments {
+        &self.comments
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    type Scope = InMemoryTransactionScope;
+
+    async fn transaction<F, Fut, T>(&self, work: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Self::Scope) -> Fut + Send,
+        Fut: Future<Output = Result<T, ApiError>> + Send,
+        T: Send,
+    {
+        let _serialized = self.store.transactions.lock().await;
+        let mut rollback = Rollback {
+            store: &self.store,
+            snapshot: Some(self.store.read().clone()),
+        };
+        let scope = InMemoryTransactionScope {
+            users: InMemoryUserRepository::new(self.store.clone()),
+            posts: InMemoryPostRepository::new(self.store.clone()),
+            post_revisions: InMemoryPostRevisionRepository::new(self.store.clone()),
+            tags: InMemoryTagRepository::new(self.store.clone()),
+            categories: InMemoryCategoryRepository::new(self.store.clone()),
+            comments: InMemoryCommentRepository::new(self.store.clone()),
+        };
+
+        let result = work(scope).await;
+        if result.is_ok() {
+            rollback.snapshot = None;
+        }
+        result
+    }
+}
+
+/// Puts the snapshot back unless disarmed, including when the transaction's
+/// future is dropped part way through.
+struct Rollback<'a> {
+    store: &'a InMemoryStore,
+    snapshot: Option<Tables>,
+}
+
+impl Drop for Rollback<'_> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.store.write() = snapshot;
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for Arc<InMemoryUnitOfWork> {
+    type Scope = InMemoryTransactionScope;
+
+    async fn transaction<F, Fut, T>(&self, work: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Self::Scope) -> Fut + Send,
+        Fut: Future<Output = Result<T, ApiError>> + Send,
+        T: Send,
+    {
+        self.as_ref().transaction(work).await
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{InMemoryStore, Tables, in_range, now, paginate, sort_rows};
+use crate::{
+    domain::models::pagination::Page,
+    domain::models::user::{User, UserQuery, UserSortField},
+    domain::repositories::UserRepository,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct InMemoryUserRepository {
+    store: InMemoryStore,
+}
+
+impl InMemoryUserRepository {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Enforces the `UNIQUE` constraints on `users.username` and `users.email`.
+fn ensure_unique(tables: &Tables, user: &User, except: Option<Uuid>) -> Result<(), ApiError> {
+    for other in tables.users.values() {
+        if Some(other.id) == except {
+            continue;
+        }
+        if other.username == user.username {
+            return Err(ApiError::Conflict {
+                field: "username".to_string(),
+            });
+        }
+        if other.email == user.email {
+            return Err(ApiError::Conflict {
+                field: "email".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn find(&self, id: Uuid) -> Result<User, ApiError> {
+        self.store
+            .read()
+            .users
+            .get(&id)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn find_all(&self, query: &UserQuery) -> Result<Page<User>, ApiError> {
+        let filter = &query.filter;
+        let mut rows: Vec<User> = self
+            .store
+            .read()
+            .users
+            .values()
+            .filter(|user| filter.role.is_none_or(|role| user.role == role))
+            .filter(|user| in_range(user.created_at, filter.created_after, filter.created_before))
+            .cloned()
+            .collect();
+
+        sort_rows(
+            &mut rows,
+            query.direction,
+            |user| user.id,
+            |a, b| match query.sort {
+                UserSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                UserSortField::Username => a.username.cmp(&b.username),
+                UserSortField::Email => a.email.cmp(&b.email),
+            },
+        );
+
+        Ok(paginate(rows, query.page))
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<User, ApiError> {
+        self.store
+            .read()
+            .users
+            .values()
+            .find(|user| user.email == email)
+            .cloned()
+            .ok_or(ApiError::NotFound)
+    }
+
+    async fn create(&self, user: User) -> Result<User, ApiError> {
+        let mut tables = self.store.write();
+        ensure_unique(&tables, &user, None)?;
+
+        let created_at = now();
+        let user = User {
+            id: Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            ..user
+        };
+        tables.users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    async fn update(&self, id: Uuid, user: User) -> Result<User, ApiError> {
+        let mut tables = self.store.write();
+        ensure_unique(&tables, &user, Some(id))?;
+
+        let existing = tables.users.get_mut(&id).ok_or(ApiError::NotFound)?;
+        existing.username = user.username;
+        existing.email = user.email;
+        existing.password_hash = user.password_hash;
+        existing.role = user.role;
+        existing.updated_at = now();
+        Ok(existing.clone())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), ApiError> {
+        if self.store.write().delete_user(id) {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound)
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepository for Arc<InMemoryUserRepository> {
+    async fn find(&self, id: Uuid) -> Result<User, ApiError> {
+        self.as_ref().find(id).await
+    }
+
+    async fn find_all(&self, query: &UserQuery) -> Result<Page<User>, ApiError> {
+        self.as_ref().find_all(query).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<User, ApiError> {
+        self.as_ref().find_by_email(email).await
+    }
+
+    async fn create(&self, user: User) -> Result<User, ApiError> {
+        self.as_ref().create(user).await
+    }
+
+    async fn update(&self, user_id: Uuid, user: User) -> Result<User, ApiError> {
+        self.as_ref().update(user_id, user).await
+    }
+
+    async fn delete(&self, user_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(user_id).await
+    }
+}
@@ -1,3 +1,11 @@
+pub mod category_repository_impl;
 pub mod comment_repository_impl;
+pub mod health_repository_impl;
+pub mod in_memory;
 pub mod post_repository_impl;
+pub mod post_revision_repository_impl;
+pub mod refresh_token_repository_impl;
+pub mod search_repository_impl;
+pub mod tag_repository_impl;
+pub mod unit_of_work_impl;
 pub mod user_repository_impl;
@@ -1,115 +1,323 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use chrono::NaiveDateTime;
+use diesel::{QueryDsl, RunQueryDsl, pg::Pg, prelude::*};
 use uuid::Uuid;
 
 use crate::{
-    domain::models::post::{NewPost, Post, UpdatePostData},
+    domain::models::pagination::{Page, SortDirection},
+    domain::models::post::{
+        NewPost, Post, PostFilter, PostQuery, PostSortField, PostStatus, UpdatePostData,
+    },
+    domain::models::slug,
     domain::repositories::PostRepository,
-    infrastructure::database::connection::PgPool,
+    infrastructure::database::{
+        executor::DbExecutor,
+        schema::{post_slugs, post_tags, posts},
+    },
     shared::error::ApiError,
 };
 
 #[derive(Clone)]
 pub struct PostRepositoryImpl {
-    pool: PgPool,
+    db: DbExecutor,
 }
 
 impl PostRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+
+    fn filtered(filter: &PostFilter) -> posts::BoxedQuery<'static, Pg> {
+        let mut query = posts::table.into_boxed();
+
+        if let Some(status) = filter.status {
+            query = query.filter(posts::status.eq(status));
+        }
+        if let Some(author_id) = filter.author_id {
+            query = query.filter(posts::author_id.eq(author_id));
+        }
+        if let Some(created_after) = filter.created_after {
+            query = query.filter(posts::created_at.ge(created_after));
+        }
+        if let Some(created_before) = filter.created_before {
+            query = query.filter(posts::created_at.lt(created_before));
+        }
+        if let Some(tag_id) = filter.tag_id {
+            query = query.filter(
+                posts::id.eq_any(
+                    post_tags::table
+                        .filter(post_tags::tag_id.eq(tag_id))
+                        .select(post_tags::post_id),
+                ),
+            );
+        }
+        if let Some(category_ids) = &filter.category_ids {
+            query = query.filter(posts::category_id.eq_any(category_ids.clone()));
+        }
+
+        query
     }
 }
 
 #[async_trait]
 impl PostRepository for PostRepositoryImpl {
-    async fn find(&self, _id: Uuid) -> Result<Post, ApiError> {
+    async fn find(&self, post_id: Uuid) -> Result<Post, ApiError> {
         use crate::infrastructure::database::schema::posts::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        self.db
+            .run(move |conn| {
+                posts
+                    .filter(id.eq(post_id))
+                    .select(Post::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_for_update(&self, post_id: Uuid) -> Result<Post, ApiError> {
+        self.db
+            .run(move |conn| {
+                posts::table
+                    .find(post_id)
+                    .select(Post::as_select())
+                    .for_update()
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_by_slug(&self, post_slug: &str) -> Result<Post, ApiError> {
+        let post_slug = post_slug.to_owned();
+        self.db
+            .run(move |conn| {
+                let current = posts::table
+                    .filter(posts::slug.eq(&post_slug))
+                    .select(Post::as_select())
+                    .first(conn)
+                    .optional()
+                    .map_err(ApiError::from)?;
+                if let Some(post) = current {
+                    return Ok(post);
+                }
 
-        posts
-            .filter(id.eq(id))
-            .select(Post::as_select())
-            .first(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                post_slugs::table
+                    .inner_join(posts::table)
+                    .filter(post_slugs::slug.eq(&post_slug))
+                    .select(Post::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
-    async fn find_all(&self) -> Result<Vec<Post>, ApiError> {
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        // Slugs are lowercase letters, digits and hyphens, so `base` holds
+        // no LIKE wildcards.
+        let base = base.to_owned();
+        let pattern = format!("{}-%", base);
+        self.db
+            .run(move |conn| {
+                let current = posts::table
+                    .filter(posts::slug.eq(&base).or(posts::slug.like(&pattern)))
+                    .select((posts::slug, posts::id));
+                let former = post_slugs::table
+                    .filter(
+                        post_slugs::slug
+                            .eq(&base)
+                            .or(post_slugs::slug.like(&pattern)),
+                    )
+                    .select((post_slugs::slug, post_slugs::post_id));
+                let taken: Vec<(String, Uuid)> = current
+                    .union_all(former)
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(taken
+                    .into_iter()
+                    .filter(|(taken, _)| slug::candidate_number(&base, taken).is_some())
+                    .collect())
+            })
+            .await
+    }
+
+    async fn find_all(&self, query: &PostQuery) -> Result<Page<Post>, ApiError> {
         use crate::infrastructure::database::schema::posts::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let total = Self::filtered(&query.filter)
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
+
+                let items = Self::filtered(&query.filter).select(Post::as_select());
+                let items = match (query.sort, query.direction) {
+                    (PostSortField::CreatedAt, SortDirection::Asc) => items.order(created_at.asc()),
+                    (PostSortField::CreatedAt, SortDirection::Desc) => {
+                        items.order(created_at.desc())
+                    }
+                    (PostSortField::UpdatedAt, SortDirection::Asc) => items.order(updated_at.asc()),
+                    (PostSortField::UpdatedAt, SortDirection::Desc) => {
+                        items.order(updated_at.desc())
+                    }
+                    (PostSortField::PublishedAt, SortDirection::Asc) => {
+                        items.order(published_at.asc())
+                    }
+                    (PostSortField::PublishedAt, SortDirection::Desc) => {
+                        items.order(published_at.desc())
+                    }
+                    (PostSortField::Title, SortDirection::Asc) => items.order(title.asc()),
+                    (PostSortField::Title, SortDirection::Desc) => items.order(title.desc()),
+                };
+                // Break ties on the primary key so pages never overlap.
+                let items = match query.direction {
+                    SortDirection::Asc => items.then_order_by(id.asc()),
+                    SortDirection::Desc => items.then_order_by(id.desc()),
+                };
 
-        posts
-            .select(Post::as_select())
-            .load(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                let items = items
+                    .limit(query.page.limit)
+                    .offset(query.page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, query.page))
+            })
+            .await
     }
 
     async fn create(&self, post: Post) -> Result<Post, ApiError> {
         use crate::infrastructure::database::schema::posts::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        let new_post = NewPost {
-            title: post.title,
-            content: post.content,
-            author_id: post.author_id,
-            published: post.published,
-        };
+        self.db
+            .run(move |conn| {
+                let new_post = NewPost {
+                    title: post.title,
+                    content: post.content,
+                    author_id: post.author_id,
+                    slug: post.slug,
+                    status: post.status,
+                    publish_at: post.publish_at,
+                    published_at: post.published_at,
+                    category_id: post.category_id,
+                    content_html: post.content_html,
+                };
 
-        diesel::insert_into(posts)
-            .values(&new_post)
-            .returning(Post::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                diesel::insert_into(posts)
+                    .values(&new_post)
+                    .returning(Post::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn update(&self, post_id: Uuid, post: Post) -> Result<Post, ApiError> {
-        use crate::infrastructure::database::schema::posts::dsl::*;
+        self.db
+            .run(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    let previous_slug: String = posts::table
+                        .find(post_id)
+                        .select(posts::slug)
+                        .for_update()
+                        .first(conn)?;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                    let update_data = UpdatePostData {
+                        title: Some(post.title),
+                        content: Some(post.content),
+                        updated_at: Some(chrono::Local::now().naive_local()),
+                        slug: Some(post.slug),
+                        status: Some(post.status),
+                        publish_at: Some(post.publish_at),
+                        published_at: Some(post.published_at),
+                        category_id: Some(post.category_id),
+                        content_html: Some(post.content_html),
+                    };
+                    let updated = diesel::update(posts::table.find(post_id))
+                        .set(&update_data)
+                        .returning(Post::as_returning())
+                        .get_result(conn)?;
 
-        let update_data = UpdatePostData {
-            title: Some(post.title),
-            content: Some(post.content),
-            published: Some(post.published),
-            updated_at: Some(chrono::Local::now().naive_local()),
-        };
+                    if updated.slug != previous_slug {
+                        diesel::insert_into(post_slugs::table)
+                            .values((
+                                post_slugs::slug.eq(&previous_slug),
+                                post_slugs::post_id.eq(post_id),
+                            ))
+                            .on_conflict(post_slugs::slug)
+                            .do_update()
+                            .set(post_slugs::post_id.eq(post_id))
+                            .execute(conn)?;
+                        // Taking back a slug the post had before.
+                        diesel::delete(post_slugs::table.find(&updated.slug)).execute(conn)?;
+                    }
 
-        diesel::update(posts.filter(id.eq(post_id)))
-            .set(&update_data)
-            .returning(Post::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+                    Ok(updated)
+                })
+                .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn delete(&self, post_id: Uuid) -> Result<(), ApiError> {
         use crate::infrastructure::database::schema::posts::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        self.db
+            .run(move |conn| {
+                let deleted = diesel::delete(posts.filter(id.eq(post_id)))
+                    .execute(conn)
+                    .map_err(ApiError::from)?;
+
+                if deleted == 0 {
+                    return Err(ApiError::NotFound);
+                }
+                Ok(())
+            })
+            .await
+    }
+
+    async fn reassign_author(&self, from: Uuid, to: Uuid) -> Result<u64, ApiError> {
+        use crate::infrastructure::database::schema::posts::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let moved = diesel::update(posts.filter(author_id.eq(from)))
+                    .set((
+                        author_id.eq(to),
+                        updated_at.eq(chrono::Local::now().naive_local()),
+                    ))
+                    .execute(conn)
+                    .map_err(ApiError::from)?;
+                Ok(moved as u64)
+            })
+            .await
+    }
 
-        diesel::delete(posts.filter(id.eq(post_id)))
-            .execute(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    async fn publish_due(&self, now: NaiveDateTime) -> Result<Vec<Post>, ApiError> {
+        use crate::infrastructure::database::schema::posts::dsl::*;
 
-        Ok(())
+        self.db
+            .run(move |conn| {
+                diesel::update(
+                    posts
+                        .filter(status.eq(PostStatus::Scheduled))
+                        .filter(publish_at.le(now)),
+                )
+                .set((
+                    status.eq(PostStatus::Published),
+                    published_at.eq(publish_at),
+                    publish_at.eq(None::<NaiveDateTime>),
+                ))
+                .returning(Post::as_returning())
+                .get_results(conn)
+                .map_err(ApiError::from)
+            })
+            .await
     }
 }
 
@@ -119,8 +327,20 @@ impl PostRepository for Arc<PostRepositoryImpl> {
         self.as_ref().find(id).await
     }
 
-    async fn find_all(&self) -> Result<Vec<Post>, ApiError> {
-        self.as_ref().find_all().await
+    async fn find_for_update(&self, id: Uuid) -> Result<Post, ApiError> {
+        self.as_ref().find_for_update(id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Post, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        self.as_ref().slug_series(base).await
+    }
+
+    async fn find_all(&self, query: &PostQuery) -> Result<Page<Post>, ApiError> {
+        self.as_ref().find_all(query).await
     }
 
     async fn create(&self, post: Post) -> Result<Post, ApiError> {
@@ -134,4 +354,12 @@ impl PostRepository for Arc<PostRepositoryImpl> {
     async fn delete(&self, post_id: Uuid) -> Result<(), ApiError> {
         self.as_ref().delete(post_id).await
     }
+
+    async fn reassign_author(&self, from: Uuid, to: Uuid) -> Result<u64, ApiError> {
+        self.as_ref().reassign_author(from, to).await
+    }
+
+    async fn publish_due(&self, now: NaiveDateTime) -> Result<Vec<Post>, ApiError> {
+        self.as_ref().publish_due(now).await
+    }
 }
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    domain::models::pagination::{Page, PageRequest},
+    domain::models::post::Post,
+    domain::models::post_revision::{NewPostRevision, PostRevision},
+    domain::repositories::PostRevisionRepository,
+    infrastructure::database::{executor::DbExecutor, schema::post_revisions},
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct PostRevisionRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl PostRevisionRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PostRevisionRepository for PostRevisionRepositoryImpl {
+    async fn record(&self, post: &Post, editor: Option<Uuid>) -> Result<PostRevision, ApiError> {
+        use crate::infrastructure::database::schema::post_revisions::dsl::*;
+
+        let post = post.clone();
+        self.db
+            .run(move |conn| {
+                // Callers save the post first, which locks its row until they
+                // commit, so two writers cannot pick the same number.
+                let latest: Option<i32> = post_revisions
+                    .filter(post_id.eq(post.id))
+                    .select(diesel::dsl::max(revision))
+                    .first(conn)
+                    .map_err(ApiError::from)?;
+
+                diesel::insert_into(post_revisions)
+                    .values(NewPostRevision::of(&post, latest.unwrap_or(0) + 1, editor))
+                    .returning(PostRevision::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find(&self, post: Uuid, number: i32) -> Result<PostRevision, ApiError> {
+        use crate::infrastructure::database::schema::post_revisions::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                post_revisions
+                    .filter(post_id.eq(post))
+                    .filter(revision.eq(number))
+                    .select(PostRevision::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_by_post(
+        &self,
+        post: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        self.db
+            .run(move |conn| {
+                let total = post_revisions::table
+                    .filter(post_revisions::post_id.eq(post))
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
+
+                let items = post_revisions::table
+                    .filter(post_revisions::post_id.eq(post))
+                    .order(post_revisions::revision.desc())
+                    .select(PostRevision::as_select())
+                    .limit(page.limit)
+                    .offset(page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, page))
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl PostRevisionRepository for Arc<PostRevisionRepositoryImpl> {
+    async fn record(&self, post: &Post, editor_id: Option<Uuid>) -> Result<PostRevision, ApiError> {
+        self.as_ref().record(post, editor_id).await
+    }
+
+    async fn find(&self, post_id: Uuid, revision: i32) -> Result<PostRevision, ApiError> {
+        self.as_ref().find(post_id, revision).await
+    }
+
+    async fn find_by_post(
+        &self,
+        post_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        self.as_ref().find_by_post(post_id, page).await
+    }
+}
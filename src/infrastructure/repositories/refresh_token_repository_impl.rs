@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    domain::models::refresh_token::{NewRefreshToken, RefreshToken},
+    domain::repositories::RefreshTokenRepository,
+    infrastructure::database::executor::DbExecutor,
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct RefreshTokenRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl RefreshTokenRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
+    async fn find(&self, token_id: Uuid) -> Result<RefreshToken, ApiError> {
+        use crate::infrastructure::database::schema::refresh_tokens::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                refresh_tokens
+                    .filter(id.eq(token_id))
+                    .select(RefreshToken::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn create(&self, token: RefreshToken) -> Result<RefreshToken, ApiError> {
+        use crate::infrastructure::database::schema::refresh_tokens::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let new_token = NewRefreshToken {
+                    id: token.id,
+                    user_id: token.user_id,
+                    expires_at: token.expires_at,
+                };
+
+                diesel::insert_into(refresh_tokens)
+                    .values(&new_token)
+                    .returning(RefreshToken::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn revoke(&self, token_id: Uuid, replacement: Option<Uuid>) -> Result<bool, ApiError> {
+        use crate::infrastructure::database::schema::refresh_tokens::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let revoked = diesel::update(
+                    refresh_tokens
+                        .filter(id.eq(token_id))
+                        .filter(revoked_at.is_null()),
+                )
+                .set((
+                    revoked_at.eq(Some(chrono::Local::now().naive_local())),
+                    replaced_by.eq(replacement),
+                ))
+                .execute(conn)
+                .map_err(ApiError::from)?;
+
+                Ok(revoked > 0)
+            })
+            .await
+    }
+
+    async fn revoke_all_for_user(&self, owner_id: Uuid) -> Result<(), ApiError> {
+        use crate::infrastructure::database::schema::refresh_tokens::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                diesel::update(
+                    refresh_tokens
+                        .filter(user_id.eq(owner_id))
+                        .filter(revoked_at.is_null()),
+                )
+                .set(revoked_at.eq(Some(chrono::Local::now().naive_local())))
+                .execute(conn)
+                .map_err(ApiError::from)?;
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for Arc<RefreshTokenRepositoryImpl> {
+    async fn find(&self, token_id: Uuid) -> Result<RefreshToken, ApiError> {
+        self.as_ref().find(token_id).await
+    }
+
+    async fn create(&self, token: RefreshToken) -> Result<RefreshToken, ApiError> {
+        self.as_ref().create(token).await
+    }
+
+    async fn revoke(&self, token_id: Uuid, replaced_by: Option<Uuid>) -> Result<bool, ApiError> {
+        self.as_ref().revoke(token_id, replaced_by).await
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().revoke_all_for_user(user_id).await
+    }
+}
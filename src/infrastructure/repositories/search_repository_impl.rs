@@ -0,0 +1,270 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::{dsl::sql, expression::SqlLiteral, pg::Pg, prelude::*, sql_types::Text};
+use uuid::Uuid;
+
+use crate::{
+    domain::models::comment::CommentStatus,
+    domain::models::pagination::Page,
+    domain::models::post::PostStatus,
+    domain::models::search::{
+        self, HIGHLIGHT_START, HIGHLIGHT_STOP, SearchHit, SearchQuery, SearchScope, SearchTerm,
+        SearchTerms,
+    },
+    domain::repositories::SearchRepository,
+    infrastructure::database::{
+        executor::DbExecutor,
+        schema::{comments, post_tags, posts, sql_types::Tsvector},
+    },
+    shared::error::ApiError,
+};
+
+mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsquery", schema = "pg_catalog"))]
+    pub struct Tsquery;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "regconfig", schema = "pg_catalog"))]
+    pub struct Regconfig;
+}
+
+use sql_types::{Regconfig, Tsquery};
+
+define_sql_function! {
+    fn to_tsquery(config: Regconfig, query: Text) -> Tsquery;
+}
+define_sql_function! {
+    fn ts_rank_cd(vector: Tsvector, query: Tsquery) -> Float4;
+}
+define_sql_function! {
+    fn ts_headline(config: Regconfig, document: Text, query: Tsquery, options: Text) -> Text;
+}
+diesel::infix_operator!(Matches, " @@ ", backend: Pg);
+
+/// Must match the configuration the `search_vector` columns are built with.
+fn config() -> SqlLiteral<Regconfig> {
+    sql("'english'::regconfig")
+}
+
+/// A snippet is up to two fragments of 15 to 35 words, with matches between
+/// the highlight markers so they survive escaping.
+fn headline_options() -> String {
+    format!(
+        "StartSel={}, StopSel={}, MinWords=15, MaxWords=35, MaxFragments=2, \
+         FragmentDelimiter=\" … \"",
+        HIGHLIGHT_START, HIGHLIGHT_STOP
+    )
+}
+
+/// `terms` in `to_tsquery` syntax. Words are alphanumeric already, so none
+/// of them needs quoting.
+fn tsquery(terms: &SearchTerms) -> String {
+    terms
+        .groups
+        .iter()
+        .map(|group| {
+            let alternatives: Vec<String> = group.iter().map(tsquery_term).collect();
+            match alternatives.as_slice() {
+                [only] => only.clone(),
+                _ => format!("({})", alternatives.join(" | ")),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+fn tsquery_term(term: &SearchTerm) -> String {
+    let mut phrase = term.words.join(" <-> ");
+    if term.prefix {
+        phrase.push_str(":*");
+    }
+    match (term.negated, term.words.len()) {
+        (false, 1) => phrase,
+        (false, _) => format!("({})", phrase),
+        (true, 1) => format!("!{}", phrase),
+        (true, _) => format!("!({})", phrase),
+    }
+}
+
+type HitRow = (Uuid, Uuid, String, String, Uuid, NaiveDateTime, f32, String);
+
+fn hit(
+    (id, post_id, post_slug, post_title, author_id, created_at, rank, headline): HitRow,
+) -> SearchHit {
+    SearchHit {
+        id,
+        post_id,
+        post_slug,
+        post_title,
+        author_id,
+        snippet: search::highlight(&headline),
+        rank,
+        created_at,
+    }
+}
+
+#[derive(Clone)]
+pub struct SearchRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl SearchRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+
+    fn matching_posts(query: &SearchQuery) -> posts::BoxedQuery<'static, Pg> {
+        let tsquery = to_tsquery(config(), tsquery(&query.terms));
+        let mut matching = posts::table
+            .filter(posts::status.eq(PostStatus::Published))
+            .filter(Matches::new(posts::search_vector, tsquery))
+            .into_boxed();
+
+        if let Some(author_id) = query.author_id {
+            matching = matching.filter(posts::author_id.eq(author_id));
+        }
+        if let Some(created_after) = query.created_after {
+            matching = matching.filter(posts::created_at.ge(created_after));
+        }
+        if let Some(created_before) = query.created_before {
+            matching = matching.filter(posts::created_at.lt(created_before));
+        }
+        if let Some(tag_id) = query.tag_id {
+            matching = matching.filter(
+                posts::id.eq_any(
+                    post_tags::table
+                        .filter(post_tags::tag_id.eq(tag_id))
+                        .select(post_tags::post_id),
+                ),
+            );
+        }
+        matching
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn matching_comments(
+        query: &SearchQuery,
+    ) -> diesel::dsl::IntoBoxed<'static, diesel::dsl::InnerJoin<comments::table, posts::table>, Pg>
+    {
+        let tsquery = to_tsquery(config(), tsquery(&query.terms));
+        let mut matching = comments::table
+            .inner_join(posts::table)
+            .filter(posts::status.eq(PostStatus::Published))
+            .filter(comments::status.eq(CommentStatus::Approved))
+            .filter(Matches::new(comments::search_vector, tsquery))
+            .into_boxed();
+
+        if let Some(author_id) = query.author_id {
+            matching = matching.filter(comments::author_id.eq(author_id));
+        }
+        if let Some(created_after) = query.created_after {
+            matching = matching.filter(comments::created_at.ge(created_after));
+        }
+        if let Some(created_before) = query.created_before {
+            matching = matching.filter(comments::created_at.lt(created_before));
+        }
+        if let Some(tag_id) = query.tag_id {
+            matching = matching.filter(
+                comments::post_id.eq_any(
+                    post_tags::table
+                        .filter(post_tags::tag_id.eq(tag_id))
+                        .select(post_tags::post_id),
+                ),
+            );
+        }
+        matching
+    }
+}
+
+#[async_trait]
+impl SearchRepository for SearchRepositoryImpl {
+    async fn search(&self, query: &SearchQuery) -> Result<Page<SearchHit>, ApiError> {
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let tsquery = || to_tsquery(config(), tsquery(&query.terms));
+
+                let (total, rows): (i64, Vec<HitRow>) = match query.scope {
+                    SearchScope::Posts => {
+                        let rank = ts_rank_cd(posts::search_vector, tsquery());
+                        let total = Self::matching_posts(&query)
+                            .count()
+                            .get_result(conn)
+                            .map_err(ApiError::from)?;
+                        let rows = Self::matching_posts(&query)
+                            .select((
+                                posts::id,
+                                posts::id,
+                                posts::slug,
+                                posts::title,
+                                posts::author_id,
+                                posts::created_at,
+                                rank.clone(),
+                                ts_headline(
+                                    config(),
+                                    posts::content,
+                                    tsquery(),
+                                    headline_options(),
+                                ),
+                            ))
+                            .order((rank.desc(), posts::created_at.desc(), posts::id.desc()))
+                            .limit(query.page.limit)
+                            .offset(query.page.offset())
+                            .load(conn)
+                            .map_err(ApiError::from)?;
+                        (total, rows)
+                    }
+                    SearchScope::Comments => {
+                        let rank = ts_rank_cd(comments::search_vector, tsquery());
+                        let total = Self::matching_comments(&query)
+                            .count()
+                            .get_result(conn)
+                            .map_err(ApiError::from)?;
+                        let rows = Self::matching_comments(&query)
+                            .select((
+                                comments::id,
+                                comments::post_id,
+                                posts::slug,
+                                posts::title,
+                                comments::author_id,
+                                comments::created_at,
+                                rank.clone(),
+                                ts_headline(
+                                    config(),
+                                    comments::content,
+                                    tsquery(),
+                                    headline_options(),
+                                ),
+                            ))
+                            .order((
+                                rank.desc(),
+                                comments::created_at.desc(),
+                                comments::id.desc(),
+                            ))
+                            .limit(query.page.limit)
+                            .offset(query.page.offset())
+                            .load(conn)
+                            .map_err(ApiError::from)?;
+                        (total, rows)
+                    }
+                };
+
+                Ok(Page::new(
+                    rows.into_iter().map(hit).collect(),
+                    total,
+                    query.page,
+                ))
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl SearchRepository for Arc<SearchRepositoryImpl> {
+    async fn search(&self, query: &SearchQuery) -> Result<Page<SearchHit>, ApiError> {
+        self.as_ref().search(query).await
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    domain::models::pagination::{Page, PageRequest},
+    domain::models::post::PostStatus,
+    domain::models::slug,
+    domain::models::tag::{NewTag, Tag},
+    domain::repositories::TagRepository,
+    infrastructure::database::{
+        executor::DbExecutor,
+        schema::{post_tags, posts, tags},
+    },
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct TagRepositoryImpl {
+    db: DbExecutor,
+}
+
+impl TagRepositoryImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TagRepository for TagRepositoryImpl {
+    async fn find_by_slug(&self, tag_slug: &str) -> Result<Tag, ApiError> {
+        use crate::infrastructure::database::schema::tags::dsl::*;
+
+        let tag_slug = tag_slug.to_owned();
+        self.db
+            .run(move |conn| {
+                tags.filter(slug.eq(tag_slug))
+                    .select(Tag::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn find_by_slugs(&self, slugs: &[String]) -> Result<Vec<Tag>, ApiError> {
+        let slugs = slugs.to_vec();
+        self.db
+            .run(move |conn| {
+                tags::table
+                    .filter(tags::slug.eq_any(slugs))
+                    .select(Tag::as_select())
+                    .load(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        // Slugs are lowercase letters, digits and hyphens, so `base` holds
+        // no LIKE wildcards.
+        let base = base.to_owned();
+        let pattern = format!("{}-%", base);
+        self.db
+            .run(move |conn| {
+                let taken: Vec<(String, Uuid)> = tags::table
+                    .filter(tags::slug.eq(&base).or(tags::slug.like(&pattern)))
+                    .select((tags::slug, tags::id))
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(taken
+                    .into_iter()
+                    .filter(|(taken, _)| slug::candidate_number(&base, taken).is_some())
+                    .collect())
+            })
+            .await
+    }
+
+    async fn find_all(&self, page: PageRequest) -> Result<Page<Tag>, ApiError> {
+        use crate::infrastructure::database::schema::tags::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let total = tags.count().get_result(conn).map_err(ApiError::from)?;
+
+                let items = tags
+                    .order((name.asc(), id.asc()))
+                    .select(Tag::as_select())
+                    .limit(page.limit)
+                    .offset(page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, page))
+            })
+            .await
+    }
+
+    async fn create(&self, tag: Tag) -> Result<Tag, ApiError> {
+        use crate::infrastructure::database::schema::tags::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let new_tag = NewTag {
+                    name: tag.name,
+                    slug: tag.slug,
+                };
+
+                diesel::insert_into(tags)
+                    .values(&new_tag)
+                    .returning(Tag::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn update(&self, tag_id: Uuid, tag: Tag) -> Result<Tag, ApiError> {
+        use crate::infrastructure::database::schema::tags::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                diesel::update(tags.filter(id.eq(tag_id)))
+                    .set((
+                        name.eq(tag.name),
+                        slug.eq(tag.slug),
+                        updated_at.eq(chrono::Local::now().naive_local()),
+                    ))
+                    .returning(Tag::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
+    }
+
+    async fn delete(&self, tag_id: Uuid) -> Result<(), ApiError> {
+        use crate::infrastructure::database::schema::tags::dsl::*;
+
+        self.db
+            .run(move |conn| {
+                let deleted = diesel::delete(tags.filter(id.eq(tag_id)))
+                    .execute(conn)
+                    .map_err(ApiError::from)?;
+
+                if deleted == 0 {
+                    return Err(ApiError::NotFound);
+                }
+                Ok(())
+            })
+            .await
+    }
+
+    async fn count_published(&self, tag_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>, ApiError> {
+        let tag_ids = tag_ids.to_vec();
+        self.db
+            .run(move |conn| {
+                let counts: Vec<(Uuid, i64)> = post_tags::table
+                    .inner_join(posts::table)
+                    .filter(post_tags::tag_id.eq_any(tag_ids))
+                    .filter(posts::status.eq(PostStatus::Published))
+                    .group_by(post_tags::tag_id)
+                    .select((post_tags::tag_id, diesel::dsl::count_star()))
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+                Ok(counts.into_iter().collect())
+            })
+            .await
+    }
+
+    async fn find_by_posts(&self, post_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Tag>>, ApiError> {
+        let post_ids = post_ids.to_vec();
+        self.db
+            .run(move |conn| {
+                let rows: Vec<(Uuid, Tag)> = post_tags::table
+                    .inner_join(tags::table)
+                    .filter(post_tags::post_id.eq_any(post_ids))
+                    .order((tags::name.asc(), tags::id.asc()))
+                    .select((post_tags::post_id, Tag::as_select()))
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                let mut by_post: HashMap<Uuid, Vec<Tag>> = HashMap::new();
+                for (post_id, tag) in rows {
+                    by_post.entry(post_id).or_default().push(tag);
+                }
+                Ok(by_post)
+            })
+            .await
+    }
+
+    async fn set_for_post(&self, post_id: Uuid, tag_ids: &[Uuid]) -> Result<(), ApiError> {
+        let rows: Vec<_> = tag_ids
+            .iter()
+            .map(|tag_id| {
+                (
+                    post_tags::post_id.eq(post_id),
+                    post_tags::tag_id.eq(*tag_id),
+                )
+            })
+            .collect();
+        self.db
+            .run(move |conn| {
+                conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                    diesel::delete(post_tags::table.filter(post_tags::post_id.eq(post_id)))
+                        .execute(conn)?;
+                    diesel::insert_into(post_tags::table)
+                        .values(&rows)
+                        .on_conflict_do_nothing()
+                        .execute(conn)?;
+                    Ok(())
+                })
+                .map_err(ApiError::from)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl TagRepository for Arc<TagRepositoryImpl> {
+    async fn find_by_slug(&self, slug: &str) -> Result<Tag, ApiError> {
+        self.as_ref().find_by_slug(slug).await
+    }
+
+    async fn find_by_slugs(&self, slugs: &[String]) -> Result<Vec<Tag>, ApiError> {
+        self.as_ref().find_by_slugs(slugs).await
+    }
+
+    async fn slug_series(&self, base: &str) -> Result<Vec<(String, Uuid)>, ApiError> {
+        self.as_ref().slug_series(base).await
+    }
+
+    async fn find_all(&self, page: PageRequest) -> Result<Page<Tag>, ApiError> {
+        self.as_ref().find_all(page).await
+    }
+
+    async fn create(&self, tag: Tag) -> Result<Tag, ApiError> {
+        self.as_ref().create(tag).await
+    }
+
+    async fn update(&self, tag_id: Uuid, tag: Tag) -> Result<Tag, ApiError> {
+        self.as_ref().update(tag_id, tag).await
+    }
+
+    async fn delete(&self, tag_id: Uuid) -> Result<(), ApiError> {
+        self.as_ref().delete(tag_id).await
+    }
+
+    async fn count_published(&self, tag_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>, ApiError> {
+        self.as_ref().count_published(tag_ids).await
+    }
+
+    async fn find_by_posts(&self, post_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Tag>>, ApiError> {
+        self.as_ref().find_by_posts(post_ids).await
+    }
+
+    async fn set_for_post(&self, post_id: Uuid, tag_ids: &[Uuid]) -> Result<(), ApiError> {
+        self.as_ref().set_for_post(post_id, tag_ids).await
+    }
+}
@@ -0,0 +1,129 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    domain::repositories::{TransactionScope, UnitOfWork},
+    infrastructure::{
+        database::executor::DbExecutor,
+        repositories::{
+            category_repository_impl::CategoryRepositoryImpl,
+            comment_repository_impl::CommentRepositoryImpl,
+            post_repository_impl::PostRepositoryImpl,
+            post_revision_repository_impl::PostRevisionRepositoryImpl,
+            tag_repository_impl::TagRepositoryImpl, user_repository_impl::UserRepositoryImpl,
+        },
+    },
+    shared::error::ApiError,
+};
+
+#[derive(Clone)]
+pub struct UnitOfWorkImpl {
+    db: DbExecutor,
+}
+
+impl UnitOfWorkImpl {
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+}
+
+/// The Diesel repositories, all running on one pinned connection.
+#[derive(Clone)]
+pub struct TransactionScopeImpl {
+    users: UserRepositoryImpl,
+    posts: PostRepositoryImpl,
+    post_revisions: PostRevisionRepositoryImpl,
+    tags: TagRepositoryImpl,
+    categories: CategoryRepositoryImpl,
+    comments: CommentRepositoryImpl,
+}
+
+impl TransactionScopeImpl {
+    fn new(db: DbExecutor) -> Self {
+        Self {
+            users: UserRepositoryImpl::new(db.clone()),
+            posts: PostRepositoryImpl::new(db.clone()),
+            post_revisions: PostRevisionRepositoryImpl::new(db.clone()),
+            tags: TagRepositoryImpl::new(db.clone()),
+            categories: CategoryRepositoryImpl::new(db.clone()),
+            comments: CommentRepositoryImpl::new(db),
+        }
+    }
+}
+
+impl TransactionScope for TransactionScopeImpl {
+    type Users = UserRepositoryImpl;
+    type Posts = PostRepositoryImpl;
+    type PostRevisions = PostRevisionRepositoryImpl;
+    type Tags = TagRepositoryImpl;
+    type Categories = CategoryRepositoryImpl;
+    type Comments = CommentRepositoryImpl;
+
+    fn users(&self) -> &Self::Users {
+        &self.users
+    }
+
+    fn posts(&self) -> &Self::Posts {
+        &self.posts
+    }
+
+    fn post_revisions(&self) -> &Self::PostRevisions {
+        &self.post_revisions
+    }
+
+    fn tags(&self) -> &Self::Tags {
+        &self.tags
+    }
+
+    fn categories(&self) -> &Self::Categories {
+        &self.categories
+    }
+
+    fn comments(&self) -> &Self::Comments {
+        &self.comments
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for UnitOfWorkImpl {
+    type Scope = TransactionScopeImpl;
+
+    async fn transaction<F, Fut, T>(&self, work: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Self::Scope) -> Fut + Send,
+        Fut: Future<Output = Result<T, ApiError>> + Send,
+        T: Send,
+    {
+        let db = self.db.begin().await?;
+
+        match work(TransactionScopeImpl::new(db.clone())).await {
+            Ok(value) => {
+                db.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                // A failed rollback leaves the transaction open, which makes
+                // the pool discard the connection; the original error is
+                // the one worth reporting.
+                let _ = db.rollback().await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for Arc<UnitOfWorkImpl> {
+    type Scope = TransactionScopeImpl;
+
+    async fn transaction<F, Fut, T>(&self, work: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Self::Scope) -> Fut + Send,
+        Fut: Future<Output = Result<T, ApiError>> + Send,
+        T: Send,
+    {
+        self.as_ref().transaction(work).await
+    }
+}
@@ -1,129 +1,171 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use diesel::{QueryDsl, RunQueryDsl, prelude::*};
+use diesel::{QueryDsl, RunQueryDsl, pg::Pg, prelude::*};
 use uuid::Uuid;
 
 use crate::{
-    domain::models::user::{NewUser, UpdateUserData, User},
+    domain::models::pagination::{Page, SortDirection},
+    domain::models::user::{NewUser, UpdateUserData, User, UserFilter, UserQuery, UserSortField},
     domain::repositories::UserRepository,
-    infrastructure::database::connection::PgPool,
+    infrastructure::database::{executor::DbExecutor, schema::users},
     shared::error::ApiError,
 };
 
 #[derive(Clone)]
 pub struct UserRepositoryImpl {
-    pool: PgPool,
+    db: DbExecutor,
 }
 
 impl UserRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbExecutor) -> Self {
+        Self { db }
+    }
+
+    fn filtered(filter: &UserFilter) -> users::BoxedQuery<'static, Pg> {
+        let mut query = users::table.into_boxed();
+
+        if let Some(role) = filter.role {
+            query = query.filter(users::role.eq(role));
+        }
+        if let Some(created_after) = filter.created_after {
+            query = query.filter(users::created_at.ge(created_after));
+        }
+        if let Some(created_before) = filter.created_before {
+            query = query.filter(users::created_at.lt(created_before));
+        }
+
+        query
     }
 }
 
 #[async_trait]
 impl UserRepository for UserRepositoryImpl {
-    async fn find(&self, _id: Uuid) -> Result<User, ApiError> {
+    async fn find(&self, user_id: Uuid) -> Result<User, ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        users
-            .filter(id.eq(id))
-            .select(User::as_select())
-            .first(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        self.db
+            .run(move |conn| {
+                users
+                    .filter(id.eq(user_id))
+                    .select(User::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
-    async fn find_all(&self) -> Result<Vec<User>, ApiError> {
+    async fn find_all(&self, query: &UserQuery) -> Result<Page<User>, ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        users
-            .select(User::as_select())
-            .load(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        let query = query.clone();
+        self.db
+            .run(move |conn| {
+                let total = Self::filtered(&query.filter)
+                    .count()
+                    .get_result(conn)
+                    .map_err(ApiError::from)?;
+
+                let items = Self::filtered(&query.filter).select(User::as_select());
+                let items = match (query.sort, query.direction) {
+                    (UserSortField::CreatedAt, SortDirection::Asc) => items.order(created_at.asc()),
+                    (UserSortField::CreatedAt, SortDirection::Desc) => {
+                        items.order(created_at.desc())
+                    }
+                    (UserSortField::Username, SortDirection::Asc) => items.order(username.asc()),
+                    (UserSortField::Username, SortDirection::Desc) => items.order(username.desc()),
+                    (UserSortField::Email, SortDirection::Asc) => items.order(email.asc()),
+                    (UserSortField::Email, SortDirection::Desc) => items.order(email.desc()),
+                };
+                let items = match query.direction {
+                    SortDirection::Asc => items.then_order_by(id.asc()),
+                    SortDirection::Desc => items.then_order_by(id.desc()),
+                };
+
+                let items = items
+                    .limit(query.page.limit)
+                    .offset(query.page.offset())
+                    .load(conn)
+                    .map_err(ApiError::from)?;
+
+                Ok(Page::new(items, total, query.page))
+            })
+            .await
     }
 
-    async fn find_by_email(&self, _email: &str) -> Result<User, ApiError> {
+    async fn find_by_email(&self, user_email: &str) -> Result<User, ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        users
-            .filter(email.eq(email))
-            .select(User::as_select())
-            .first(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        let user_email = user_email.to_owned();
+        self.db
+            .run(move |conn| {
+                users
+                    .filter(email.eq(user_email))
+                    .select(User::as_select())
+                    .first(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn create(&self, user: User) -> Result<User, ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        let new_user = NewUser {
-            username: user.username,
-            email: user.email,
-            password_hash: user.password_hash, // Already hashed by the domain model
-        };
-
-        diesel::insert_into(users)
-            .values(&new_user)
-            .returning(User::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        self.db
+            .run(move |conn| {
+                let new_user = NewUser {
+                    username: user.username,
+                    email: user.email,
+                    password_hash: user.password_hash, // Already hashed by the domain model
+                    role: user.role,
+                };
+
+                diesel::insert_into(users)
+                    .values(&new_user)
+                    .returning(User::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn update(&self, user_id: Uuid, user: User) -> Result<User, ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        let update_data = UpdateUserData {
-            username: Some(user.username),
-            email: Some(user.email),
-            password_hash: Some(user.password_hash), // Already hashed by the domain model
-            updated_at: Some(chrono::Local::now().naive_local()),
-        };
-
-        diesel::update(users.filter(id.eq(user_id)))
-            .set(&update_data)
-            .returning(User::as_returning())
-            .get_result(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        self.db
+            .run(move |conn| {
+                let update_data = UpdateUserData {
+                    username: Some(user.username),
+                    email: Some(user.email),
+                    password_hash: Some(user.password_hash), // Already hashed by the domain model
+                    role: Some(user.role),
+                    updated_at: Some(chrono::Local::now().naive_local()),
+                };
+
+                diesel::update(users.filter(id.eq(user_id)))
+                    .set(&update_data)
+                    .returning(User::as_returning())
+                    .get_result(conn)
+                    .map_err(ApiError::from)
+            })
+            .await
     }
 
     async fn delete(&self, user_id: Uuid) -> Result<(), ApiError> {
         use crate::infrastructure::database::schema::users::dsl::*;
 
-        let mut conn = self
-            .pool
-            .get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        diesel::delete(users.filter(id.eq(user_id)))
-            .execute(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        Ok(())
+        self.db
+            .run(move |conn| {
+                let deleted = diesel::delete(users.filter(id.eq(user_id)))
+                    .execute(conn)
+                    .map_err(ApiError::from)?;
+
+                if deleted == 0 {
+                    return Err(ApiError::NotFound);
+                }
+                Ok(())
+            })
+            .await
     }
 }
 
@@ -133,8 +175,8 @@ impl UserRepository for Arc<UserRepositoryImpl> {
         self.as_ref().find(id).await
     }
 
-    async fn find_all(&self) -> Result<Vec<User>, ApiError> {
-        self.as_ref().find_all().await
+    async fn find_all(&self, query: &UserQuery) -> Result<Page<User>, ApiError> {
+        self.as_ref().find_all(query).await
     }
 
     async fn find_by_email(&self, email: &str) -> Result<User, ApiError> {
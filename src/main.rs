@@ -1,52 +1,334 @@
-mod application;
-mod domain;
-mod infrastructure;
-mod shared;
-
-use application::routes::{self};
-use axum::{Router, routing::get, serve};
-use domain::services::{
-    comment_service::CommentServiceImpl, post_service::PostServiceImpl,
+use axum::{Router, middleware, serve};
+use blog::application::{
+    jobs::scheduled_publishing,
+    routes::{self, health_routes},
+};
+use blog::domain::models::comment::ModerationSettings;
+use blog::domain::repositories::{
+    CategoryRepository, CommentRepository, HealthRepository, PostRepository,
+    PostRevisionRepository, RefreshTokenRepository, SearchRepository, TagRepository, UnitOfWork,
+    UserRepository,
+};
+use blog::domain::services::{
+    auth_service::{AuthConfig, AuthServiceImpl, TokenCodec},
+    comment_service::CommentServiceImpl,
+    health_service::HealthServiceImpl,
+    post_service::PostServiceImpl,
+    search_service::SearchServiceImpl,
+    taxonomy_service::TaxonomyServiceImpl,
     user_service::UserServiceImpl,
 };
-use dotenvy::dotenv;
-use infrastructure::database::connection::init_pool;
-use infrastructure::repositories::{
-    comment_repository_impl::CommentRepositoryImpl, post_repository_impl::PostRepositoryImpl,
+use blog::infrastructure::database::{
+    connection::{PgPool, init_pool},
+    executor::DbExecutor,
+    migrations,
+};
+use blog::infrastructure::repositories::{
+    category_repository_impl::CategoryRepositoryImpl,
+    comment_repository_impl::CommentRepositoryImpl,
+    health_repository_impl::HealthRepositoryImpl,
+    in_memory::{
+        InMemoryStore, category_repository::InMemoryCategoryRepository,
+        comment_repository::InMemoryCommentRepository, health_repository::InMemoryHealthRepository,
+        post_repository::InMemoryPostRepository,
+        post_revision_repository::InMemoryPostRevisionRepository,
+        refresh_token_repository::InMemoryRefreshTokenRepository,
+        search_repository::InMemorySearchRepository, tag_repository::InMemoryTagRepository,
+        unit_of_work::InMemoryUnitOfWork, user_repository::InMemoryUserRepository,
+    },
+    post_repository_impl::PostRepositoryImpl,
+    post_revision_repository_impl::PostRevisionRepositoryImpl,
+    refresh_token_repository_impl::RefreshTokenRepositoryImpl,
+    search_repository_impl::SearchRepositoryImpl,
+    tag_repository_impl::TagRepositoryImpl,
+    unit_of_work_impl::UnitOfWorkImpl,
     user_repository_impl::UserRepositoryImpl,
 };
-use std::sync::Arc;
+use blog::shared::{
+    config::{Config, DatabaseBackend},
+    error::ApiError,
+    request_id,
+    shutdown::{Shutdown, termination_signal},
+};
+use dotenvy::dotenv;
+use std::{env, future::IntoFuture, process, sync::Arc, time::Duration};
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// What the binary was asked to do, chosen by its first argument.
+enum Mode {
+    Serve,
+    /// `--migrate-only`: apply pending migrations and exit.
+    MigrateOnly,
+    /// `--pending-migrations`: list migrations that have not run and exit,
+    /// with status 1 if there are any so deploy scripts can gate on it.
+    PendingMigrations,
+}
+
+impl Mode {
+    fn from_args() -> Result<Self, String> {
+        let args: Vec<String> = env::args().skip(1).collect();
+        match args.as_slice() {
+            [] => Ok(Mode::Serve),
+            [flag] if flag == "--migrate-only" => Ok(Mode::MigrateOnly),
+            [flag] if flag == "--pending-migrations" => Ok(Mode::PendingMigrations),
+            _ => Err(format!(
+                "Unrecognized arguments: {}\nUsage: blog [--migrate-only | --pending-migrations]",
+                args.join(" ")
+            )),
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
-    // Initialize database pool
-    let pool = init_pool().expect("Failed to create database pool");
+    let mode = Mode::from_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let shutdown = Shutdown::new();
+
+    let (app, db) = match config.database.backend {
+        DatabaseBackend::Postgres => {
+            // Initialize database pool
+            let pool = init_pool(&config.database).expect("Failed to create database pool");
+
+            match mode {
+                Mode::MigrateOnly => {
+                    apply_migrations(&pool);
+                    return;
+                }
+                Mode::PendingMigrations => {
+                    report_pending_migrations(&pool);
+                    return;
+                }
+                Mode::Serve => {
+                    if config.features.run_migrations {
+                        apply_migrations(&pool);
+                    }
+                }
+            }
+
+            let db = DbExecutor::from_config(pool, &config.database);
+            let app = build_app(
+                Repositories {
+                    posts: Arc::new(PostRepositoryImpl::new(db.clone())),
+                    post_revisions: Arc::new(PostRevisionRepositoryImpl::new(db.clone())),
+                    tags: Arc::new(TagRepositoryImpl::new(db.clone())),
+                    categories: Arc::new(CategoryRepositoryImpl::new(db.clone())),
+                    users: Arc::new(UserRepositoryImpl::new(db.clone())),
+                    comments: Arc::new(CommentRepositoryImpl::new(db.clone())),
+                    search: Arc::new(SearchRepositoryImpl::new(db.clone())),
+                    refresh_tokens: Arc::new(RefreshTokenRepositoryImpl::new(db.clone())),
+                    health: Arc::new(HealthRepositoryImpl::new(db.clone())),
+                    unit_of_work: Arc::new(UnitOfWorkImpl::new(db.clone())),
+                },
+                &shutdown,
+                &config,
+            );
+            (app, Some(db))
+        }
+        DatabaseBackend::Memory => {
+            if !matches!(mode, Mode::Serve) {
+                eprintln!("Migrations only apply to the postgres database backend");
+                process::exit(2);
+            }
+            println!("Using the in-memory backend; data is lost on exit");
 
-    // Initialize repositories
-    let post_repository = Arc::new(PostRepositoryImpl::new(pool.clone()));
-    let user_repository = Arc::new(UserRepositoryImpl::new(pool.clone()));
-    let comment_repository = Arc::new(CommentRepositoryImpl::new(pool));
+            let store = InMemoryStore::new();
+            let app = build_app(
+                Repositories {
+                    posts: Arc::new(InMemoryPostRepository::new(store.clone())),
+                    post_revisions: Arc::new(InMemoryPostRevisionRepository::new(store.clone())),
+                    tags: Arc::new(InMemoryTagRepository::new(store.clone())),
+                    categories: Arc::new(InMemoryCategoryRepository::new(store.clone())),
+                    users: Arc::new(InMemoryUserRepository::new(store.clone())),
+                    comments: Arc::new(InMemoryCommentRepository::new(store.clone())),
+                    search: Arc::new(InMemorySearchRepository::new(store.clone())),
+                    refresh_tokens: Arc::new(InMemoryRefreshTokenRepository::new(store.clone())),
+                    health: Arc::new(InMemoryHealthRepository::new()),
+                    unit_of_work: Arc::new(InMemoryUnitOfWork::new(store)),
+                },
+                &shutdown,
+                &config,
+            );
+            (app, None)
+        }
+    };
 
+    // Bind listener
+    let listener = TcpListener::bind(config.server.listen_addr).await.unwrap();
+    println!("Server running on {}", listener.local_addr().unwrap());
+
+    let stop_accepting = CancellationToken::new();
+    tokio::spawn(wait_for_termination(
+        shutdown.clone(),
+        stop_accepting.clone(),
+        config.shutdown.readiness_delay(),
+    ));
+
+    // Once the listener closes, in-flight requests get until the drain
+    // deadline to finish before their connections are dropped.
+    let server = serve(listener, app)
+        .with_graceful_shutdown(stop_accepting.clone().cancelled_owned())
+        .into_future();
+    let drain_deadline = async {
+        stop_accepting.cancelled().await;
+        tokio::time::sleep(config.shutdown.drain_timeout()).await;
+    };
+    tokio::select! {
+        result = server => result.unwrap(),
+        _ = drain_deadline => eprintln!("Drain deadline passed, dropping remaining connections"),
+    }
+
+    if !shutdown
+        .finish_background(config.shutdown.background_timeout())
+        .await
+    {
+        eprintln!("Background tasks did not finish in time");
+    }
+    if let Some(db) = db {
+        if !db.close(config.shutdown.background_timeout()).await {
+            eprintln!("Database queries were still running at shutdown");
+        }
+        drop(db);
+    }
+    println!("Shutdown complete");
+}
+
+/// One backend's repositories, ready to be handed to the services.
+struct Repositories<P, V, G, K, U, C, S, T, H, W> {
+    posts: Arc<P>,
+    post_revisions: Arc<V>,
+    tags: Arc<G>,
+    categories: Arc<K>,
+    users: Arc<U>,
+    comments: Arc<C>,
+    search: Arc<S>,
+    refresh_tokens: Arc<T>,
+    health: Arc<H>,
+    unit_of_work: Arc<W>,
+}
+
+/// Wires services over the given repositories into the full application.
+fn build_app<P, V, G, K, U, C, S, T, H, W>(
+    repositories: Repositories<P, V, G, K, U, C, S, T, H, W>,
+    shutdown: &Shutdown,
+    config: &Config,
+) -> Router
+where
+    P: PostRepository + Send + Sync + 'static,
+    V: PostRevisionRepository + Send + Sync + 'static,
+    G: TagRepository + Send + Sync + 'static,
+    K: CategoryRepository + Send + Sync + 'static,
+    U: UserRepository + Send + Sync + 'static,
+    C: CommentRepository + Send + Sync + 'static,
+    S: SearchRepository + Send + Sync + 'static,
+    T: RefreshTokenRepository + Send + Sync + 'static,
+    H: HealthRepository + Send + Sync + 'static,
+    W: UnitOfWork + Send + Sync + 'static,
+{
     // Initialize services
-    let post_service = Arc::new(PostServiceImpl::new(Arc::clone(&post_repository)));
-    let user_service = Arc::new(UserServiceImpl::new(Arc::clone(&user_repository)));
-    let comment_service = Arc::new(CommentServiceImpl::new(Arc::clone(&comment_repository)));
+    let post_service = Arc::new(PostServiceImpl::new(
+        Arc::clone(&repositories.posts),
+        repositories.post_revisions,
+        Arc::clone(&repositories.tags),
+        Arc::clone(&repositories.unit_of_work),
+    ));
+    let publisher = Arc::clone(&post_service);
+    let publish_interval = config.scheduler.publish_interval();
+    shutdown.spawn(move |token| scheduled_publishing::run(publisher, publish_interval, token));
+    let user_service = Arc::new(UserServiceImpl::new(
+        Arc::clone(&repositories.users),
+        Arc::clone(&repositories.unit_of_work),
+    ));
+    let search_service = Arc::new(SearchServiceImpl::new(
+        repositories.search,
+        Arc::clone(&repositories.tags),
+    ));
+    let taxonomy_service = Arc::new(TaxonomyServiceImpl::new(
+        repositories.tags,
+        repositories.categories,
+        repositories.unit_of_work,
+    ));
+    let comment_service = Arc::new(CommentServiceImpl::new(
+        repositories.comments,
+        repositories.posts,
+        ModerationSettings::from(&config.moderation),
+    ));
+    let auth_service = Arc::new(AuthServiceImpl::new(
+        repositories.users,
+        repositories.refresh_tokens,
+        TokenCodec::new(&AuthConfig::from(&config.auth)),
+    ));
+    let health_service = Arc::new(HealthServiceImpl::new(
+        repositories.health,
+        shutdown.clone(),
+    ));
 
     // Create router with all routes
-    let app = Router::new()
+    Router::new()
         .nest(
             "/api",
-            routes::create_routes(comment_service, post_service, user_service),
+            routes::create_routes(
+                comment_service,
+                post_service,
+                taxonomy_service,
+                search_service,
+                user_service,
+                auth_service,
+                config,
+            ),
         )
-        .route("/health", get(|| async { "OK" }));
+        .nest("/health", health_routes::health_router(health_service))
+        .fallback(|| async { ApiError::NotFound })
+        .layer(middleware::from_fn(request_id::propagate))
+}
 
-    // Bind listener
-    let listener = TcpListener::bind("127.0.0.1:5000").await.unwrap();
-    println!("Server running on {}", listener.local_addr().unwrap());
+/// Flips readiness on SIGTERM/Ctrl+C, keeps serving for `readiness_delay` so
+/// load balancers can react, then tells the server to stop accepting.
+async fn wait_for_termination(
+    shutdown: Shutdown,
+    stop_accepting: CancellationToken,
+    readiness_delay: Duration,
+) {
+    termination_signal().await;
+    println!("Shutdown signal received, draining");
+    shutdown.begin();
+    tokio::time::sleep(readiness_delay).await;
+    stop_accepting.cancel();
+}
+
+fn report_pending_migrations(pool: &PgPool) {
+    let mut conn = pool.get().expect("Failed to get a database connection");
+    let pending = migrations::pending(&mut conn).expect("Failed to read migrations");
+    if pending.is_empty() {
+        println!("No pending migrations");
+    } else {
+        println!("{} pending migration(s):", pending.len());
+        for name in pending {
+            println!("  {}", name);
+        }
+        process::exit(1);
+    }
+}
 
-    // Serve using Axum best practice
-    serve(listener, app).await.unwrap();
+fn apply_migrations(pool: &PgPool) {
+    let mut conn = pool.get().expect("Failed to get a database connection");
+    let applied = migrations::run_pending(&mut conn).expect("Failed to apply migrations");
+    if applied.is_empty() {
+        println!("Database schema is up to date");
+    } else {
+        for version in applied {
+            println!("Applied migration {}", version);
+        }
+    }
 }
@@ -0,0 +1,507 @@
+use std::{env, fs, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
+
+use axum::http::HeaderValue;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// File read when `BLOG_CONFIG` is not set. It is optional; an explicitly
+/// configured path must exist.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid value for {key}: {message}")]
+    Env { key: &'static str, message: String },
+
+    #[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+/// Application settings, resolved from built-in defaults, then an optional
+/// TOML file, then environment variables, each layer overriding the last.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthSettings,
+    pub cors: CorsConfig,
+    pub features: FeatureToggles,
+    pub shutdown: ShutdownConfig,
+    pub scheduler: SchedulerConfig,
+    pub moderation: ModerationConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub listen_addr: SocketAddr,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: SocketAddr::from(([127, 0, 0, 1], 5000)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    Postgres,
+    /// Keep everything in process memory; for demos and prototyping.
+    Memory,
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "postgres" => Ok(DatabaseBackend::Postgres),
+            "memory" => Ok(DatabaseBackend::Memory),
+            other => Err(format!("expected postgres or memory, got {:?}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub backend: DatabaseBackend,
+    pub url: String,
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub connection_timeout_secs: u64,
+    /// Upper bound on queries in flight at once; defaults to, and may not
+    /// exceed, `max_size`.
+    pub max_concurrency: Option<usize>,
+    /// How long a query may wait for a free slot before the request is shed
+    /// with a 503; defaults to the connection timeout.
+    pub queue_timeout_ms: Option<u64>,
+    /// How long the readiness probe waits for its reserved connection and
+    /// query before reporting the database as down.
+    pub probe_timeout_ms: u64,
+}
+
+impl DatabaseConfig {
+    pub fn connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.connection_timeout_secs)
+    }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(self.max_size as usize)
+    }
+
+    pub fn queue_timeout(&self) -> Duration {
+        self.queue_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| self.connection_timeout())
+    }
+
+    pub fn probe_timeout(&self) -> Duration {
+        Duration::from_millis(self.probe_timeout_ms)
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            backend: DatabaseBackend::Postgres,
+            url: String::new(),
+            max_size: 10,
+            min_idle: Some(2),
+            connection_timeout_secs: 5,
+            max_concurrency: None,
+            queue_timeout_ms: None,
+            probe_timeout_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSettings {
+    pub jwt_secret: String,
+    pub access_token_ttl_secs: u64,
+    pub refresh_token_ttl_secs: u64,
+    /// Key for signing pagination cursors; falls back to `jwt_secret`.
+    pub cursor_secret: Option<String>,
+}
+
+impl AuthSettings {
+    pub fn cursor_secret(&self) -> &str {
+        self.cursor_secret.as_deref().unwrap_or(&self.jwt_secret)
+    }
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            jwt_secret: String::new(),
+            access_token_ttl_secs: 15 * 60,
+            refresh_token_ttl_secs: 30 * 24 * 60 * 60,
+            cursor_secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Exact origins allowed to call the API from a browser. Empty disables
+    /// CORS entirely.
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureToggles {
+    /// Apply pending migrations before serving.
+    pub run_migrations: bool,
+    /// Expose `POST /api/users` for self-service sign-up.
+    pub registration: bool,
+}
+
+impl Default for FeatureToggles {
+    fn default() -> Self {
+        Self {
+            run_migrations: false,
+            registration: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long to keep serving after readiness flips, so load balancers
+    /// notice before the listener closes.
+    pub readiness_delay_secs: u64,
+    /// How long in-flight requests get to finish once the listener closes.
+    pub drain_timeout_secs: u64,
+    /// How long background tasks and running queries get after draining.
+    pub background_timeout_secs: u64,
+}
+
+impl ShutdownConfig {
+    pub fn readiness_delay(&self) -> Duration {
+        Duration::from_secs(self.readiness_delay_secs)
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_secs)
+    }
+
+    pub fn background_timeout(&self) -> Duration {
+        Duration::from_secs(self.background_timeout_secs)
+    }
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            readiness_delay_secs: 0,
+            drain_timeout_secs: 30,
+            background_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    /// How often to look for scheduled posts that are due.
+    pub publish_interval_secs: u64,
+}
+
+impl SchedulerConfig {
+    pub fn publish_interval(&self) -> Duration {
+        Duration::from_secs(self.publish_interval_secs)
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            publish_interval_secs: 30,
+        }
+    }
+}
+
+/// How new comments are moderated unless a post says otherwise.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModerationConfig {
+    /// Hold every comment for a moderator unless its author is trusted.
+    pub require_approval: bool,
+    /// Approve comments by authors straight away. Editors and admins
+    /// moderate, so theirs always are.
+    pub auto_approve_trusted: bool,
+    /// Hold comments from users without an approved comment yet.
+    pub hold_first_time: bool,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            require_approval: false,
+            auto_approve_trusted: true,
+            hold_first_time: true,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the file named by `BLOG_CONFIG` (or `config.toml` if present),
+    /// applies environment overrides and validates the result.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env::var("BLOG_CONFIG") {
+            Ok(path) => Self::from_file(PathBuf::from(path))?,
+            Err(_) => {
+                let path = PathBuf::from(DEFAULT_CONFIG_FILE);
+                if path.exists() {
+                    Self::from_file(path)?
+                } else {
+                    Self::default()
+                }
+            }
+        };
+
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: PathBuf) -> Result<Self, ConfigError> {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(source) => return Err(ConfigError::Read { path, source }),
+        };
+
+        toml::from_str(&contents).map_err(|source| ConfigError::Parse { path, source })
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env("LISTEN_ADDR", &mut self.server.listen_addr)?;
+
+        override_from_env("DATABASE_BACKEND", &mut self.database.backend)?;
+        override_from_env("DATABASE_URL", &mut self.database.url)?;
+        override_from_env("DATABASE_MAX_SIZE", &mut self.database.max_size)?;
+        override_optional_from_env("DATABASE_MIN_IDLE", &mut self.database.min_idle)?;
+        override_from_env(
+            "DATABASE_CONNECTION_TIMEOUT_SECS",
+            &mut self.database.connection_timeout_secs,
+        )?;
+        override_optional_from_env(
+            "DATABASE_MAX_CONCURRENCY",
+            &mut self.database.max_concurrency,
+        )?;
+        override_optional_from_env(
+            "DATABASE_QUEUE_TIMEOUT_MS",
+            &mut self.database.queue_timeout_ms,
+        )?;
+        override_from_env(
+            "DATABASE_PROBE_TIMEOUT_MS",
+            &mut self.database.probe_timeout_ms,
+        )?;
+
+        override_from_env("JWT_SECRET", &mut self.auth.jwt_secret)?;
+        override_from_env(
+            "ACCESS_TOKEN_TTL_SECS",
+            &mut self.auth.access_token_ttl_secs,
+        )?;
+        override_from_env(
+            "REFRESH_TOKEN_TTL_SECS",
+            &mut self.auth.refresh_token_ttl_secs,
+        )?;
+        override_optional_from_env("CURSOR_SECRET", &mut self.auth.cursor_secret)?;
+
+        if let Ok(origins) = env::var("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        override_bool_from_env("RUN_MIGRATIONS", &mut self.features.run_migrations)?;
+        override_bool_from_env("ENABLE_REGISTRATION", &mut self.features.registration)?;
+
+        override_from_env(
+            "SHUTDOWN_READINESS_DELAY_SECS",
+            &mut self.shutdown.readiness_delay_secs,
+        )?;
+        override_from_env(
+            "SHUTDOWN_DRAIN_TIMEOUT_SECS",
+            &mut self.shutdown.drain_timeout_secs,
+        )?;
+        override_from_env(
+            "SHUTDOWN_BACKGROUND_TIMEOUT_SECS",
+            &mut self.shutdown.background_timeout_secs,
+        )?;
+
+        override_from_env(
+            "SCHEDULER_PUBLISH_INTERVAL_SECS",
+            &mut self.scheduler.publish_interval_secs,
+        )?;
+
+        override_bool_from_env(
+            "MODERATION_REQUIRE_APPROVAL",
+            &mut self.moderation.require_approval,
+        )?;
+        override_bool_from_env(
+            "MODERATION_AUTO_APPROVE_TRUSTED",
+            &mut self.moderation.auto_approve_trusted,
+        )?;
+        override_bool_from_env(
+            "MODERATION_HOLD_FIRST_TIME",
+            &mut self.moderation.hold_first_time,
+        )?;
+
+        Ok(())
+    }
+
+    /// Checks the settings as a whole and reports every problem at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.database.backend == DatabaseBackend::Postgres && self.database.url.is_empty() {
+            problems.push("database.url (DATABASE_URL) must be set".to_string());
+        }
+        if self.database.max_size == 0 {
+            problems.push("database.max_size must be at least 1".to_string());
+        }
+        if let Some(min_idle) = self.database.min_idle
+            && min_idle > self.database.max_size
+        {
+            problems.push(format!(
+                "database.min_idle ({}) must not exceed database.max_size ({})",
+                min_idle, self.database.max_size
+            ));
+        }
+        if self.database.connection_timeout_secs == 0 {
+            problems.push("database.connection_timeout_secs must be at least 1".to_string());
+        }
+        if self.database.max_concurrency == Some(0) {
+            problems.push("database.max_concurrency must be at least 1".to_string());
+        }
+        // More slots than connections would let requests take the one the
+        // pool holds back for the readiness probe.
+        if let Some(max_concurrency) = self.database.max_concurrency
+            && max_concurrency > self.database.max_size as usize
+        {
+            problems.push(format!(
+                "database.max_concurrency ({}) must not exceed database.max_size ({})",
+                max_concurrency, self.database.max_size
+            ));
+        }
+        if self.database.probe_timeout_ms == 0 {
+            problems.push("database.probe_timeout_ms must be at least 1".to_string());
+        }
+
+        if self.auth.jwt_secret.is_empty() {
+            problems.push("auth.jwt_secret (JWT_SECRET) must be set".to_string());
+        }
+        if self.auth.cursor_secret.as_deref() == Some("") {
+            problems.push("auth.cursor_secret must not be empty when set".to_string());
+        }
+        if self.auth.access_token_ttl_secs == 0 {
+            problems.push("auth.access_token_ttl_secs must be at least 1".to_string());
+        }
+        if self.auth.refresh_token_ttl_secs <= self.auth.access_token_ttl_secs {
+            problems.push(
+                "auth.refresh_token_ttl_secs must be longer than auth.access_token_ttl_secs"
+                    .to_string(),
+            );
+        }
+
+        if self.scheduler.publish_interval_secs == 0 {
+            problems.push("scheduler.publish_interval_secs must be at least 1".to_string());
+        }
+
+        for origin in &self.cors.allowed_origins {
+            let well_formed = (origin.starts_with("http://") || origin.starts_with("https://"))
+                && !origin.ends_with('/')
+                && HeaderValue::from_str(origin).is_ok();
+            if !well_formed {
+                problems.push(format!(
+                    "cors.allowed_origins entry {:?} must look like https://example.com",
+                    origin
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+fn override_from_env<T>(key: &'static str, target: &mut T) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Ok(value) = env::var(key) {
+        *target = value.parse().map_err(|e: T::Err| ConfigError::Env {
+            key,
+            message: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+fn override_optional_from_env<T>(
+    key: &'static str,
+    target: &mut Option<T>,
+) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Ok(value) = env::var(key) {
+        *target = if value.is_empty() {
+            None
+        } else {
+            Some(value.parse().map_err(|e: T::Err| ConfigError::Env {
+                key,
+                message: e.to_string(),
+            })?)
+        };
+    }
+    Ok(())
+}
+
+fn override_bool_from_env(key: &'static str, target: &mut bool) -> Result<(), ConfigError> {
+    if let Ok(value) = env::var(key) {
+        *target = match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => {
+                return Err(ConfigError::Env {
+                    key,
+                    message: format!("expected true or false, got {:?}", value),
+                });
+            }
+        };
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::domain::models::pagination::{CursorPosition, SortDirection};
+use crate::shared::error::ApiError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Turns keyset positions into opaque, HMAC-signed cursor strings.
+///
+/// A cursor is bound to the scope it was issued for (e.g. a post id) and the
+/// direction it walks, so a cursor taken from one listing cannot be replayed
+/// against another or turned around, and any edit to its contents
+/// invalidates the signature.
+#[derive(Clone)]
+pub struct CursorCodec {
+    secret: Vec<u8>,
+}
+
+impl CursorCodec {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+        }
+    }
+
+    pub fn encode(
+        &self,
+        scope: Uuid,
+        direction: SortDirection,
+        position: &CursorPosition,
+    ) -> String {
+        let payload = format!(
+            "{}|{}|{}|{}",
+            scope,
+            direction_tag(direction),
+            position.created_at.and_utc().timestamp_micros(),
+            position.id
+        );
+        let signature = self.mac(payload.as_bytes()).finalize().into_bytes();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    pub fn decode(
+        &self,
+        scope: Uuid,
+        direction: SortDirection,
+        cursor: &str,
+    ) -> Result<CursorPosition, ApiError> {
+        let invalid = || ApiError::BadRequest("invalid cursor".to_string());
+
+        let (payload, signature) = cursor.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+        self.mac(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+        let mut parts = payload.split('|');
+        let (Some(cursor_scope), Some(cursor_direction), Some(micros), Some(id), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(invalid());
+        };
+
+        if cursor_scope.parse::<Uuid>().map_err(|_| invalid())? != scope
+            || cursor_direction != direction_tag(direction)
+        {
+            return Err(invalid());
+        }
+
+        let created_at = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+
+        Ok(CursorPosition {
+            created_at,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+fn direction_tag(direction: SortDirection) -> &'static str {
+    match direction {
+        SortDirection::Asc => "asc",
+        SortDirection::Desc => "desc",
+    }
+}
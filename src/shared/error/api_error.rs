@@ -1,10 +1,15 @@
+use std::collections::BTreeMap;
+
 use axum::{
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
 use thiserror::Error;
-use validator::ValidationErrors;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::shared::error::field_errors::{FieldViolation, field_violations, single_violation};
+use crate::shared::request_id;
 
 #[derive(Debug, Error)]
 pub enum ApiError {
@@ -16,44 +21,261 @@ pub enum ApiError {
 
     #[error("Validation error: {0}")]
     ValidationError(#[from] ValidationErrors),
-    // #[error("Unauthorized")]
-    // Unauthorized,
+
+    /// Input that could not even be decoded into the expected shape, e.g. a
+    /// string where a number belongs. Reported like a validation failure.
+    #[error("Invalid input for {field}: {message}")]
+    InvalidInput {
+        field: String,
+        rule: &'static str,
+        message: String,
+    },
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Forbidden")]
+    Forbidden,
+
     #[error("Internal server error")]
     InternalServerError,
-    // #[error("Bad request: {0}")]
-    // BadRequest(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Service unavailable")]
+    ServiceUnavailable,
+
+    #[error("Conflict on {field}")]
+    Conflict { field: String },
+
+    #[error("Invalid reference in {field}")]
+    InvalidReference { field: String },
+
+    #[error("Cannot move from {from} to {to}")]
+    InvalidTransition { from: String, to: String },
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        let status = match self {
+/// Prefix for the `type` member of problem responses. Each code gets its
+/// own URN so clients can match on either field.
+const PROBLEM_TYPE_PREFIX: &str = "urn:blog:problem:";
+
+/// An RFC 7807 problem details body.
+#[derive(Debug, Serialize)]
+struct Problem {
+    #[serde(rename = "type")]
+    type_uri: String,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// Present on `validation_failed`: which inputs broke which rules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<BTreeMap<String, Vec<FieldViolation>>>,
+}
+
+impl ApiError {
+    /// A validation failure on one field, for rules checked outside the
+    /// validator.
+    pub fn invalid_field(field: &'static str, code: &'static str, message: &'static str) -> Self {
+        let mut error = ValidationError::new(code);
+        error.message = Some(message.into());
+        let mut errors = ValidationErrors::new();
+        errors.add(field, error);
+        ApiError::ValidationError(errors)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
             ApiError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::NotFound => StatusCode::NOT_FOUND,
-            ApiError::ValidationError(_) => StatusCode::BAD_REQUEST,
-            // ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::ValidationError(_) | ApiError::InvalidInput { .. } => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
             ApiError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
-            // ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
-        };
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::InvalidReference { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::InvalidTransition { .. } => StatusCode::CONFLICT,
+        }
+    }
 
-        #[derive(Serialize)]
-        struct ErrorResponse {
-            error: String,
+    /// Stable, machine-readable identifier for the kind of failure. These
+    /// are part of the public API: never rename one, only add new ones.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::DatabaseError(_) => "database_error",
+            ApiError::NotFound => "not_found",
+            ApiError::ValidationError(_) | ApiError::InvalidInput { .. } => "validation_failed",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::Forbidden => "forbidden",
+            ApiError::InternalServerError => "internal_error",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::ServiceUnavailable => "service_unavailable",
+            ApiError::Conflict { .. } => "conflict",
+            ApiError::InvalidReference { .. } => "invalid_reference",
+            ApiError::InvalidTransition { .. } => "invalid_transition",
         }
+    }
 
-        let body = ErrorResponse {
-            error: self.to_string(),
+    fn title(&self) -> &'static str {
+        match self {
+            ApiError::DatabaseError(_) | ApiError::InternalServerError => "Internal server error",
+            ApiError::NotFound => "Resource not found",
+            ApiError::ValidationError(_) | ApiError::InvalidInput { .. } => "Validation failed",
+            ApiError::Unauthorized => "Authentication required",
+            ApiError::Forbidden => "Permission denied",
+            ApiError::BadRequest(_) => "Bad request",
+            ApiError::ServiceUnavailable => "Service unavailable",
+            ApiError::Conflict { .. } => "Resource already exists",
+            ApiError::InvalidReference { .. } => "Referenced resource does not exist",
+            ApiError::InvalidTransition { .. } => "Transition not allowed",
+        }
+    }
+
+    /// Client-facing explanation. Anything that could reveal internals
+    /// (SQL, driver messages) is replaced with a generic sentence.
+    fn detail(&self) -> String {
+        match self {
+            ApiError::DatabaseError(_) | ApiError::InternalServerError => {
+                "The server encountered an unexpected error.".to_string()
+            }
+            ApiError::NotFound => "The requested resource does not exist.".to_string(),
+            ApiError::ValidationError(errors) => {
+                let mut fields: Vec<&str> = errors.errors().keys().copied().collect();
+                fields.sort_unstable();
+                format!("Invalid value for: {}.", fields.join(", "))
+            }
+            ApiError::InvalidInput { field, .. } => format!("Invalid value for: {}.", field),
+            ApiError::Unauthorized => "A valid access token is required.".to_string(),
+            ApiError::Forbidden => "You are not allowed to perform this action.".to_string(),
+            ApiError::BadRequest(message) => message.clone(),
+            ApiError::ServiceUnavailable => {
+                "The server is overloaded; retry after a short delay.".to_string()
+            }
+            ApiError::Conflict { field } => format!("The value for {} is already taken.", field),
+            ApiError::InvalidReference { field } => {
+                format!(
+                    "The value for {} does not refer to an existing resource.",
+                    field
+                )
+            }
+            ApiError::InvalidTransition { from, to } => {
+                format!("A resource in state {} cannot move to {}.", from, to)
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let request_id = request_id::current();
+
+        if status.is_server_error() {
+            eprintln!(
+                "[request {}] {} {}: {}",
+                request_id.as_deref().unwrap_or("-"),
+                status.as_u16(),
+                self.code(),
+                self
+            );
+        }
+
+        let problem = Problem {
+            type_uri: format!("{}{}", PROBLEM_TYPE_PREFIX, self.code()),
+            title: self.title(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code(),
+            request_id,
+            errors: match &self {
+                ApiError::ValidationError(errors) => Some(field_violations(errors)),
+                ApiError::InvalidInput {
+                    field,
+                    rule,
+                    message,
+                } => Some(single_violation(field, rule, message)),
+                ApiError::Conflict { field } => {
+                    Some(single_violation(field, "unique", "is already taken"))
+                }
+                ApiError::InvalidReference { field } => Some(single_violation(
+                    field,
+                    "exists",
+                    "does not refer to an existing resource",
+                )),
+                _ => None,
+            },
         };
 
-        (status, axum::Json(body)).into_response()
+        let mut response = (status, axum::Json(problem)).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+        }
+        response
     }
 }
 
 // Conversion from diesel::result::Error to ApiError
 impl From<diesel::result::Error> for ApiError {
     fn from(error: diesel::result::Error) -> Self {
+        use diesel::result::{DatabaseErrorKind, Error};
+
         match error {
-            diesel::result::Error::NotFound => ApiError::NotFound,
+            Error::NotFound => ApiError::NotFound,
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, ref info) => {
+                ApiError::Conflict {
+                    field: constraint_field(info.as_ref()),
+                }
+            }
+            Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, ref info) => {
+                ApiError::InvalidReference {
+                    field: constraint_field(info.as_ref()),
+                }
+            }
             _ => ApiError::DatabaseError(error.to_string()),
         }
     }
 }
+
+/// Recovers the column behind a constraint violation. Postgres names
+/// constraints `<table>_<column>_key` / `<table>_<column>_fkey` unless told
+/// otherwise, and does not report the column for either kind. A primary
+/// key is just `<table>_pkey`, and is reported as `id`.
+fn constraint_field(info: &dyn diesel::result::DatabaseErrorInformation) -> String {
+    if let Some(column) = info.column_name() {
+        return column.to_string();
+    }
+
+    let Some(constraint) = info.constraint_name() else {
+        return "unknown".to_string();
+    };
+    if constraint.ends_with("_pkey") {
+        return "id".to_string();
+    }
+    let without_table = info
+        .table_name()
+        .and_then(|table| constraint.strip_prefix(table))
+        .and_then(|rest| rest.strip_prefix('_'))
+        .unwrap_or(constraint);
+
+    ["_fkey", "_key", "_idx"]
+        .iter()
+        .find_map(|suffix| without_table.strip_suffix(suffix))
+        .unwrap_or(without_table)
+        .to_string()
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// One failed rule on one input field.
+#[derive(Debug, Serialize)]
+pub struct FieldViolation {
+    pub code: String,
+    pub message: String,
+    pub params: Map<String, Value>,
+}
+
+/// Flattens validator output into `field path -> violations`. Nested structs
+/// use dotted paths and list items an index, e.g. `tags[2].name`.
+pub fn field_violations(errors: &ValidationErrors) -> BTreeMap<String, Vec<FieldViolation>> {
+    let mut fields = BTreeMap::new();
+    collect(errors, None, &mut fields);
+    fields
+}
+
+/// A violation map naming a single field, for errors detected outside the
+/// validator (e.g. by a database constraint).
+pub fn single_violation(
+    field: &str,
+    code: &str,
+    message: &str,
+) -> BTreeMap<String, Vec<FieldViolation>> {
+    BTreeMap::from([(
+        field.to_string(),
+        vec![FieldViolation {
+            code: code.to_string(),
+            message: message.to_string(),
+            params: Map::new(),
+        }],
+    )])
+}
+
+fn collect(
+    errors: &ValidationErrors,
+    prefix: Option<&str>,
+    fields: &mut BTreeMap<String, Vec<FieldViolation>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, field),
+            None => field.to_string(),
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(violations) => {
+                fields
+                    .entry(path)
+                    .or_default()
+                    .extend(violations.iter().map(violation));
+            }
+            ValidationErrorsKind::Struct(nested) => collect(nested, Some(&path), fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect(nested, Some(&format!("{}[{}]", path, index)), fields);
+                }
+            }
+        }
+    }
+}
+
+fn violation(error: &ValidationError) -> FieldViolation {
+    // `value` echoes the rejected input back, which may be a password.
+    let params: Map<String, Value> = error
+        .params
+        .iter()
+        .filter(|(key, _)| key.as_ref() != "value")
+        .map(|(key, value)| (key.to_string(), normalize(value)))
+        .collect();
+
+    let message = match &error.message {
+        Some(message) => message.to_string(),
+        None => default_message(&error.code, &params),
+    };
+
+    FieldViolation {
+        code: error.code.to_string(),
+        message,
+        params,
+    }
+}
+
+/// `range` bounds arrive as floats; report whole numbers as integers so
+/// clients see `"min": 1` rather than `"min": 1.0`.
+fn normalize(value: &Value) -> Value {
+    match value.as_f64() {
+        Some(number) if value.is_f64() && number.fract() == 0.0 => Value::from(number as i64),
+        _ => value.clone(),
+    }
+}
+
+fn default_message(code: &str, params: &Map<String, Value>) -> String {
+    let min = params.get("min");
+    let max = params.get("max");
+    let equal = params.get("equal");
+
+    match (code, min, max, equal) {
+        ("length", _, _, Some(equal)) => format!("must be exactly {} characters long", equal),
+        ("length", Some(min), Some(max), _) => {
+            format!("must be between {} and {} characters long", min, max)
+        }
+        ("length", Some(min), None, _) => format!("must be at least {} characters long", min),
+        ("length", None, Some(max), _) => format!("must be at most {} characters long", max),
+        ("range", Some(min), Some(max), _) => format!("must be between {} and {}", min, max),
+        ("range", Some(min), None, _) => format!("must be at least {}", min),
+        ("range", None, Some(max), _) => format!("must be at most {}", max),
+        ("email", ..) => "must be a valid email address".to_string(),
+        ("url", ..) => "must be a valid URL".to_string(),
+        ("required", ..) => "is required".to_string(),
+        _ => "is invalid".to_string(),
+    }
+}
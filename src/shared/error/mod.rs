@@ -1,3 +1,4 @@
 pub mod api_error;
+pub mod field_errors;
 
 pub use api_error::ApiError;
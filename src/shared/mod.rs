@@ -1 +1,5 @@
+pub mod config;
+pub mod cursor;
 pub mod error;
+pub mod request_id;
+pub mod shutdown;
@@ -0,0 +1,356 @@
+use axum::http::StatusCode;
+use blog::domain::models::{post::PostStatus, user::Role};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn create_comment_on_a_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(&reader)
+        .json(json!({ "content": "Nice post", "post_id": post.id }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["content"], "Nice post");
+    assert_eq!(response.body["post_id"], post.id.to_string());
+    assert_eq!(response.body["author_id"], reader.id().to_string());
+}
+
+#[tokio::test]
+async fn create_comment_on_a_missing_post_is_unprocessable() {
+    let app = TestApp::new();
+    let reader = app.user(Role::Reader).await;
+
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(&reader)
+        .json(json!({ "content": "Hello?", "post_id": Uuid::new_v4() }))
+        .send()
+        .await;
+    response.assert_field_error(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "invalid_reference",
+        "post_id",
+        "exists",
+    );
+}
+
+#[tokio::test]
+async fn create_comment_only_on_published_posts() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let reader = app.user(Role::Reader).await;
+
+    for status in [
+        PostStatus::Draft,
+        PostStatus::Scheduled,
+        PostStatus::Archived,
+    ] {
+        let post = app.post_in(&author, status).await;
+        // Readers cannot see the post at all; its author can, but it is
+        // still closed.
+        app.post("/api/comments")
+            .authenticated_as(&reader)
+            .json(json!({ "content": "Early bird", "post_id": post.id }))
+            .send()
+            .await
+            .assert_field_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_reference",
+                "post_id",
+                "exists",
+            );
+        app.post("/api/comments")
+            .authenticated_as(&author)
+            .json(json!({ "content": "Note to self", "post_id": post.id }))
+            .send()
+            .await
+            .assert_field_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                "post_id",
+                "published",
+            );
+    }
+}
+
+#[tokio::test]
+async fn create_comment_requires_authentication_and_content() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    app.post("/api/comments")
+        .json(json!({ "content": "Anonymous", "post_id": post.id }))
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.post("/api/comments")
+        .authenticated_as(&author)
+        .json(json!({ "content": "", "post_id": post.id }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "content",
+            "length",
+        );
+}
+
+#[tokio::test]
+async fn get_comment_by_id() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    app.comment_by(&author, &post).await;
+    let comment = app.comment_by(&author, &post).await;
+
+    let response = app
+        .get(&format!("/api/comments/{}", comment.id))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["id"], comment.id.to_string());
+    assert_eq!(response.body["content"], comment.content);
+
+    app.get(&format!("/api/comments/{}", Uuid::new_v4()))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn list_comments_for_a_post_by_page() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let other_post = app.post_by(&author).await;
+    for _ in 0..3 {
+        app.comment_by(&author, &post).await;
+    }
+    app.comment_by(&author, &other_post).await;
+
+    let response = app
+        .get(&format!("/api/comments/post/{}?limit=2", post.id))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 3);
+    assert_eq!(response.body["data"].as_array().unwrap().len(), 2);
+    assert!(
+        response.body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|comment| comment["post_id"] == post.id.to_string())
+    );
+}
+
+#[tokio::test]
+async fn comments_on_unpublished_posts_are_hidden_like_the_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let reader = app.user(Role::Reader).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_by(&author).await;
+    let comment = app.comment_by(&reader, &post).await;
+    app.put(&format!("/api/posts/{}/status", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "status": "archived" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    for uri in [
+        format!("/api/comments/post/{}", post.id),
+        format!("/api/comments/post/{}?cursor=", post.id),
+        format!("/api/comments/post/{}/thread", post.id),
+        format!("/api/comments/{}", comment.id),
+    ] {
+        app.get(&uri)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.get(&uri)
+            .authenticated_as(&reader)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.get(&uri)
+            .authenticated_as(&author)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        app.get(&uri)
+            .authenticated_as(&editor)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn list_comments_for_a_post_by_cursor() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let mut expected = Vec::new();
+    for _ in 0..5 {
+        expected.push(app.comment_by(&author, &post).await.id.to_string());
+    }
+
+    let mut seen = Vec::new();
+    let mut uri = format!(
+        "/api/comments/post/{}?cursor=&limit=2&direction=asc",
+        post.id
+    );
+    loop {
+        let response = app.get(&uri).send().await;
+        response.assert_status(StatusCode::OK);
+        seen.extend(
+            response.body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|comment| comment["id"].as_str().unwrap().to_string()),
+        );
+        match response.body["links"]["next"].as_str() {
+            Some(next) => uri = next.to_string(),
+            None => break,
+        }
+    }
+
+    expected.sort();
+    seen.sort();
+    assert_eq!(seen, expected);
+}
+
+#[tokio::test]
+async fn list_comments_rejects_bad_cursor_requests() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let base = format!("/api/comments/post/{}", post.id);
+
+    let response = app.get(&format!("{}?cursor=&page=2", base)).send().await;
+    let body = response.assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+    assert_eq!(body["detail"], "page and cursor cannot be combined");
+
+    let response = app
+        .get(&format!("{}?cursor=&sort=updated_at", base))
+        .send()
+        .await;
+    let body = response.assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+    assert_eq!(
+        body["detail"],
+        "cursor pagination only supports sort=created_at"
+    );
+
+    let response = app.get(&format!("{}?cursor=garbage", base)).send().await;
+    let body = response.assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+    assert_eq!(body["detail"], "invalid cursor");
+
+    // A cursor only walks the way it was issued for.
+    app.comment_by(&author, &post).await;
+    app.comment_by(&author, &post).await;
+    let response = app.get(&format!("{}?cursor=&limit=1", base)).send().await;
+    let next = response.body["links"]["next"].as_str().unwrap().to_string();
+    app.get(&next).send().await.assert_status(StatusCode::OK);
+    let response = app.get(&format!("{}&direction=asc", next)).send().await;
+    let body = response.assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+    assert_eq!(body["detail"], "invalid cursor");
+}
+
+#[tokio::test]
+async fn update_comment_by_author_or_moderator() {
+    let app = TestApp::new();
+    let author = app.user(Role::Reader).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_by(&editor).await;
+    let comment = app.comment_by(&author, &post).await;
+    let uri = format!("/api/comments/{}", comment.id);
+
+    let response = app
+        .put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "content": "Edited" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["content"], "Edited");
+
+    let response = app
+        .put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "content": "Moderated" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["author_id"], author.id().to_string());
+}
+
+#[tokio::test]
+async fn update_comment_rejects_strangers_and_missing_comments() {
+    let app = TestApp::new();
+    let author = app.user(Role::Reader).await;
+    let stranger = app.user(Role::Author).await;
+    let post = app.post_by(&stranger).await;
+    let comment = app.comment_by(&author, &post).await;
+
+    app.put(&format!("/api/comments/{}", comment.id))
+        .authenticated_as(&stranger)
+        .json(json!({ "content": "Not yours" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.put(&format!("/api/comments/{}", Uuid::new_v4()))
+        .authenticated_as(&author)
+        .json(json!({ "content": "Gone" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn delete_comment() {
+    let app = TestApp::new();
+    let author = app.user(Role::Reader).await;
+    let stranger = app.user(Role::Reader).await;
+    let post_author = app.user(Role::Author).await;
+    let post = app.post_by(&post_author).await;
+    let comment = app.comment_by(&author, &post).await;
+    let uri = format!("/api/comments/{}", comment.id);
+
+    app.delete(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.delete(&uri)
+        .authenticated_as(&stranger)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.delete(&uri)
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.get(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&uri)
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
@@ -0,0 +1,90 @@
+use blog::shared::error::ApiError;
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, Error};
+
+/// What Postgres reports about a violated constraint: the table and the
+/// constraint's name, but not the column.
+struct Violation {
+    table: Option<&'static str>,
+    constraint: &'static str,
+}
+
+impl DatabaseErrorInformation for Violation {
+    fn message(&self) -> &str {
+        "constraint violated"
+    }
+
+    fn details(&self) -> Option<&str> {
+        None
+    }
+
+    fn hint(&self) -> Option<&str> {
+        None
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        self.table
+    }
+
+    fn column_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        Some(self.constraint)
+    }
+
+    fn statement_position(&self) -> Option<i32> {
+        None
+    }
+}
+
+fn violation(
+    kind: DatabaseErrorKind,
+    table: Option<&'static str>,
+    constraint: &'static str,
+) -> ApiError {
+    Error::DatabaseError(kind, Box::new(Violation { table, constraint })).into()
+}
+
+fn conflict_field(error: ApiError) -> String {
+    match error {
+        ApiError::Conflict { field } => field,
+        other => panic!("expected Conflict, got {:?}", other),
+    }
+}
+
+#[test]
+fn unique_and_foreign_key_violations_name_their_column() {
+    let error = violation(
+        DatabaseErrorKind::UniqueViolation,
+        Some("tags"),
+        "tags_slug_key",
+    );
+    assert_eq!(conflict_field(error), "slug");
+
+    match violation(
+        DatabaseErrorKind::ForeignKeyViolation,
+        Some("comments"),
+        "comments_post_id_fkey",
+    ) {
+        ApiError::InvalidReference { field } => assert_eq!(field, "post_id"),
+        other => panic!("expected InvalidReference, got {:?}", other),
+    }
+}
+
+#[test]
+fn primary_key_violations_are_reported_against_id() {
+    let error = violation(
+        DatabaseErrorKind::UniqueViolation,
+        Some("refresh_tokens"),
+        "refresh_tokens_pkey",
+    );
+    assert_eq!(conflict_field(error), "id");
+
+    let error = violation(
+        DatabaseErrorKind::UniqueViolation,
+        None,
+        "refresh_tokens_pkey",
+    );
+    assert_eq!(conflict_field(error), "id");
+}
@@ -0,0 +1,14 @@
+//! HTTP-level tests for the routes mounted by `create_routes`, driven
+//! in-process through the harness in `support`.
+
+mod comments;
+mod errors;
+mod markdown;
+mod moderation;
+mod posts;
+mod revisions;
+mod search;
+mod support;
+mod taxonomy;
+mod threads;
+mod users;
@@ -0,0 +1,231 @@
+use axum::http::StatusCode;
+use blog::domain::models::user::Role;
+use serde_json::json;
+
+use crate::support::{TestApp, TestUser};
+
+async fn rendered_post(app: &TestApp, author: &TestUser, content: &str) -> String {
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(author)
+        .json(json!({ "title": "Rendered", "content": content }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body["content_html"].as_str().unwrap().to_string()
+}
+
+async fn rendered_comment(app: &TestApp, author: &TestUser, content: &str) -> String {
+    let post = app.post_by(author).await;
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(author)
+        .json(json!({ "content": content, "post_id": post.id }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body["content_html"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn posts_render_commonmark_with_extensions() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let html = rendered_post(
+        &app,
+        &author,
+        "# Intro\n\nSome *text*.[^1]\n\n\
+         | Name | Size |\n|:-----|-----:|\n| a | 1 |\n\n\
+         ```rust\nfn main() {}\n```\n\n\
+         ![Logo](https://example.com/logo.png)\n\n\
+         [^1]: A note.\n",
+    )
+    .await;
+
+    assert!(html.contains(r#"<h1 id="intro">Intro</h1>"#), "{}", html);
+    assert!(html.contains("<em>text</em>"), "{}", html);
+    assert!(
+        html.contains(r#"<th style="text-align:left">Name</th>"#),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"<td style="text-align:right">1</td>"#),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"<code class="language-rust">fn main() {}"#),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"<img src="https://example.com/logo.png" alt="Logo">"#),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r##"<sup class="footnote-reference"><a href="#fn-1" rel="noopener noreferrer">1</a></sup>"##),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"<div class="footnote-definition" id="fn-1">"#),
+        "{}",
+        html
+    );
+}
+
+#[tokio::test]
+async fn heading_anchors_are_unique_within_a_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let html = rendered_post(&app, &author, "## Setup\n\n## Setup\n\n### `cargo` Usage\n").await;
+
+    assert!(html.contains(r#"<h2 id="setup">"#), "{}", html);
+    assert!(html.contains(r#"<h2 id="setup-2">"#), "{}", html);
+    assert!(html.contains(r#"<h3 id="cargo-usage">"#), "{}", html);
+}
+
+#[tokio::test]
+async fn post_html_is_sanitized() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let html = rendered_post(
+        &app,
+        &author,
+        "<script>alert(1)</script>\n\n\
+         <p onclick=\"steal()\">Hi</p>\n\n\
+         [click](javascript:alert(1))\n\n\
+         ```x\" onmouseover=\"steal()\nbody\n```\n",
+    )
+    .await;
+
+    assert!(!html.contains("script"), "{}", html);
+    assert!(!html.contains("onclick"), "{}", html);
+    assert!(!html.contains("javascript:"), "{}", html);
+    assert!(!html.contains("onmouseover"), "{}", html);
+    assert!(html.contains("<p>Hi</p>"), "{}", html);
+    assert!(html.contains("<code>body"), "{}", html);
+}
+
+#[tokio::test]
+async fn post_html_follows_edits_and_restores() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": "Draft", "content": "**one**" }))
+        .send()
+        .await;
+    let uri = format!("/api/posts/{}", response.body["id"].as_str().unwrap());
+
+    let response = app
+        .put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "content": "_two_" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["content_html"], "<p><em>two</em></p>\n");
+
+    let response = app
+        .post(&format!("{}/revisions/1/restore", uri))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    assert_eq!(
+        response.body["content_html"],
+        "<p><strong>one</strong></p>\n"
+    );
+    let response = app.get(&uri).authenticated_as(&author).send().await;
+    assert_eq!(
+        response.body["content_html"],
+        "<p><strong>one</strong></p>\n"
+    );
+}
+
+#[tokio::test]
+async fn rows_saved_before_rendering_are_rendered_on_read() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    // The fixtures write straight to the store, leaving no stored HTML.
+    let post = app.post_by(&author).await;
+    let comment = app.comment_by(&author, &post).await;
+    assert!(post.content_html.is_none());
+
+    let response = app
+        .get(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    assert_eq!(
+        response.body["content_html"],
+        format!("<p>{}</p>\n", post.content)
+    );
+    let response = app
+        .get(&format!("/api/comments/{}", comment.id))
+        .send()
+        .await;
+    assert_eq!(
+        response.body["content_html"],
+        format!("<p>{}</p>\n", comment.content)
+    );
+}
+
+#[tokio::test]
+async fn comments_allow_only_a_restricted_subset() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let html = rendered_comment(
+        &app,
+        &author,
+        "## Hi\n\n\
+         <b>bold</b> and ![a cat](https://example.com/cat.png)\n\n\
+         [home](https://example.com) and `code`[^1]\n\n\
+         [^1]: Just text.\n",
+    )
+    .await;
+
+    assert!(html.contains("<h2>Hi</h2>"), "{}", html);
+    assert!(
+        html.contains("&lt;b&gt;bold&lt;/b&gt; and a cat"),
+        "{}",
+        html
+    );
+    assert!(!html.contains("<img"), "{}", html);
+    assert!(
+        html.contains(
+            r#"<a href="https://example.com" rel="nofollow noopener noreferrer">home</a>"#
+        ),
+        "{}",
+        html
+    );
+    assert!(html.contains("<code>code</code>[^1]"), "{}", html);
+    assert!(!html.contains("footnote"), "{}", html);
+    assert!(html.contains("<p>[^1]: Just text.</p>"), "{}", html);
+
+    let comment_id = app
+        .post("/api/comments")
+        .authenticated_as(&author)
+        .json(json!({ "content": "first", "post_id": app.post_by(&author).await.id }))
+        .send()
+        .await
+        .body["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response = app
+        .put(&format!("/api/comments/{}", comment_id))
+        .authenticated_as(&author)
+        .json(json!({ "content": "~~second~~" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["content_html"], "<p><del>second</del></p>\n");
+}
@@ -0,0 +1,370 @@
+use axum::http::StatusCode;
+use blog::domain::models::{post::Post, user::Role};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::support::{TestApp, TestUser};
+
+async fn comment(app: &TestApp, user: &TestUser, post: &Post) -> Value {
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(user)
+        .json(json!({ "content": "Nice post", "post_id": post.id }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body.clone()
+}
+
+async fn public_ids(app: &TestApp, post: &Post) -> Vec<Value> {
+    let response = app
+        .get(&format!("/api/comments/post/{}", post.id))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|comment| comment["id"].clone())
+        .collect()
+}
+
+async fn moderate(app: &TestApp, moderator: &TestUser, ids: &[&Value], status: &str) -> Value {
+    let response = app
+        .post("/api/comments/queue/moderate")
+        .authenticated_as(moderator)
+        .json(json!({
+            "ids": ids.iter().map(|comment| comment["id"].clone()).collect::<Vec<_>>(),
+            "status": status,
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.body.clone()
+}
+
+fn moderation_uri(post: &Post) -> String {
+    format!("/api/comments/post/{}/moderation", post.id)
+}
+
+#[tokio::test]
+async fn first_time_commenters_wait_for_approval() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&editor).await;
+
+    let held = comment(&app, &reader, &post).await;
+    assert_eq!(held["status"], "pending");
+    assert!(public_ids(&app, &post).await.is_empty());
+    let thread = app
+        .get(&format!("/api/comments/post/{}/thread", post.id))
+        .send()
+        .await;
+    assert_eq!(thread.body["data"], json!([]));
+
+    let uri = format!("/api/comments/{}", held["id"].as_str().unwrap());
+    app.get(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.get(&uri)
+        .authenticated_as(&reader)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/api/comments")
+        .authenticated_as(&editor)
+        .json(json!({ "content": "Reply", "post_id": post.id, "parent_id": held["id"] }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_reference",
+            "parent_id",
+            "exists",
+        );
+
+    let approved = moderate(&app, &editor, &[&held], "approved").await;
+    assert_eq!(approved[0]["status"], "approved");
+    assert_eq!(public_ids(&app, &post).await, [held["id"].clone()]);
+
+    // Once one comment is approved the reader is no longer new.
+    let next = comment(&app, &reader, &post).await;
+    assert_eq!(next["status"], "approved");
+}
+
+#[tokio::test]
+async fn trusted_users_skip_the_queue_unless_the_post_says_otherwise() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&editor).await;
+
+    assert_eq!(comment(&app, &author, &post).await["status"], "approved");
+
+    app.put(&moderation_uri(&post))
+        .authenticated_as(&editor)
+        .json(json!({ "auto_approve_trusted": false }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let second_author = app.user(Role::Author).await;
+    assert_eq!(
+        comment(&app, &second_author, &post).await["status"],
+        "pending"
+    );
+    // Moderators are never held.
+    assert_eq!(comment(&app, &editor, &post).await["status"], "approved");
+}
+
+#[tokio::test]
+async fn posts_override_the_global_settings() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&editor).await;
+    app.comment_by(&reader, &app.post_by(&editor).await).await;
+
+    let response = app
+        .get(&moderation_uri(&post))
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["post_id"], json!(post.id));
+    assert_eq!(response.body["require_approval"], Value::Null);
+    assert_eq!(
+        response.body["effective"],
+        json!({
+            "require_approval": false,
+            "auto_approve_trusted": true,
+            "hold_first_time": true,
+        })
+    );
+    assert_eq!(comment(&app, &reader, &post).await["status"], "approved");
+
+    let response = app
+        .put(&moderation_uri(&post))
+        .authenticated_as(&editor)
+        .json(json!({ "require_approval": true, "hold_first_time": false }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["require_approval"], true);
+    assert_eq!(response.body["effective"]["require_approval"], true);
+    assert_eq!(response.body["effective"]["hold_first_time"], false);
+    assert_eq!(comment(&app, &reader, &post).await["status"], "pending");
+
+    // Leaving a setting out goes back to the global one.
+    let response = app
+        .put(&moderation_uri(&post))
+        .authenticated_as(&editor)
+        .json(json!({}))
+        .send()
+        .await;
+    assert_eq!(response.body["require_approval"], Value::Null);
+    assert_eq!(response.body["effective"]["require_approval"], false);
+
+    app.get(&moderation_uri(&post))
+        .authenticated_as(&reader)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.put(&format!("/api/comments/post/{}/moderation", Uuid::new_v4()))
+        .authenticated_as(&editor)
+        .json(json!({ "require_approval": true }))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn moderators_work_through_the_queue_in_bulk() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_by(&editor).await;
+    let other = app.post_by(&editor).await;
+    let mut held = Vec::new();
+    for target in [&post, &post, &other] {
+        let reader = app.user(Role::Reader).await;
+        held.push(comment(&app, &reader, target).await);
+    }
+
+    let response = app
+        .get("/api/comments/queue")
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 3);
+    assert_eq!(response.body["data"][0]["id"], held[0]["id"]);
+    let response = app
+        .get(&format!("/api/comments/queue?post_id={}", other.id))
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], held[2]["id"]);
+
+    let rejected = moderate(&app, &editor, &[&held[0], &held[1]], "rejected").await;
+    assert_eq!(rejected.as_array().unwrap().len(), 2);
+    moderate(&app, &editor, &[&held[2]], "spam").await;
+    let unknown = json!({ "id": Uuid::new_v4() });
+    assert_eq!(
+        moderate(&app, &editor, &[&unknown], "approved").await,
+        json!([])
+    );
+
+    let response = app
+        .get("/api/comments/queue")
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    assert_eq!(response.body["meta"]["total"], 0);
+    let response = app
+        .get("/api/comments/queue?status=spam")
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    assert_eq!(response.body["data"][0]["id"], held[2]["id"]);
+    assert!(public_ids(&app, &post).await.is_empty());
+}
+
+#[tokio::test]
+async fn only_moderators_see_and_act_on_the_queue() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    app.get("/api/comments/queue")
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.get("/api/comments/queue")
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.post("/api/comments/queue/moderate")
+        .authenticated_as(&author)
+        .json(json!({ "ids": [Uuid::new_v4()], "status": "approved" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+
+    let editor = app.user(Role::Editor).await;
+    app.post("/api/comments/queue/moderate")
+        .authenticated_as(&editor)
+        .json(json!({ "ids": [], "status": "approved" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "ids",
+            "length",
+        );
+}
+
+#[tokio::test]
+async fn held_replies_are_not_counted_or_shown_through_a_tombstone() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&editor).await;
+    let parent = comment(&app, &editor, &post).await;
+    let uri = format!("/api/comments/{}", parent["id"].as_str().unwrap());
+
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(&reader)
+        .json(json!({ "content": "Reply", "post_id": post.id, "parent_id": parent["id"] }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["status"], "pending");
+    let held = response.body.clone();
+    assert_eq!(app.get(&uri).send().await.body["reply_count"], 0);
+
+    // The tombstone stays for the held reply, but there is nothing to show.
+    app.delete(&uri)
+        .authenticated_as(&editor)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.get(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    assert!(public_ids(&app, &post).await.is_empty());
+    let thread = app
+        .get(&format!("/api/comments/post/{}/thread", post.id))
+        .send()
+        .await;
+    assert_eq!(thread.body["data"], json!([]));
+
+    moderate(&app, &editor, &[&held], "approved").await;
+    let response = app.get(&uri).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["reply_count"], 1);
+    assert_eq!(response.body["author_id"], Value::Null);
+    let thread = app
+        .get(&format!("/api/comments/post/{}/thread", post.id))
+        .send()
+        .await;
+    assert_eq!(thread.body["data"][0]["id"], parent["id"]);
+    assert_eq!(thread.body["data"][0]["replies"][0]["id"], held["id"]);
+}
+
+#[tokio::test]
+async fn edits_go_back_to_the_queue_when_the_post_holds_comments() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&editor).await;
+    let approved = app.comment_by(&reader, &post).await;
+    let own = comment(&app, &editor, &post).await;
+    app.put(&moderation_uri(&post))
+        .authenticated_as(&editor)
+        .json(json!({ "require_approval": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .put(&format!("/api/comments/{}", approved.id))
+        .authenticated_as(&reader)
+        .json(json!({ "content": "Edited" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["status"], "pending");
+    assert_eq!(public_ids(&app, &post).await, vec![own["id"].clone()]);
+
+    // Moderators are trusted with their edits, and with other people's.
+    for id in [
+        own["id"].as_str().unwrap().to_string(),
+        approved.id.to_string(),
+    ] {
+        let response = app
+            .put(&format!("/api/comments/{}", id))
+            .authenticated_as(&editor)
+            .json(json!({ "content": "Moderated" }))
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.body["content"], "Moderated");
+    }
+    let response = app
+        .get(&format!("/api/comments/{}", own["id"].as_str().unwrap()))
+        .send()
+        .await;
+    assert_eq!(response.body["status"], "approved");
+    let response = app
+        .get(&format!("/api/comments/{}", approved.id))
+        .authenticated_as(&reader)
+        .send()
+        .await;
+    assert_eq!(response.body["status"], "pending");
+}
@@ -0,0 +1,963 @@
+use axum::http::StatusCode;
+use blog::{
+    application::dto::post_dto::PostResponse,
+    domain::{
+        models::{
+            auth::Actor,
+            pagination::{Page, PageRequest},
+            post::{
+                ChangePostStatus, CreatePost, Post, PostQuery, PostStatus, TaggedPost, UpdatePost,
+            },
+            post_revision::{PostRevision, RevisionDiff},
+            user::Role,
+        },
+        repositories::PostRepository,
+        services::post_service::PostService,
+    },
+    infrastructure::repositories::in_memory::{
+        InMemoryStore, post_repository::InMemoryPostRepository,
+    },
+    shared::error::ApiError,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::support::{Services, TestApp, test_config};
+
+#[tokio::test]
+async fn list_posts_is_public_and_paginated() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    for _ in 0..3 {
+        app.post_by(&author).await;
+    }
+
+    let response = app.get("/api/posts?limit=2").send().await;
+    response.assert_status(StatusCode::OK);
+    let body = &response.body;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["meta"]["total"], 3);
+    assert_eq!(body["meta"]["total_pages"], 2);
+    assert_eq!(body["links"]["next"], "/api/posts?limit=2&page=2");
+    assert_eq!(body["links"]["prev"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn list_posts_filters_by_author() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let mine = app.post_by(&author).await;
+    app.post_by(&other).await;
+
+    let response = app
+        .get(&format!("/api/posts?author_id={}", author.id()))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], mine.id.to_string());
+}
+
+#[tokio::test]
+async fn list_posts_rejects_out_of_range_parameters() {
+    let app = TestApp::new();
+
+    app.get("/api/posts?limit=0")
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "limit",
+            "range",
+        );
+}
+
+#[tokio::test]
+async fn list_posts_rejects_pages_too_deep_to_offset() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    app.post_by(&author).await;
+
+    for uri in [
+        "/api/posts?page=9223372036854775807",
+        "/api/users?page=1000001",
+    ] {
+        app.get(uri)
+            .authenticated_as(&author)
+            .send()
+            .await
+            .assert_field_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                "page",
+                "range",
+            );
+    }
+
+    let response = app.get("/api/posts?page=1000000&limit=100").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["data"], json!([]));
+    assert_eq!(response.body["meta"]["total"], 1);
+}
+
+#[tokio::test]
+async fn get_post_returns_the_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    app.post_by(&author).await;
+    let post = app.post_by(&author).await;
+
+    let response = app.get(&format!("/api/posts/{}", post.id)).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["id"], post.id.to_string());
+    assert_eq!(response.body["title"], post.title);
+    assert_eq!(response.body["author_id"], author.id().to_string());
+}
+
+#[tokio::test]
+async fn get_missing_post_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app
+        .get(&format!("/api/posts/{}", Uuid::new_v4()))
+        .send()
+        .await;
+    let body = response.assert_problem(StatusCode::NOT_FOUND, "not_found");
+    assert_eq!(body["title"], "Resource not found");
+}
+
+#[tokio::test]
+async fn get_post_with_malformed_id_is_rejected() {
+    let app = TestApp::new();
+
+    app.get("/api/posts/not-a-uuid")
+        .send()
+        .await
+        .assert_field_error(StatusCode::BAD_REQUEST, "validation_failed", "id", "type");
+}
+
+#[tokio::test]
+async fn malformed_queries_and_bodies_are_reported_per_field() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    app.get("/api/posts?page=abc")
+        .send()
+        .await
+        .assert_field_error(StatusCode::BAD_REQUEST, "validation_failed", "page", "type");
+    app.post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": 42, "content": "Body" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "title",
+            "type",
+        );
+    app.post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "content": "Body" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "title",
+            "required",
+        );
+    app.get(&format!("/api/posts/{}/revisions/first", Uuid::new_v4()))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_field_error(StatusCode::BAD_REQUEST, "validation_failed", "rev", "type");
+}
+
+#[tokio::test]
+async fn create_post_belongs_to_the_caller() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": "Hello", "content": "World" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["title"], "Hello");
+    assert_eq!(response.body["author_id"], author.id().to_string());
+    assert_eq!(response.body["status"], "draft");
+    assert_eq!(response.body["published_at"], serde_json::Value::Null);
+
+    let created: PostResponse = response.json();
+    let fetched: PostResponse = app
+        .get(&format!("/api/posts/{}", created.id))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(fetched.created_at, created.created_at);
+}
+
+#[tokio::test]
+async fn create_post_requires_authentication() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/posts")
+        .json(json!({ "title": "Hello", "content": "World" }))
+        .send()
+        .await;
+    response.assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    assert_eq!(response.header("www-authenticate"), Some("Bearer"));
+
+    app.post("/api/posts")
+        .bearer("not-a-token")
+        .json(json!({ "title": "Hello", "content": "World" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+}
+
+#[tokio::test]
+async fn readers_cannot_create_posts() {
+    let app = TestApp::new();
+    let reader = app.user(Role::Reader).await;
+
+    app.post("/api/posts")
+        .authenticated_as(&reader)
+        .json(json!({ "title": "Hello", "content": "World" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+}
+
+#[tokio::test]
+async fn create_post_validates_fields() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": "", "content": "World" }))
+        .send()
+        .await;
+    response.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "title",
+        "length",
+    );
+    assert_eq!(response.body["detail"], "Invalid value for: title.");
+}
+
+#[tokio::test]
+async fn update_post_applies_changes() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "title": "Renamed" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["title"], "Renamed");
+    assert_eq!(response.body["content"], post.content);
+    assert_eq!(response.body["status"], "published");
+}
+
+#[tokio::test]
+async fn editors_may_update_any_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&editor)
+        .json(json!({ "content": "Edited" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["author_id"], author.id().to_string());
+}
+
+#[tokio::test]
+async fn other_authors_cannot_update_a_post() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    app.put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&other)
+        .json(json!({ "title": "Mine now" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+}
+
+#[tokio::test]
+async fn update_post_reports_missing_and_invalid_input() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    app.put(&format!("/api/posts/{}", Uuid::new_v4()))
+        .authenticated_as(&author)
+        .json(json!({ "title": "Ghost" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+
+    app.put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "title": "x".repeat(101) }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "title",
+            "length",
+        );
+}
+
+#[tokio::test]
+async fn delete_post_removes_it() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let uri = format!("/api/posts/{}", post.id);
+
+    let response = app.delete(&uri).authenticated_as(&author).send().await;
+    response.assert_status(StatusCode::NO_CONTENT);
+    assert!(response.body.is_null());
+
+    app.get(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&uri)
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn delete_post_is_restricted_to_owner_and_editors() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let uri = format!("/api/posts/{}", post.id);
+
+    app.delete(&uri)
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.delete(&uri)
+        .authenticated_as(&other)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+}
+
+#[tokio::test]
+async fn create_post_derives_a_transliterated_slug() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": "Crème Brûlée: Ещё раз!", "content": "Dessert" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["slug"], "creme-brulee-eshche-raz");
+
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": "¿¡…!?", "content": "Nothing to slug" }))
+        .send()
+        .await;
+    assert_eq!(response.body["slug"], "post");
+}
+
+#[tokio::test]
+async fn repeated_titles_get_numbered_slugs() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let mut slugs = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .post("/api/posts")
+            .authenticated_as(&author)
+            .json(json!({ "title": "Hello, World", "content": "Again" }))
+            .send()
+            .await;
+        slugs.push(response.body["slug"].as_str().unwrap().to_string());
+    }
+    assert_eq!(slugs, ["hello-world", "hello-world-2", "hello-world-3"]);
+}
+
+#[tokio::test]
+async fn concurrent_posts_with_one_title_all_get_a_slug() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+
+    let responses = futures::future::join_all((0..4).map(|_| {
+        app.post("/api/posts")
+            .authenticated_as(&author)
+            .json(json!({ "title": "Breaking", "content": "News" }))
+            .send()
+    }))
+    .await;
+
+    let mut slugs: Vec<String> = responses
+        .iter()
+        .map(|response| {
+            response.assert_status(StatusCode::CREATED);
+            response.body["slug"].as_str().unwrap().to_string()
+        })
+        .collect();
+    slugs.sort();
+    assert_eq!(
+        slugs,
+        ["breaking", "breaking-2", "breaking-3", "breaking-4"]
+    );
+}
+
+#[tokio::test]
+async fn get_post_by_slug() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .get(&format!("/api/posts/by-slug/{}", post.slug))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["id"], post.id.to_string());
+
+    app.get("/api/posts/by-slug/no-such-post")
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn renamed_posts_redirect_from_their_old_slug() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "title": "A Better Title" }))
+        .send()
+        .await;
+    assert_eq!(response.body["slug"], "a-better-title");
+
+    let response = app
+        .get(&format!("/api/posts/by-slug/{}", post.slug))
+        .send()
+        .await;
+    response.assert_status(StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(
+        response.header("location"),
+        Some("/api/posts/by-slug/a-better-title")
+    );
+
+    // The old slug stays reserved for the redirect.
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(&author)
+        .json(json!({ "title": post.title, "content": "Copycat" }))
+        .send()
+        .await;
+    assert_eq!(response.body["slug"], format!("{}-2", post.slug));
+}
+
+#[tokio::test]
+async fn editing_without_renaming_keeps_the_slug() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+
+    let response = app
+        .put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "title": post.title, "content": "Revised" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["slug"], post.slug);
+}
+
+#[tokio::test]
+async fn unpublished_posts_are_hidden_from_the_public() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let published = app.post_by(&author).await;
+    let mut hidden = Vec::new();
+    for status in [
+        PostStatus::Draft,
+        PostStatus::InReview,
+        PostStatus::Scheduled,
+        PostStatus::Archived,
+    ] {
+        hidden.push(app.post_in(&author, status).await);
+    }
+
+    let response = app.get("/api/posts").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], published.id.to_string());
+
+    for post in &hidden {
+        let uri = format!("/api/posts/{}", post.id);
+        app.get(&uri)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.get(&uri)
+            .authenticated_as(&other)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.get(&format!("/api/posts/by-slug/{}", post.slug))
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.get(&uri)
+            .authenticated_as(&author)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+        app.get(&uri)
+            .authenticated_as(&editor)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn writes_to_posts_the_caller_cannot_see_are_not_found() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+
+    for status in [
+        PostStatus::Draft,
+        PostStatus::InReview,
+        PostStatus::Scheduled,
+    ] {
+        let post = app.post_in(&author, status).await;
+        let uri = format!("/api/posts/{}", post.id);
+        app.put(&uri)
+            .authenticated_as(&other)
+            .json(json!({ "title": "Mine now" }))
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.put(&format!("{}/status", uri))
+            .authenticated_as(&other)
+            .json(json!({ "status": "archived" }))
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        for revisions in [format!("{}/revisions", uri), format!("{}/revisions/1", uri)] {
+            app.get(&revisions)
+                .authenticated_as(&other)
+                .send()
+                .await
+                .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        }
+        app.post(&format!("{}/revisions/1/restore", uri))
+            .authenticated_as(&other)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+        app.delete(&uri)
+            .authenticated_as(&other)
+            .send()
+            .await
+            .assert_problem(StatusCode::NOT_FOUND, "not_found");
+
+        let response = app.get(&uri).authenticated_as(&author).send().await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.body["title"], post.title);
+    }
+}
+
+#[tokio::test]
+async fn listing_other_statuses_is_scoped_to_the_caller() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let mine = app.post_in(&author, PostStatus::Draft).await;
+    app.post_in(&other, PostStatus::Draft).await;
+
+    app.get("/api/posts?status=draft")
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+
+    let response = app
+        .get("/api/posts?status=draft")
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], mine.id.to_string());
+
+    app.get(&format!("/api/posts?status=draft&author_id={}", other.id()))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+
+    let response = app
+        .get("/api/posts?status=draft")
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    assert_eq!(response.body["meta"]["total"], 2);
+}
+
+#[tokio::test]
+async fn authors_submit_and_editors_publish() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_in(&author, PostStatus::Draft).await;
+    let uri = format!("/api/posts/{}/status", post.id);
+
+    let response = app
+        .put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "status": "in_review" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["status"], "in_review");
+
+    app.put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "status": "published" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+
+    let response = app
+        .put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "status": "published" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["status"], "published");
+    assert!(response.body["published_at"].is_string());
+
+    app.get(&format!("/api/posts/{}", post.id))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // Authors may still take their own post down.
+    let response = app
+        .put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "status": "archived" }))
+        .send()
+        .await;
+    assert_eq!(response.body["status"], "archived");
+    assert!(response.body["published_at"].is_string());
+}
+
+#[tokio::test]
+async fn illegal_transitions_are_rejected() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_in(&author, PostStatus::Draft).await;
+
+    let response = app
+        .put(&format!("/api/posts/{}/status", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "status": "archived" }))
+        .send()
+        .await;
+    let body = response.assert_problem(StatusCode::CONFLICT, "invalid_transition");
+    assert_eq!(
+        body["detail"],
+        "A resource in state draft cannot move to archived."
+    );
+
+    app.put(&format!("/api/posts/{}/status", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "status": "live" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "status",
+            "type",
+        );
+}
+
+#[tokio::test]
+async fn scheduling_requires_a_future_publish_at() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_in(&author, PostStatus::InReview).await;
+    let uri = format!("/api/posts/{}/status", post.id);
+    let now = chrono::Local::now().naive_local();
+
+    app.put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "status": "scheduled" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "publish_at",
+            "required",
+        );
+    app.put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "status": "scheduled", "publish_at": now - chrono::Duration::minutes(1) }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "publish_at",
+            "future",
+        );
+    app.put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "status": "published", "publish_at": now }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "publish_at",
+            "scheduled_only",
+        );
+
+    let publish_at = now + chrono::Duration::days(1);
+    let response = app
+        .put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "status": "scheduled", "publish_at": publish_at }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let scheduled: PostResponse = response.json();
+    assert_eq!(scheduled.publish_at, Some(publish_at));
+    assert_eq!(scheduled.published_at, None);
+
+    // Unscheduling clears the date.
+    let response = app
+        .put(&uri)
+        .authenticated_as(&author)
+        .json(json!({ "status": "draft" }))
+        .send()
+        .await;
+    assert_eq!(response.body["publish_at"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn due_scheduled_posts_are_published() {
+    let config = test_config();
+    let store = InMemoryStore::new();
+    let services = Services::in_memory(&store, &config);
+    let posts = services.posts.clone();
+    let repository = InMemoryPostRepository::new(store.clone());
+    let app = TestApp::with_services(store, services, &config);
+    let author = app.user(Role::Author).await;
+    let mut due = app.post_in(&author, PostStatus::Scheduled).await;
+    let not_due = app.post_in(&author, PostStatus::Scheduled).await;
+    due.publish_at = Some(chrono::Local::now().naive_local() - chrono::Duration::seconds(1));
+    let due = repository.update(due.id, due).await.unwrap();
+
+    let published = posts.publish_due_posts().await.unwrap();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].id, due.id);
+
+    let response = app.get("/api/posts").send().await;
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], due.id.to_string());
+    assert_eq!(response.body["data"][0]["status"], "published");
+    assert_eq!(
+        response.body["data"][0]["published_at"],
+        json!(due.publish_at)
+    );
+    app.get(&format!("/api/posts/{}", not_due.id))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+/// A post service whose storage is always down.
+#[derive(Clone)]
+struct BrokenPostService;
+
+#[async_trait::async_trait]
+impl PostService for BrokenPostService {
+    async fn get_post(&self, _actor: Option<&Actor>, _id: Uuid) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::DatabaseError("connection refused".to_string()))
+    }
+
+    async fn get_post_by_slug(
+        &self,
+        _actor: Option<&Actor>,
+        _slug: &str,
+    ) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn get_posts(
+        &self,
+        _actor: Option<&Actor>,
+        _query: PostQuery,
+    ) -> Result<Page<TaggedPost>, ApiError> {
+        Err(ApiError::ServiceUnavailable)
+    }
+
+    async fn create_post(&self, _actor: &Actor, _post: CreatePost) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn update_post(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _post: UpdatePost,
+    ) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn change_status(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _change: ChangePostStatus,
+    ) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn delete_post(&self, _actor: &Actor, _id: Uuid) -> Result<(), ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn publish_due_posts(&self) -> Result<Vec<Post>, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn get_revisions(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _page: PageRequest,
+    ) -> Result<Page<PostRevision>, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn get_revision(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _revision: i32,
+    ) -> Result<PostRevision, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn diff_revisions(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _from: i32,
+        _to: i32,
+    ) -> Result<RevisionDiff, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+
+    async fn restore_revision(
+        &self,
+        _actor: &Actor,
+        _id: Uuid,
+        _revision: i32,
+    ) -> Result<TaggedPost, ApiError> {
+        Err(ApiError::InternalServerError)
+    }
+}
+
+#[tokio::test]
+async fn service_failures_are_reported_without_internals() {
+    let config = test_config();
+    let store = InMemoryStore::new();
+    let Services {
+        comments,
+        taxonomy,
+        search,
+        users,
+        auth,
+        ..
+    } = Services::in_memory(&store, &config);
+    let app = TestApp::with_services(
+        store,
+        Services {
+            comments,
+            posts: BrokenPostService,
+            taxonomy,
+            search,
+            users,
+            auth,
+        },
+        &config,
+    );
+
+    let response = app
+        .get(&format!("/api/posts/{}", Uuid::new_v4()))
+        .header("x-request-id", "trace-me")
+        .send()
+        .await;
+    let body = response.assert_problem(StatusCode::INTERNAL_SERVER_ERROR, "database_error");
+    assert_eq!(body["request_id"], "trace-me");
+    assert_eq!(
+        body["detail"],
+        "The server encountered an unexpected error."
+    );
+    assert!(!body.to_string().contains("connection refused"));
+
+    let response = app.get("/api/posts").send().await;
+    response.assert_problem(StatusCode::SERVICE_UNAVAILABLE, "service_unavailable");
+    assert_eq!(response.header("retry-after"), Some("1"));
+}
@@ -0,0 +1,239 @@
+use axum::http::StatusCode;
+use blog::domain::models::{post::PostStatus, user::Role};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::support::{TestApp, TestUser};
+
+/// Creates a post through the API so its history starts the way it does in
+/// production, then applies each of `edits` as an update.
+async fn edited_post(app: &TestApp, author: &TestUser, edits: &[Value]) -> Uuid {
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(author)
+        .json(json!({ "title": "Draft", "content": "one\ntwo\nthree" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let id: Uuid = response.body["id"].as_str().unwrap().parse().unwrap();
+
+    for edit in edits {
+        app.put(&format!("/api/posts/{}", id))
+            .authenticated_as(author)
+            .json(edit.clone())
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    id
+}
+
+#[tokio::test]
+async fn every_change_is_recorded_newest_first() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let id = edited_post(&app, &author, &[json!({ "content": "one\n2\nthree" })]).await;
+    app.put(&format!("/api/posts/{}/status", id))
+        .authenticated_as(&editor)
+        .json(json!({ "status": "published" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .get(&format!("/api/posts/{}/revisions", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    let revisions = response.body["data"].as_array().unwrap();
+    let numbers: Vec<_> = revisions.iter().map(|r| r["revision"].clone()).collect();
+    assert_eq!(numbers, [json!(3), json!(2), json!(1)]);
+    assert_eq!(revisions[0]["status"], "published");
+    assert_eq!(revisions[0]["editor_id"], editor.id().to_string());
+    assert_eq!(revisions[1]["content"], "one\n2\nthree");
+    assert_eq!(revisions[2]["content"], "one\ntwo\nthree");
+    assert_eq!(revisions[2]["editor_id"], author.id().to_string());
+
+    let response = app
+        .get(&format!("/api/posts/{}/revisions/1", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["status"], "draft");
+}
+
+#[tokio::test]
+async fn diff_compares_lines_between_revisions() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let id = edited_post(
+        &app,
+        &author,
+        &[
+            json!({ "content": "one\n2\nthree" }),
+            json!({ "title": "Final", "content": "one\n2\nthree\nfour" }),
+        ],
+    )
+    .await;
+
+    let response = app
+        .get(&format!("/api/posts/{}/revisions/2/diff", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["from"], 1);
+    assert_eq!(response.body["to"], 2);
+    assert_eq!(
+        response.body["title"],
+        json!([{ "op": "equal", "text": "Draft" }])
+    );
+    assert_eq!(response.body["status"], Value::Null);
+    assert_eq!(
+        response.body["content"],
+        json!([{
+            "old_start": 1,
+            "old_lines": 3,
+            "new_start": 1,
+            "new_lines": 3,
+            "lines": [
+                { "op": "equal", "text": "one" },
+                { "op": "delete", "text": "two" },
+                { "op": "insert", "text": "2" },
+                { "op": "equal", "text": "three" },
+            ],
+        }])
+    );
+
+    let response = app
+        .get(&format!("/api/posts/{}/revisions/3/diff?from=1", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    assert_eq!(
+        response.body["title"],
+        json!([
+            { "op": "delete", "text": "Draft" },
+            { "op": "insert", "text": "Final" },
+        ])
+    );
+    let lines = &response.body["content"][0]["lines"];
+    assert_eq!(lines.as_array().unwrap().len(), 5);
+    assert_eq!(lines[4], json!({ "op": "insert", "text": "four" }));
+
+    app.get(&format!("/api/posts/{}/revisions/1/diff", id))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+    app.get(&format!("/api/posts/{}/revisions/9/diff", id))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn restore_brings_back_an_old_revision_as_a_new_one() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let id = edited_post(
+        &app,
+        &author,
+        &[json!({ "title": "Oops", "content": "gone" })],
+    )
+    .await;
+
+    let response = app
+        .post(&format!("/api/posts/{}/revisions/1/restore", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["title"], "Draft");
+    assert_eq!(response.body["content"], "one\ntwo\nthree");
+    // The original slug was kept in history, so the post takes it back.
+    assert_eq!(response.body["slug"], "draft");
+
+    let response = app
+        .get(&format!("/api/posts/{}/revisions", id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    assert_eq!(response.body["meta"]["total"], 3);
+    assert_eq!(response.body["data"][0]["title"], "Draft");
+    assert_eq!(response.body["data"][1]["title"], "Oops");
+}
+
+#[tokio::test]
+async fn restore_leaves_the_status_alone() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_in(&author, PostStatus::Published).await;
+    app.put(&format!("/api/posts/{}", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "content": "Changed" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.put(&format!("/api/posts/{}/status", post.id))
+        .authenticated_as(&author)
+        .json(json!({ "status": "archived" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app
+        .post(&format!("/api/posts/{}/revisions/1/restore", post.id))
+        .authenticated_as(&author)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["content"], post.content);
+    assert_eq!(response.body["status"], "archived");
+}
+
+#[tokio::test]
+async fn history_is_limited_to_those_who_may_edit() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+    let post = app.post_by(&author).await;
+    let revisions = format!("/api/posts/{}/revisions", post.id);
+    let restore = format!("/api/posts/{}/revisions/1/restore", post.id);
+
+    app.get(&revisions)
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.get(&revisions)
+        .authenticated_as(&other)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.post(&restore)
+        .authenticated_as(&other)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.get(&revisions)
+        .authenticated_as(&editor)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.post(&format!("/api/posts/{}/revisions/7/restore", post.id))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.get(&format!("/api/posts/{}/revisions", Uuid::new_v4()))
+        .authenticated_as(&editor)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
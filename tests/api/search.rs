@@ -0,0 +1,226 @@
+use axum::http::StatusCode;
+use blog::domain::models::user::Role;
+use serde_json::{Value, json};
+
+use crate::support::{TestApp, TestUser};
+
+/// Creates a post with `body` and publishes it unless `publish` is false.
+async fn post_with(app: &TestApp, editor: &TestUser, body: Value, publish: bool) -> Value {
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(editor)
+        .json(body)
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    if !publish {
+        return response.body.clone();
+    }
+
+    let response = app
+        .put(&format!(
+            "/api/posts/{}/status",
+            response.body["id"].as_str().unwrap()
+        ))
+        .authenticated_as(editor)
+        .json(json!({ "status": "published" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.body.clone()
+}
+
+async fn published(app: &TestApp, editor: &TestUser, title: &str, content: &str) -> Value {
+    post_with(
+        app,
+        editor,
+        json!({ "title": title, "content": content }),
+        true,
+    )
+    .await
+}
+
+async fn search(app: &TestApp, query: &str) -> Value {
+    let response = app.get(&format!("/api/search?{}", query)).send().await;
+    response.assert_status(StatusCode::OK);
+    response.body.clone()
+}
+
+fn titles(response: &Value) -> Vec<String> {
+    response["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["post_title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn phrases_prefixes_alternatives_and_exclusions() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    published(&app, &editor, "Async", "Async Rust with tokio.").await;
+    published(&app, &editor, "Order", "Rust is not async by default.").await;
+    published(&app, &editor, "Go", "Goroutines are not async either.").await;
+
+    let mut found = titles(&search(&app, "q=%22async+rust%22").await);
+    assert_eq!(found, ["Async"]);
+
+    found = titles(&search(&app, "q=gorout*").await);
+    assert_eq!(found, ["Go"]);
+
+    found = titles(&search(&app, "q=tokio+or+goroutines").await);
+    found.sort();
+    assert_eq!(found, ["Async", "Go"]);
+
+    found = titles(&search(&app, "q=async+-rust").await);
+    assert_eq!(found, ["Go"]);
+}
+
+#[tokio::test]
+async fn title_matches_rank_above_body_matches() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    published(&app, &editor, "Cooking", "A note on ownership.").await;
+    published(&app, &editor, "Ownership", "Moves and borrows.").await;
+
+    let response = search(&app, "q=ownership").await;
+    assert_eq!(titles(&response), ["Ownership", "Cooking"]);
+    assert_eq!(response["meta"]["total"], 2);
+    assert!(response["data"][0]["rank"].as_f64() > response["data"][1]["rank"].as_f64());
+}
+
+#[tokio::test]
+async fn snippets_highlight_matches_and_escape_the_rest() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let post = published(&app, &editor, "Tips", "Use <Vec> & borrow the vec wisely.").await;
+
+    let response = search(&app, "q=vec").await;
+    let hit = &response["data"][0];
+    assert_eq!(hit["id"], post["id"]);
+    assert_eq!(hit["post_id"], post["id"]);
+    assert_eq!(hit["post_slug"], "tips");
+    let snippet = hit["snippet"].as_str().unwrap();
+    assert!(snippet.contains("<mark>"), "{}", snippet);
+    assert!(!snippet.contains("<Vec>"), "{}", snippet);
+    assert!(snippet.contains("&amp;"), "{}", snippet);
+}
+
+#[tokio::test]
+async fn only_published_posts_are_found() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    published(&app, &editor, "Live", "Searchable words.").await;
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Draft", "content": "Searchable words." }),
+        false,
+    )
+    .await;
+
+    let response = app
+        .get("/api/search?q=searchable")
+        .authenticated_as(&editor)
+        .send()
+        .await;
+    assert_eq!(titles(&response.body), ["Live"]);
+}
+
+#[tokio::test]
+async fn results_filter_by_author_tag_and_date() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let other = app.user(Role::Editor).await;
+    app.post("/api/tags")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "Rust" }))
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Tagged", "content": "Lifetimes.", "tags": ["rust"] }),
+        true,
+    )
+    .await;
+    published(&app, &other, "Other", "Lifetimes.").await;
+
+    let found = titles(&search(&app, &format!("q=lifetimes&author_id={}", other.id())).await);
+    assert_eq!(found, ["Other"]);
+    let found = titles(&search(&app, "q=lifetimes&tag=rust").await);
+    assert_eq!(found, ["Tagged"]);
+    let response = search(&app, "q=lifetimes&tag=missing").await;
+    assert_eq!(response["meta"]["total"], 0);
+    let response = search(&app, "q=lifetimes&created_after=2999-01-01T00:00:00").await;
+    assert_eq!(response["meta"]["total"], 0);
+    let response = search(&app, "q=lifetimes&created_before=2999-01-01T00:00:00").await;
+    assert_eq!(response["meta"]["total"], 2);
+}
+
+#[tokio::test]
+async fn comments_on_published_posts_are_searchable() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let post = published(&app, &editor, "Thread", "Nothing here.").await;
+    let archived = published(&app, &editor, "Hidden", "Nothing here.").await;
+    let mut comments = Vec::new();
+    for target in [&post, &archived] {
+        let response = app
+            .post("/api/comments")
+            .authenticated_as(&editor)
+            .json(json!({ "content": "Great pangolin facts!", "post_id": target["id"] }))
+            .send()
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        comments.push(response.body.clone());
+    }
+    app.put(&format!(
+        "/api/posts/{}/status",
+        archived["id"].as_str().unwrap()
+    ))
+    .authenticated_as(&editor)
+    .json(json!({ "status": "archived" }))
+    .send()
+    .await
+    .assert_status(StatusCode::OK);
+
+    assert_eq!(search(&app, "q=pangolin").await["meta"]["total"], 0);
+    let response = search(&app, "q=pangolin&scope=comments").await;
+    assert_eq!(response["meta"]["total"], 1);
+    let hit = &response["data"][0];
+    assert_eq!(hit["id"], comments[0]["id"]);
+    assert_eq!(hit["post_id"], post["id"]);
+    assert_eq!(hit["post_title"], "Thread");
+    assert!(
+        hit["snippet"]
+            .as_str()
+            .unwrap()
+            .contains("<mark>pangolin</mark>")
+    );
+}
+
+#[tokio::test]
+async fn queries_need_a_word_to_look_for() {
+    let app = TestApp::new();
+
+    app.get("/api/search?q=").send().await.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "q",
+        "length",
+    );
+    for query in ["-excluded", "%22%22", "%2A%21"] {
+        app.get(&format!("/api/search?q={}", query))
+            .send()
+            .await
+            .assert_field_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                "q",
+                "searchable",
+            );
+    }
+}
@@ -0,0 +1,465 @@
+//! In-process test harness for the HTTP API.
+//!
+//! [`TestApp`] mounts `create_routes` under `/api` exactly as `main` does and
+//! drives it with `tower::ServiceExt::oneshot`, so no socket is opened. By
+//! default the real services run on an [`InMemoryStore`]; any of them can be
+//! swapped for a stub through [`Services`] and [`TestApp::with_services`].
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{HeaderMap, Method, Request, StatusCode, header},
+    middleware,
+};
+use blog::{
+    application::routes::create_routes,
+    domain::{
+        models::{
+            auth::TokenType,
+            comment::{Comment, CommentStatus, ModerationSettings},
+            post::{Post, PostStatus},
+            slug::slugify,
+            user::Role,
+            user::User,
+        },
+        repositories::{CommentRepository, PostRepository, PostRevisionRepository, UserRepository},
+        services::{
+            auth_service::{AuthConfig, AuthService, AuthServiceImpl, TokenCodec},
+            comment_service::{CommentService, CommentServiceImpl},
+            post_service::{PostService, PostServiceImpl},
+            search_service::{SearchService, SearchServiceImpl},
+            taxonomy_service::{TaxonomyService, TaxonomyServiceImpl},
+            user_service::{UserService, UserServiceImpl},
+        },
+    },
+    infrastructure::repositories::in_memory::{
+        InMemoryStore, category_repository::InMemoryCategoryRepository,
+        comment_repository::InMemoryCommentRepository, post_repository::InMemoryPostRepository,
+        post_revision_repository::InMemoryPostRevisionRepository,
+        refresh_token_repository::InMemoryRefreshTokenRepository,
+        search_repository::InMemorySearchRepository, tag_repository::InMemoryTagRepository,
+        unit_of_work::InMemoryUnitOfWork, user_repository::InMemoryUserRepository,
+    },
+    shared::{config::Config, error::ApiError, request_id},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+pub const TEST_JWT_SECRET: &str = "test-secret";
+
+/// Password of every user created by [`TestApp::user`].
+pub const FIXTURE_PASSWORD: &str = "fixture-password";
+
+pub fn test_config() -> Config {
+    let mut config = Config::default();
+    config.auth.jwt_secret = TEST_JWT_SECRET.to_string();
+    config
+}
+
+pub struct TestApp {
+    router: Router,
+    tokens: TokenCodec,
+    users: InMemoryUserRepository,
+    posts: InMemoryPostRepository,
+    revisions: InMemoryPostRevisionRepository,
+    comments: InMemoryCommentRepository,
+    sequence: AtomicUsize,
+}
+
+pub type DefaultCommentService =
+    Arc<CommentServiceImpl<InMemoryCommentRepository, InMemoryPostRepository>>;
+pub type DefaultPostService = Arc<
+    PostServiceImpl<
+        InMemoryPostRepository,
+        InMemoryPostRevisionRepository,
+        InMemoryTagRepository,
+        InMemoryUnitOfWork,
+    >,
+>;
+pub type DefaultTaxonomyService =
+    Arc<TaxonomyServiceImpl<InMemoryTagRepository, InMemoryCategoryRepository, InMemoryUnitOfWork>>;
+pub type DefaultSearchService =
+    Arc<SearchServiceImpl<InMemorySearchRepository, InMemoryTagRepository>>;
+pub type DefaultUserService = Arc<UserServiceImpl<InMemoryUserRepository, InMemoryUnitOfWork>>;
+pub type DefaultAuthService =
+    Arc<AuthServiceImpl<InMemoryUserRepository, InMemoryRefreshTokenRepository>>;
+
+/// The services handed to `create_routes`. Start from [`Services::in_memory`]
+/// and move a stub into whichever field a test needs to control.
+pub struct Services<C, P, X, S, U, A> {
+    pub comments: C,
+    pub posts: P,
+    pub taxonomy: X,
+    pub search: S,
+    pub users: U,
+    pub auth: A,
+}
+
+impl
+    Services<
+        DefaultCommentService,
+        DefaultPostService,
+        DefaultTaxonomyService,
+        DefaultSearchService,
+        DefaultUserService,
+        DefaultAuthService,
+    >
+{
+    pub fn in_memory(store: &InMemoryStore, config: &Config) -> Self {
+        let users = Arc::new(InMemoryUserRepository::new(store.clone()));
+        let tags = Arc::new(InMemoryTagRepository::new(store.clone()));
+        let posts = Arc::new(InMemoryPostRepository::new(store.clone()));
+
+        Self {
+            comments: Arc::new(CommentServiceImpl::new(
+                Arc::new(InMemoryCommentRepository::new(store.clone())),
+                Arc::clone(&posts),
+                ModerationSettings::from(&config.moderation),
+            )),
+            posts: Arc::new(PostServiceImpl::new(
+                posts,
+                Arc::new(InMemoryPostRevisionRepository::new(store.clone())),
+                Arc::clone(&tags),
+                Arc::new(InMemoryUnitOfWork::new(store.clone())),
+            )),
+            taxonomy: Arc::new(TaxonomyServiceImpl::new(
+                Arc::clone(&tags),
+                Arc::new(InMemoryCategoryRepository::new(store.clone())),
+                Arc::new(InMemoryUnitOfWork::new(store.clone())),
+            )),
+            search: Arc::new(SearchServiceImpl::new(
+                Arc::new(InMemorySearchRepository::new(store.clone())),
+                tags,
+            )),
+            users: Arc::new(UserServiceImpl::new(
+                Arc::clone(&users),
+                Arc::new(InMemoryUnitOfWork::new(store.clone())),
+            )),
+            auth: Arc::new(AuthServiceImpl::new(
+                users,
+                Arc::new(InMemoryRefreshTokenRepository::new(store.clone())),
+                TokenCodec::new(&AuthConfig::from(&config.auth)),
+            )),
+        }
+    }
+}
+
+impl TestApp {
+    /// The real services over an empty in-memory store.
+    pub fn new() -> Self {
+        Self::with_config(test_config())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let store = InMemoryStore::new();
+        let services = Services::in_memory(&store, &config);
+        Self::with_services(store, services, &config)
+    }
+
+    /// Mounts `services`. Fixtures are written to `store`, so they are only
+    /// visible to services that read from it.
+    pub fn with_services<C, P, X, S, U, A>(
+        store: InMemoryStore,
+        services: Services<C, P, X, S, U, A>,
+        config: &Config,
+    ) -> Self
+    where
+        C: CommentService + Clone + Send + Sync + 'static,
+        P: PostService + Clone + Send + Sync + 'static,
+        X: TaxonomyService + Clone + Send + Sync + 'static,
+        S: SearchService + Clone + Send + Sync + 'static,
+        U: UserService + Clone + Send + Sync + 'static,
+        A: AuthService + Clone + Send + Sync + 'static,
+    {
+        let tokens = services.auth.token_codec();
+        let router = Router::new()
+            .nest(
+                "/api",
+                create_routes(
+                    services.comments,
+                    services.posts,
+                    services.taxonomy,
+                    services.search,
+                    services.users,
+                    services.auth,
+                    config,
+                ),
+            )
+            .fallback(|| async { ApiError::NotFound })
+            .layer(middleware::from_fn(request_id::propagate));
+
+        Self {
+            router,
+            tokens,
+            users: InMemoryUserRepository::new(store.clone()),
+            posts: InMemoryPostRepository::new(store.clone()),
+            revisions: InMemoryPostRevisionRepository::new(store.clone()),
+            comments: InMemoryCommentRepository::new(store),
+            sequence: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn get(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::POST, uri)
+    }
+
+    pub fn put(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, uri)
+    }
+
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest<'_> {
+        TestRequest {
+            app: self,
+            builder: Request::builder().method(method).uri(uri),
+            body: Body::empty(),
+        }
+    }
+
+    // Fixtures
+
+    fn next_name(&self, prefix: &str) -> String {
+        format!(
+            "{}{}",
+            prefix,
+            self.sequence.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// A stored user with the given role and an access token for them.
+    pub async fn user(&self, role: Role) -> TestUser {
+        let username = self.next_name(role.as_str());
+        let now = chrono::Local::now().naive_local();
+        let user = self
+            .users
+            .create(User {
+                id: Uuid::new_v4(),
+                email: format!("{}@example.com", username),
+                username,
+                password_hash: fixture_password_hash(),
+                created_at: now,
+                updated_at: now,
+                role,
+            })
+            .await
+            .unwrap();
+        let token = self
+            .tokens
+            .issue(&user, Uuid::new_v4(), TokenType::Access)
+            .unwrap();
+        TestUser { user, token }
+    }
+
+    /// A published post, visible to everyone.
+    pub async fn post_by(&self, author: &TestUser) -> Post {
+        self.post_in(author, PostStatus::Published).await
+    }
+
+    /// A post in `status`, with its first revision recorded. Scheduled posts
+    /// are due an hour from now.
+    pub async fn post_in(&self, author: &TestUser, status: PostStatus) -> Post {
+        let now = chrono::Local::now().naive_local();
+        let title = self.next_name("Post ");
+        let post = self
+            .posts
+            .create(Post {
+                id: Uuid::new_v4(),
+                slug: slugify(&title),
+                title,
+                content: "Some content".to_string(),
+                author_id: author.id(),
+                created_at: now,
+                updated_at: now,
+                status,
+                publish_at: (status == PostStatus::Scheduled)
+                    .then(|| now + chrono::Duration::hours(1)),
+                published_at: matches!(status, PostStatus::Published | PostStatus::Archived)
+                    .then_some(now),
+                category_id: None,
+                content_html: None,
+            })
+            .await
+            .unwrap();
+        self.revisions
+            .record(&post, Some(author.id()))
+            .await
+            .unwrap();
+        post
+    }
+
+    /// An approved comment.
+    pub async fn comment_by(&self, author: &TestUser, post: &Post) -> Comment {
+        let now = chrono::Local::now().naive_local();
+        self.comments
+            .create(Comment {
+                id: Uuid::new_v4(),
+                content: self.next_name("Comment "),
+                post_id: post.id,
+                author_id: author.id(),
+                created_at: now,
+                updated_at: now,
+                content_html: None,
+                parent_id: None,
+                reply_count: 0,
+                deleted_at: None,
+                status: CommentStatus::Approved,
+            })
+            .await
+            .unwrap()
+    }
+}
+
+// Hashing is deliberately slow, so every fixture user shares one hash.
+fn fixture_password_hash() -> String {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| {
+        User::new(
+            "fixture".to_string(),
+            "fixture@example.com".to_string(),
+            FIXTURE_PASSWORD.to_string(),
+        )
+        .unwrap()
+        .password_hash
+    })
+    .clone()
+}
+
+pub struct TestUser {
+    pub user: User,
+    pub token: String,
+}
+
+impl TestUser {
+    pub fn id(&self) -> Uuid {
+        self.user.id
+    }
+}
+
+pub struct TestRequest<'a> {
+    app: &'a TestApp,
+    builder: axum::http::request::Builder,
+    body: Body,
+}
+
+impl TestRequest<'_> {
+    pub fn authenticated_as(self, user: &TestUser) -> Self {
+        self.bearer(&user.token)
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.header(header::AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    pub fn json(mut self, body: Value) -> Self {
+        self.builder = self
+            .builder
+            .header(header::CONTENT_TYPE, "application/json");
+        self.body = Body::from(body.to_string());
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let request = self.builder.body(self.body).unwrap();
+        let response = self.app.router.clone().oneshot(request).await.unwrap();
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_value(self.body.clone()).unwrap()
+    }
+
+    #[track_caller]
+    pub fn assert_status(&self, expected: StatusCode) -> &Self {
+        assert_eq!(
+            self.status, expected,
+            "unexpected status; body: {}",
+            self.body
+        );
+        self
+    }
+
+    /// Asserts an RFC 7807 problem response and returns its body.
+    #[track_caller]
+    pub fn assert_problem(&self, status: StatusCode, code: &str) -> &Value {
+        self.assert_status(status);
+        assert_eq!(
+            self.header("content-type"),
+            Some("application/problem+json")
+        );
+        assert_eq!(self.body["status"], status.as_u16());
+        assert_eq!(self.body["code"], code);
+        assert_eq!(self.body["type"], format!("urn:blog:problem:{}", code));
+        assert_eq!(
+            self.body["request_id"].as_str(),
+            self.header("x-request-id"),
+            "problem should carry the response's request id"
+        );
+        &self.body
+    }
+
+    /// Asserts a problem whose `errors` map names `field` with `rule`.
+    #[track_caller]
+    pub fn assert_field_error(&self, status: StatusCode, code: &str, field: &str, rule: &str) {
+        let body = self.assert_problem(status, code);
+        let rules: Vec<&str> = body["errors"][field]
+            .as_array()
+            .unwrap_or_else(|| panic!("no errors for {} in {}", field, body))
+            .iter()
+            .filter_map(|violation| violation["code"].as_str())
+            .collect();
+        assert!(
+            rules.contains(&rule),
+            "expected {} on {}, got {:?}",
+            rule,
+            field,
+            rules
+        );
+    }
+}
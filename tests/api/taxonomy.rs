@@ -0,0 +1,452 @@
+use axum::http::StatusCode;
+use blog::domain::models::user::Role;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::support::{TestApp, TestUser};
+
+async fn tag(app: &TestApp, user: &TestUser, name: &str) -> Value {
+    let response = app
+        .post("/api/tags")
+        .authenticated_as(user)
+        .json(json!({ "name": name }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body.clone()
+}
+
+async fn category(app: &TestApp, user: &TestUser, name: &str, parent: Option<&Value>) -> Value {
+    let response = app
+        .post("/api/categories")
+        .authenticated_as(user)
+        .json(json!({ "name": name, "parent_id": parent.map(|p| p["id"].clone()) }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body.clone()
+}
+
+/// Creates a post with `body` and publishes it unless `publish` is false.
+async fn post_with(app: &TestApp, editor: &TestUser, body: Value, publish: bool) -> Value {
+    let response = app
+        .post("/api/posts")
+        .authenticated_as(editor)
+        .json(body)
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    if !publish {
+        return response.body.clone();
+    }
+
+    let response = app
+        .put(&format!(
+            "/api/posts/{}/status",
+            response.body["id"].as_str().unwrap()
+        ))
+        .authenticated_as(editor)
+        .json(json!({ "status": "published" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.body.clone()
+}
+
+fn titles(response: &Value) -> Vec<&str> {
+    response["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["title"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn authors_create_tags_and_editors_manage_them() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let editor = app.user(Role::Editor).await;
+
+    let created = tag(&app, &author, "Rust Async").await;
+    assert_eq!(created["slug"], "rust-async");
+    assert_eq!(created["post_count"], 0);
+
+    assert_eq!(
+        tag(&app, &author, "rust async").await["slug"],
+        "rust-async-2"
+    );
+    app.post("/api/tags")
+        .json(json!({ "name": "Anonymous" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.post("/api/tags")
+        .authenticated_as(&author)
+        .json(json!({ "name": "" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "name",
+            "length",
+        );
+
+    app.put("/api/tags/rust-async")
+        .authenticated_as(&author)
+        .json(json!({ "name": "Tokio" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    let response = app
+        .put("/api/tags/rust-async")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "Tokio" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["slug"], "tokio");
+    assert_eq!(response.body["id"], created["id"]);
+
+    app.delete("/api/tags/tokio")
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.delete("/api/tags/tokio")
+        .authenticated_as(&editor)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.get("/api/tags/tokio")
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn names_that_share_a_slug_get_numbered_ones() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+
+    assert_eq!(tag(&app, &editor, "C++").await["slug"], "c");
+    assert_eq!(tag(&app, &editor, "C#").await["slug"], "c-2");
+    assert_eq!(tag(&app, &editor, "Rust").await["slug"], "rust");
+    let response = app
+        .put("/api/tags/rust")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "C" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["slug"], "c-3");
+    // Renaming a tag to what it is called already keeps its slug.
+    let response = app
+        .put("/api/tags/c-2")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "C#" }))
+        .send()
+        .await;
+    assert_eq!(response.body["slug"], "c-2");
+    let response = app.get("/api/tags/c-3").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["name"], "C");
+
+    let rust = category(&app, &editor, "Rust", None).await;
+    assert_eq!(rust["slug"], "rust");
+    let other = category(&app, &editor, "rust!", Some(&rust)).await;
+    assert_eq!(other["slug"], "rust-2");
+    let response = app
+        .put("/api/categories/rust-2")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "RUST" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["slug"], "rust-2");
+    let response = app
+        .put("/api/categories/rust")
+        .authenticated_as(&editor)
+        .json(json!({ "name": "Go" }))
+        .send()
+        .await;
+    assert_eq!(response.body["slug"], "go");
+    assert_eq!(category(&app, &editor, "Rust", None).await["slug"], "rust");
+}
+
+#[tokio::test]
+async fn posts_are_tagged_by_slug_on_create_and_update() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    tag(&app, &editor, "Rust").await;
+    tag(&app, &editor, "Web").await;
+
+    let post = post_with(
+        &app,
+        &editor,
+        json!({ "title": "Axum", "content": "Body", "tags": ["web", "rust", "rust"] }),
+        false,
+    )
+    .await;
+    let slugs: Vec<_> = post["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["slug"].clone())
+        .collect();
+    assert_eq!(slugs, [json!("rust"), json!("web")]);
+
+    let uri = format!("/api/posts/{}", post["id"].as_str().unwrap());
+    let response = app
+        .put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "tags": ["web"] }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.body["tags"],
+        json!([{ "slug": "web", "name": "Web" }])
+    );
+
+    // Leaving `tags` out keeps them as they are.
+    let response = app
+        .put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "title": "Axum, again" }))
+        .send()
+        .await;
+    assert_eq!(
+        response.body["tags"],
+        json!([{ "slug": "web", "name": "Web" }])
+    );
+
+    app.put(&uri)
+        .authenticated_as(&editor)
+        .json(json!({ "tags": ["web", "missing"] }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_reference",
+            "tags",
+            "exists",
+        );
+    app.post("/api/posts")
+        .authenticated_as(&editor)
+        .json(json!({ "title": "Other", "content": "Body", "tags": ["missing"] }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_reference",
+            "tags",
+            "exists",
+        );
+}
+
+#[tokio::test]
+async fn tag_listings_count_and_show_only_published_posts() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    tag(&app, &editor, "Rust").await;
+    tag(&app, &editor, "Go").await;
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Live", "content": "Body", "tags": ["rust"] }),
+        true,
+    )
+    .await;
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Draft", "content": "Body", "tags": ["rust"] }),
+        false,
+    )
+    .await;
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Untagged", "content": "Body" }),
+        true,
+    )
+    .await;
+
+    let response = app.get("/api/tags/rust/posts").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(titles(&response.body), ["Live"]);
+    assert_eq!(response.body["meta"]["total"], 1);
+
+    let response = app.get("/api/tags").send().await;
+    response.assert_status(StatusCode::OK);
+    let counts: Vec<_> = response.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            (
+                t["slug"].as_str().unwrap(),
+                t["post_count"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(counts, [("go", 0), ("rust", 1)]);
+    assert_eq!(app.get("/api/tags/rust").send().await.body["post_count"], 1);
+
+    app.get("/api/tags/missing/posts")
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn categories_form_a_tree_whose_listings_include_subcategories() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let author = app.user(Role::Author).await;
+    let tech = category(&app, &editor, "Tech", None).await;
+    let rust = category(&app, &editor, "Rust", Some(&tech)).await;
+    category(&app, &editor, "Life", None).await;
+    app.post("/api/categories")
+        .authenticated_as(&author)
+        .json(json!({ "name": "Mine" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Ownership", "content": "Body", "category_id": rust["id"] }),
+        true,
+    )
+    .await;
+    post_with(
+        &app,
+        &editor,
+        json!({ "title": "Hardware", "content": "Body", "category_id": tech["id"] }),
+        true,
+    )
+    .await;
+
+    let response = app.get("/api/categories").send().await;
+    response.assert_status(StatusCode::OK);
+    let roots = response.body.as_array().unwrap();
+    assert_eq!(roots.len(), 2);
+    assert_eq!(roots[0]["slug"], "life");
+    assert_eq!(roots[1]["slug"], "tech");
+    assert_eq!(roots[1]["post_count"], 2);
+    assert_eq!(roots[1]["children"][0]["slug"], "rust");
+    assert_eq!(roots[1]["children"][0]["post_count"], 1);
+
+    let response = app.get("/api/categories/tech/posts").send().await;
+    let mut listed = titles(&response.body);
+    listed.sort();
+    assert_eq!(listed, ["Hardware", "Ownership"]);
+    let response = app.get("/api/categories/rust/posts").send().await;
+    assert_eq!(titles(&response.body), ["Ownership"]);
+
+    app.post("/api/posts")
+        .authenticated_as(&editor)
+        .json(json!({ "title": "Lost", "content": "Body", "category_id": Uuid::new_v4() }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_reference",
+            "category_id",
+            "exists",
+        );
+}
+
+#[tokio::test]
+async fn categories_cannot_be_moved_under_themselves() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let tech = category(&app, &editor, "Tech", None).await;
+    let rust = category(&app, &editor, "Rust", Some(&tech)).await;
+
+    for parent in [&tech, &rust] {
+        app.put("/api/categories/tech")
+            .authenticated_as(&editor)
+            .json(json!({ "parent_id": parent["id"] }))
+            .send()
+            .await
+            .assert_field_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                "parent_id",
+                "cycle",
+            );
+    }
+
+    let response = app
+        .put("/api/categories/rust")
+        .authenticated_as(&editor)
+        .json(json!({ "parent_id": null }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["parent_id"], Value::Null);
+}
+
+#[tokio::test]
+async fn concurrent_moves_cannot_make_a_loop() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let tech = category(&app, &editor, "Tech", None).await;
+    let news = category(&app, &editor, "News", None).await;
+
+    let (tech_moved, news_moved) = tokio::join!(
+        app.put("/api/categories/tech")
+            .authenticated_as(&editor)
+            .json(json!({ "parent_id": news["id"] }))
+            .send(),
+        app.put("/api/categories/news")
+            .authenticated_as(&editor)
+            .json(json!({ "parent_id": tech["id"] }))
+            .send(),
+    );
+    let mut statuses = [tech_moved.status, news_moved.status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::BAD_REQUEST]);
+
+    let response = app.get("/api/categories").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn deleting_a_category_keeps_its_subcategories_and_posts() {
+    let app = TestApp::new();
+    let editor = app.user(Role::Editor).await;
+    let tech = category(&app, &editor, "Tech", None).await;
+    let hardware = category(&app, &editor, "Hardware", Some(&tech)).await;
+    category(&app, &editor, "Chips", Some(&hardware)).await;
+    let post = post_with(
+        &app,
+        &editor,
+        json!({ "title": "Boards", "content": "Body", "category_id": hardware["id"] }),
+        true,
+    )
+    .await;
+
+    app.delete("/api/categories/hardware")
+        .authenticated_as(&editor)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let response = app.get("/api/categories/tech").send().await;
+    assert_eq!(response.body["children"][0]["slug"], "chips");
+    assert_eq!(response.body["children"][0]["parent_id"], tech["id"]);
+
+    let response = app
+        .get(&format!("/api/posts/{}", post["id"].as_str().unwrap()))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["category_id"], Value::Null);
+}
@@ -0,0 +1,228 @@
+use axum::http::StatusCode;
+use blog::domain::models::{comment::MAX_REPLY_DEPTH, post::Post, user::Role};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::support::{TestApp, TestUser};
+
+async fn reply(app: &TestApp, user: &TestUser, post: &Post, parent: Option<&Value>) -> Value {
+    let response = app
+        .post("/api/comments")
+        .authenticated_as(user)
+        .json(json!({
+            "content": "A reply",
+            "post_id": post.id,
+            "parent_id": parent.map(|p| p["id"].clone()),
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.body.clone()
+}
+
+async fn thread(app: &TestApp, post: &Post) -> Value {
+    let response = app
+        .get(&format!("/api/comments/post/{}/thread", post.id))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.body["data"].clone()
+}
+
+fn comment_uri(comment: &Value) -> String {
+    format!("/api/comments/{}", comment["id"].as_str().unwrap())
+}
+
+#[tokio::test]
+async fn replies_nest_into_threads_with_counts() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let first = reply(&app, &author, &post, None).await;
+    let answer = reply(&app, &author, &post, Some(&first)).await;
+    let follow_up = reply(&app, &author, &post, Some(&answer)).await;
+    let second = reply(&app, &author, &post, None).await;
+    assert_eq!(answer["parent_id"], first["id"]);
+    assert_eq!(answer["reply_count"], 0);
+
+    let threads = thread(&app, &post).await;
+    let roots = threads.as_array().unwrap();
+    assert_eq!(roots.len(), 2);
+    assert_eq!(roots[0]["id"], first["id"]);
+    assert_eq!(roots[0]["depth"], 0);
+    assert_eq!(roots[0]["reply_count"], 1);
+    assert_eq!(roots[0]["replies"][0]["id"], answer["id"]);
+    assert_eq!(roots[0]["replies"][0]["depth"], 1);
+    assert_eq!(roots[0]["replies"][0]["replies"][0]["id"], follow_up["id"]);
+    assert_eq!(roots[0]["replies"][0]["replies"][0]["depth"], 2);
+    assert_eq!(roots[1]["id"], second["id"]);
+    assert_eq!(roots[1]["replies"], json!([]));
+
+    let response = app.get(&comment_uri(&answer)).send().await;
+    assert_eq!(response.body["reply_count"], 1);
+}
+
+#[tokio::test]
+async fn threads_are_paged_by_their_top_level_comments() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let mut roots = Vec::new();
+    for _ in 0..3 {
+        let root = reply(&app, &author, &post, None).await;
+        reply(&app, &author, &post, Some(&root)).await;
+        roots.push(root);
+    }
+
+    let uri = format!("/api/comments/post/{}/thread?limit=2", post.id);
+    let response = app.get(&uri).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 3);
+    let page = response.body["data"].as_array().unwrap();
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0]["id"], roots[0]["id"]);
+    assert_eq!(page[1]["id"], roots[1]["id"]);
+    assert_eq!(page[1]["replies"].as_array().unwrap().len(), 1);
+
+    let next = response.body["links"]["next"].as_str().unwrap().to_string();
+    let response = app.get(&next).send().await;
+    assert_eq!(response.body["data"][0]["id"], roots[2]["id"]);
+    assert_eq!(response.body["data"][0]["replies"][0]["depth"], 1);
+    assert_eq!(response.body["links"]["next"], Value::Null);
+
+    app.get(&format!("/api/comments/post/{}/thread?limit=0", post.id))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "limit",
+            "range",
+        );
+}
+
+#[tokio::test]
+async fn replies_need_a_live_parent_on_the_same_post_within_the_depth_limit() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let other = app.post_by(&author).await;
+    let elsewhere = reply(&app, &author, &other, None).await;
+
+    let attempt = |parent_id: Value| {
+        app.post("/api/comments")
+            .authenticated_as(&author)
+            .json(json!({ "content": "Reply", "post_id": post.id, "parent_id": parent_id }))
+            .send()
+    };
+    attempt(json!(Uuid::new_v4())).await.assert_field_error(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "invalid_reference",
+        "parent_id",
+        "exists",
+    );
+    attempt(elsewhere["id"].clone()).await.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "parent_id",
+        "same_post",
+    );
+
+    let mut deepest = reply(&app, &author, &post, None).await;
+    for _ in 0..MAX_REPLY_DEPTH {
+        deepest = reply(&app, &author, &post, Some(&deepest)).await;
+    }
+    attempt(deepest["id"].clone()).await.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "parent_id",
+        "depth",
+    );
+}
+
+#[tokio::test]
+async fn deleting_a_comment_with_replies_leaves_a_tombstone() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let reader = app.user(Role::Reader).await;
+    let post = app.post_by(&author).await;
+    // An approved comment elsewhere keeps the reader's reply out of the queue.
+    app.comment_by(&reader, &app.post_by(&author).await).await;
+    let parent = reply(&app, &author, &post, None).await;
+    let child = reply(&app, &reader, &post, Some(&parent)).await;
+
+    app.delete(&comment_uri(&parent))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let threads = thread(&app, &post).await;
+    let tombstone = &threads[0];
+    assert_eq!(tombstone["id"], parent["id"]);
+    assert_eq!(tombstone["content"], "");
+    assert_eq!(tombstone["content_html"], "");
+    assert!(tombstone["deleted_at"].is_string());
+    assert_eq!(tombstone["author_id"], Value::Null);
+    assert_eq!(tombstone["replies"][0]["id"], child["id"]);
+    assert_eq!(tombstone["replies"][0]["content"], "A reply");
+
+    app.put(&comment_uri(&parent))
+        .authenticated_as(&author)
+        .json(json!({ "content": "Back" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&comment_uri(&parent))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.post("/api/comments")
+        .authenticated_as(&reader)
+        .json(json!({ "content": "Hi", "post_id": post.id, "parent_id": parent["id"] }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "parent_id",
+            "deleted",
+        );
+
+    // The last reply going takes the tombstone with it.
+    app.delete(&comment_uri(&child))
+        .authenticated_as(&reader)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(thread(&app, &post).await, json!([]));
+    app.get(&comment_uri(&parent))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn deleting_a_reply_updates_its_parents_count() {
+    let app = TestApp::new();
+    let author = app.user(Role::Author).await;
+    let post = app.post_by(&author).await;
+    let parent = reply(&app, &author, &post, None).await;
+    let first = reply(&app, &author, &post, Some(&parent)).await;
+    reply(&app, &author, &post, Some(&parent)).await;
+    assert_eq!(
+        app.get(&comment_uri(&parent)).send().await.body["reply_count"],
+        2
+    );
+
+    app.delete(&comment_uri(&first))
+        .authenticated_as(&author)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let response = app.get(&comment_uri(&parent)).send().await;
+    assert_eq!(response.body["reply_count"], 1);
+    assert_eq!(response.body["deleted_at"], Value::Null);
+}
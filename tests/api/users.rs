@@ -0,0 +1,335 @@
+use axum::http::StatusCode;
+use blog::domain::models::user::Role;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::support::{TestApp, test_config};
+
+#[tokio::test]
+async fn list_users_is_paginated_and_filterable() {
+    let app = TestApp::new();
+    app.user(Role::Reader).await;
+    app.user(Role::Reader).await;
+    let editor = app.user(Role::Editor).await;
+
+    let response = app.get("/api/users?limit=2").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 3);
+    assert_eq!(response.body["data"].as_array().unwrap().len(), 2);
+
+    let response = app.get("/api/users?role=editor").send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["meta"]["total"], 1);
+    assert_eq!(response.body["data"][0]["id"], editor.id().to_string());
+}
+
+#[tokio::test]
+async fn get_user_by_id_and_email() {
+    let app = TestApp::new();
+    let user = app.user(Role::Reader).await;
+
+    let response = app.get(&format!("/api/users/{}", user.id())).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["username"], user.user.username);
+    assert_eq!(response.body["role"], "reader");
+    assert!(response.body.get("password_hash").is_none());
+
+    let response = app
+        .get(&format!("/api/users/email/{}", user.user.email))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["id"], user.id().to_string());
+}
+
+#[tokio::test]
+async fn get_missing_user_is_not_found() {
+    let app = TestApp::new();
+
+    app.get(&format!("/api/users/{}", Uuid::new_v4()))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.get("/api/users/email/nobody@example.com")
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn register_creates_an_author() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/users")
+        .json(json!({
+            "username": "newcomer",
+            "email": "newcomer@example.com",
+            "password": "correct horse"
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.body["username"], "newcomer");
+    assert_eq!(response.body["role"], "author");
+    assert!(response.body.get("password").is_none());
+    assert!(response.body.get("password_hash").is_none());
+}
+
+#[tokio::test]
+async fn register_reports_every_invalid_field() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/users")
+        .json(json!({ "username": "ab", "email": "nope", "password": "short" }))
+        .send()
+        .await;
+    response.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "username",
+        "length",
+    );
+    response.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "email",
+        "email",
+    );
+    response.assert_field_error(
+        StatusCode::BAD_REQUEST,
+        "validation_failed",
+        "password",
+        "length",
+    );
+    assert_eq!(
+        response.body["errors"]["username"][0]["params"],
+        json!({ "min": 3, "max": 50 })
+    );
+}
+
+#[tokio::test]
+async fn register_rejects_taken_email() {
+    let app = TestApp::new();
+    let existing = app.user(Role::Reader).await;
+
+    let response = app
+        .post("/api/users")
+        .json(json!({
+            "username": "someone-else",
+            "email": existing.user.email,
+            "password": "correct horse"
+        }))
+        .send()
+        .await;
+    response.assert_field_error(StatusCode::CONFLICT, "conflict", "email", "unique");
+    assert_eq!(
+        response.body["detail"],
+        "The value for email is already taken."
+    );
+}
+
+#[tokio::test]
+async fn register_is_absent_when_disabled() {
+    let mut config = test_config();
+    config.features.registration = false;
+    let app = TestApp::with_config(config);
+
+    app.post("/api/users")
+        .json(json!({
+            "username": "newcomer",
+            "email": "newcomer@example.com",
+            "password": "correct horse"
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    app.get("/api/users")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn users_may_update_themselves() {
+    let app = TestApp::new();
+    let user = app.user(Role::Reader).await;
+
+    let response = app
+        .put(&format!("/api/users/{}", user.id()))
+        .authenticated_as(&user)
+        .json(json!({ "username": "renamed" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["username"], "renamed");
+    assert_eq!(response.body["email"], user.user.email);
+}
+
+#[tokio::test]
+async fn users_cannot_update_others_or_promote_themselves() {
+    let app = TestApp::new();
+    let user = app.user(Role::Author).await;
+    let other = app.user(Role::Author).await;
+
+    app.put(&format!("/api/users/{}", other.id()))
+        .authenticated_as(&user)
+        .json(json!({ "username": "hijacked" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.put(&format!("/api/users/{}", user.id()))
+        .authenticated_as(&user)
+        .json(json!({ "role": "admin" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.put(&format!("/api/users/{}", user.id()))
+        .json(json!({ "username": "anonymous" }))
+        .send()
+        .await
+        .assert_problem(StatusCode::UNAUTHORIZED, "unauthorized");
+}
+
+#[tokio::test]
+async fn admins_may_assign_roles() {
+    let app = TestApp::new();
+    let admin = app.user(Role::Admin).await;
+    let user = app.user(Role::Author).await;
+
+    let response = app
+        .put(&format!("/api/users/{}", user.id()))
+        .authenticated_as(&admin)
+        .json(json!({ "role": "editor" }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["role"], "editor");
+}
+
+#[tokio::test]
+async fn update_user_reports_conflicts_and_invalid_input() {
+    let app = TestApp::new();
+    let user = app.user(Role::Reader).await;
+    let other = app.user(Role::Reader).await;
+    let uri = format!("/api/users/{}", user.id());
+
+    app.put(&uri)
+        .authenticated_as(&user)
+        .json(json!({ "username": other.user.username }))
+        .send()
+        .await
+        .assert_field_error(StatusCode::CONFLICT, "conflict", "username", "unique");
+    app.put(&uri)
+        .authenticated_as(&user)
+        .json(json!({ "email": "not-an-email" }))
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "email",
+            "email",
+        );
+}
+
+#[tokio::test]
+async fn delete_user_is_restricted_to_self_and_admins() {
+    let app = TestApp::new();
+    let admin = app.user(Role::Admin).await;
+    let user = app.user(Role::Reader).await;
+    let other = app.user(Role::Reader).await;
+
+    app.delete(&format!("/api/users/{}", other.id()))
+        .authenticated_as(&user)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+
+    app.delete(&format!("/api/users/{}", user.id()))
+        .authenticated_as(&user)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("/api/users/{}", other.id()))
+        .authenticated_as(&admin)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    app.get(&format!("/api/users/{}", user.id()))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&format!("/api/users/{}", Uuid::new_v4()))
+        .authenticated_as(&admin)
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn admins_may_hand_a_deleted_users_posts_to_someone_else() {
+    let app = TestApp::new();
+    let admin = app.user(Role::Admin).await;
+    let leaving = app.user(Role::Author).await;
+    let heir = app.user(Role::Author).await;
+    let post = app.post_by(&leaving).await;
+
+    app.delete(&format!(
+        "/api/users/{}?reassign_posts_to={}",
+        leaving.id(),
+        heir.id()
+    ))
+    .authenticated_as(&admin)
+    .send()
+    .await
+    .assert_status(StatusCode::NO_CONTENT);
+
+    app.get(&format!("/api/users/{}", leaving.id()))
+        .send()
+        .await
+        .assert_problem(StatusCode::NOT_FOUND, "not_found");
+    let response = app.get(&format!("/api/posts/{}", post.id)).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.body["author_id"], heir.id().to_string());
+}
+
+#[tokio::test]
+async fn reassigning_posts_on_delete_is_checked_before_anything_changes() {
+    let app = TestApp::new();
+    let admin = app.user(Role::Admin).await;
+    let leaving = app.user(Role::Author).await;
+    let heir = app.user(Role::Author).await;
+    let post = app.post_by(&leaving).await;
+    let uri = |target: Uuid| format!("/api/users/{}?reassign_posts_to={}", leaving.id(), target);
+
+    app.delete(&uri(heir.id()))
+        .authenticated_as(&leaving)
+        .send()
+        .await
+        .assert_problem(StatusCode::FORBIDDEN, "forbidden");
+    app.delete(&uri(Uuid::new_v4()))
+        .authenticated_as(&admin)
+        .send()
+        .await
+        .assert_field_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_reference",
+            "reassign_posts_to",
+            "exists",
+        );
+    app.delete(&uri(leaving.id()))
+        .authenticated_as(&admin)
+        .send()
+        .await
+        .assert_problem(StatusCode::BAD_REQUEST, "bad_request");
+
+    app.get(&format!("/api/users/{}", leaving.id()))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    let response = app.get(&format!("/api/posts/{}", post.id)).send().await;
+    assert_eq!(response.body["author_id"], leaving.id().to_string());
+}
@@ -0,0 +1,2053 @@
+//! Behaviour every repository implementation must share.
+//!
+//! Each check is a plain async function over a [`Backend`], so the same
+//! assertions run against Postgres and the in-memory store. Checks assume an
+//! empty backend and create everything they look at.
+
+use std::time::Duration;
+
+use blog::domain::{
+    models::{
+        category::Category,
+        comment::{
+            Comment, CommentFilter, CommentQuery, CommentStatus, CommentStreamQuery,
+            ModerationOverrides, ModerationQueueQuery,
+        },
+        pagination::{Page, PageRequest, SortDirection},
+        post::{Post, PostFilter, PostQuery, PostSortField, PostStatus},
+        search::{SearchHit, SearchQuery, SearchScope, SearchTerms},
+        tag::Tag,
+        user::{Role, User, UserFilter, UserQuery},
+    },
+    repositories::{
+        CategoryRepository, CommentRepository, PostRepository, PostRevisionRepository,
+        SearchRepository, TagRepository, TransactionScope, UnitOfWork, UserRepository,
+    },
+};
+use blog::shared::error::ApiError;
+use chrono::SubsecRound;
+use uuid::Uuid;
+
+pub trait Backend {
+    type Users: UserRepository;
+    type Posts: PostRepository;
+    type PostRevisions: PostRevisionRepository;
+    type Tags: TagRepository;
+    type Categories: CategoryRepository;
+    type Comments: CommentRepository;
+    type Search: SearchRepository;
+    type Work: UnitOfWork;
+
+    fn users(&self) -> &Self::Users;
+    fn posts(&self) -> &Self::Posts;
+    fn post_revisions(&self) -> &Self::PostRevisions;
+    fn tags(&self) -> &Self::Tags;
+    fn categories(&self) -> &Self::Categories;
+    fn comments(&self) -> &Self::Comments;
+    fn search(&self) -> &Self::Search;
+    fn unit_of_work(&self) -> &Self::Work;
+}
+
+/// Truncated to what Postgres stores, so round-tripped values compare equal.
+fn now() -> chrono::NaiveDateTime {
+    chrono::Local::now().naive_local().trunc_subsecs(6)
+}
+
+/// A user with a placeholder hash; hashing a real password is slow and the
+/// repositories never look inside it.
+fn user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        username: name.to_string(),
+        email: format!("{}@example.com", name),
+        password_hash: "not-a-real-hash".to_string(),
+        created_at: now(),
+        updated_at: now(),
+        role: Role::Author,
+    }
+}
+
+fn post(author_id: Uuid, title: &str) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        title: title.to_string(),
+        content: format!("Content of {}", title),
+        author_id,
+        created_at: now(),
+        updated_at: now(),
+        slug: format!("{}-{}", title.to_lowercase(), Uuid::new_v4().simple()),
+        status: PostStatus::Draft,
+        publish_at: None,
+        published_at: None,
+        category_id: None,
+        content_html: None,
+    }
+}
+
+fn tag(name: &str) -> Tag {
+    Tag {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        slug: name.to_lowercase(),
+        created_at: now(),
+        updated_at: now(),
+    }
+}
+
+fn category(name: &str, parent_id: Option<Uuid>) -> Category {
+    Category {
+        id: Uuid::new_v4(),
+        parent_id,
+        name: name.to_string(),
+        slug: name.to_lowercase(),
+        description: None,
+        created_at: now(),
+        updated_at: now(),
+    }
+}
+
+fn comment(post_id: Uuid, author_id: Uuid, content: &str) -> Comment {
+    Comment {
+        id: Uuid::new_v4(),
+        content: content.to_string(),
+        post_id,
+        author_id,
+        created_at: now(),
+        updated_at: now(),
+        content_html: None,
+        parent_id: None,
+        reply_count: 0,
+        deleted_at: None,
+        status: CommentStatus::Approved,
+    }
+}
+
+async fn create_user<B: Backend>(backend: &B, name: &str) -> User {
+    backend.users().create(user(name)).await.unwrap()
+}
+
+async fn create_post<B: Backend>(backend: &B, author_id: Uuid, title: &str) -> Post {
+    backend
+        .posts()
+        .create(post(author_id, title))
+        .await
+        .unwrap()
+}
+
+async fn create_tag<B: Backend>(backend: &B, name: &str) -> Tag {
+    backend.tags().create(tag(name)).await.unwrap()
+}
+
+async fn create_category<B: Backend>(backend: &B, name: &str, parent_id: Option<Uuid>) -> Category {
+    backend
+        .categories()
+        .create(category(name, parent_id))
+        .await
+        .unwrap()
+}
+
+async fn publish<B: Backend>(backend: &B, mut post: Post) -> Post {
+    post.status = PostStatus::Published;
+    post.published_at = Some(now());
+    backend.posts().update(post.id, post).await.unwrap()
+}
+
+async fn create_comment<B: Backend>(
+    backend: &B,
+    post_id: Uuid,
+    author_id: Uuid,
+    content: &str,
+) -> Comment {
+    backend
+        .comments()
+        .create(comment(post_id, author_id, content))
+        .await
+        .unwrap()
+}
+
+fn assert_not_found<T: std::fmt::Debug>(result: Result<T, ApiError>) {
+    match result {
+        Err(ApiError::NotFound) => {}
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+fn assert_conflict_on<T: std::fmt::Debug>(result: Result<T, ApiError>, expected: &str) {
+    match result {
+        Err(ApiError::Conflict { field }) => assert_eq!(field, expected),
+        other => panic!("expected Conflict on {}, got {:?}", expected, other),
+    }
+}
+
+fn assert_invalid_reference<T: std::fmt::Debug>(result: Result<T, ApiError>, expected: &str) {
+    match result {
+        Err(ApiError::InvalidReference { field }) => assert_eq!(field, expected),
+        other => panic!("expected InvalidReference on {}, got {:?}", expected, other),
+    }
+}
+
+// Users
+
+pub async fn user_find_returns_the_requested_row<B: Backend>(backend: &B) {
+    let _first = create_user(backend, "first").await;
+    let second = create_user(backend, "second").await;
+    let _third = create_user(backend, "third").await;
+
+    let found = backend.users().find(second.id).await.unwrap();
+    assert_eq!(found.id, second.id);
+    assert_eq!(found.username, "second");
+}
+
+pub async fn user_find_missing_is_not_found<B: Backend>(backend: &B) {
+    create_user(backend, "someone").await;
+    assert_not_found(backend.users().find(Uuid::new_v4()).await);
+}
+
+pub async fn user_find_by_email_matches_the_address<B: Backend>(backend: &B) {
+    create_user(backend, "first").await;
+    let second = create_user(backend, "second").await;
+
+    let found = backend
+        .users()
+        .find_by_email("second@example.com")
+        .await
+        .unwrap();
+    assert_eq!(found.id, second.id);
+
+    assert_not_found(backend.users().find_by_email("nobody@example.com").await);
+}
+
+pub async fn user_create_assigns_id_and_timestamps<B: Backend>(backend: &B) {
+    let requested = user("fresh");
+    let created = backend.users().create(requested.clone()).await.unwrap();
+
+    assert_eq!(created.username, requested.username);
+    assert_eq!(created.created_at, created.updated_at);
+    assert_eq!(created.role, Role::Author);
+    assert_eq!(
+        backend.users().find(created.id).await.unwrap().email,
+        created.email
+    );
+}
+
+pub async fn user_username_and_email_are_unique<B: Backend>(backend: &B) {
+    create_user(backend, "taken").await;
+
+    let mut same_name = user("taken");
+    same_name.email = "other@example.com".to_string();
+    assert_conflict_on(backend.users().create(same_name).await, "username");
+
+    let mut same_email = user("other");
+    same_email.email = "taken@example.com".to_string();
+    assert_conflict_on(backend.users().create(same_email).await, "email");
+}
+
+pub async fn user_update_replaces_fields_and_bumps_updated_at<B: Backend>(backend: &B) {
+    let created = create_user(backend, "before").await;
+
+    let mut changes = created.clone();
+    changes.username = "after".to_string();
+    changes.email = "after@example.com".to_string();
+    changes.role = Role::Editor;
+    let updated = backend.users().update(created.id, changes).await.unwrap();
+
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.username, "after");
+    assert_eq!(updated.email, "after@example.com");
+    assert_eq!(updated.role, Role::Editor);
+    assert_eq!(updated.created_at, created.created_at);
+    assert!(updated.updated_at >= created.updated_at);
+
+    let reloaded = backend.users().find(created.id).await.unwrap();
+    assert_eq!(reloaded.username, "after");
+}
+
+pub async fn user_update_keeps_uniqueness<B: Backend>(backend: &B) {
+    create_user(backend, "owner").await;
+    let other = create_user(backend, "other").await;
+
+    let mut changes = other.clone();
+    changes.email = "owner@example.com".to_string();
+    assert_conflict_on(backend.users().update(other.id, changes).await, "email");
+
+    // Saving a row unchanged must not trip over its own values.
+    backend
+        .users()
+        .update(other.id, other.clone())
+        .await
+        .unwrap();
+}
+
+pub async fn user_update_missing_is_not_found<B: Backend>(backend: &B) {
+    assert_not_found(backend.users().update(Uuid::new_v4(), user("ghost")).await);
+}
+
+pub async fn user_delete_cascades_to_posts_and_comments<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let reader = create_user(backend, "reader").await;
+    let authored = create_post(backend, author.id, "Doomed").await;
+    let kept = create_post(backend, reader.id, "Kept").await;
+    let on_doomed = create_comment(backend, authored.id, reader.id, "on doomed post").await;
+    let by_author = create_comment(backend, kept.id, author.id, "by doomed author").await;
+    let survivor = create_comment(backend, kept.id, reader.id, "survives").await;
+
+    backend.users().delete(author.id).await.unwrap();
+
+    assert_not_found(backend.users().find(author.id).await);
+    assert_not_found(backend.posts().find(authored.id).await);
+    assert_not_found(backend.comments().find(on_doomed.id).await);
+    assert_not_found(backend.comments().find(by_author.id).await);
+    backend.posts().find(kept.id).await.unwrap();
+    backend.comments().find(survivor.id).await.unwrap();
+}
+
+pub async fn user_delete_missing_is_not_found<B: Backend>(backend: &B) {
+    assert_not_found(backend.users().delete(Uuid::new_v4()).await);
+}
+
+pub async fn user_find_all_filters_and_paginates<B: Backend>(backend: &B) {
+    for name in ["ann", "bob", "cat", "dan", "eve"] {
+        create_user(backend, name).await;
+    }
+    let mut editor = create_user(backend, "fay").await;
+    editor.role = Role::Editor;
+    backend
+        .users()
+        .update(editor.id, editor.clone())
+        .await
+        .unwrap();
+
+    let query = UserQuery {
+        sort: blog::domain::models::user::UserSortField::Username,
+        direction: SortDirection::Asc,
+        page: PageRequest::new(Some(2), Some(2)),
+        ..Default::default()
+    };
+    let page = backend.users().find_all(&query).await.unwrap();
+    assert_eq!(page.total, 6);
+    let names: Vec<_> = page.items.iter().map(|u| u.username.as_str()).collect();
+    assert_eq!(names, ["cat", "dan"]);
+
+    let query = UserQuery {
+        filter: UserFilter {
+            role: Some(Role::Editor),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let page = backend.users().find_all(&query).await.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, editor.id);
+}
+
+// Posts
+
+pub async fn post_find_returns_the_requested_row<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    create_post(backend, author.id, "One").await;
+    let two = create_post(backend, author.id, "Two").await;
+    create_post(backend, author.id, "Three").await;
+
+    let found = backend.posts().find(two.id).await.unwrap();
+    assert_eq!(found.id, two.id);
+    assert_eq!(found.title, "Two");
+}
+
+pub async fn post_find_missing_is_not_found<B: Backend>(backend: &B) {
+    assert_not_found(backend.posts().find(Uuid::new_v4()).await);
+}
+
+pub async fn post_create_requires_an_existing_author<B: Backend>(backend: &B) {
+    assert_invalid_reference(
+        backend.posts().create(post(Uuid::new_v4(), "Orphan")).await,
+        "author_id",
+    );
+}
+
+pub async fn post_update_replaces_content_but_not_author<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let other = create_user(backend, "other").await;
+    let created = create_post(backend, author.id, "Draft").await;
+
+    let mut changes = created.clone();
+    changes.title = "Final".to_string();
+    changes.content = "Rewritten".to_string();
+    changes.content_html = Some("<p>Rewritten</p>".to_string());
+    changes.status = PostStatus::Published;
+    changes.published_at = Some(now());
+    changes.author_id = other.id;
+    let updated = backend
+        .posts()
+        .update(created.id, changes.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(updated.title, "Final");
+    assert_eq!(updated.content, "Rewritten");
+    assert_eq!(updated.content_html.as_deref(), Some("<p>Rewritten</p>"));
+    assert_eq!(updated.status, PostStatus::Published);
+    assert_eq!(updated.published_at, changes.published_at);
+    assert_eq!(updated.author_id, author.id);
+    assert_eq!(updated.created_at, created.created_at);
+    assert!(updated.updated_at >= created.updated_at);
+}
+
+pub async fn post_update_missing_is_not_found<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    assert_not_found(
+        backend
+            .posts()
+            .update(Uuid::new_v4(), post(author.id, "Ghost"))
+            .await,
+    );
+}
+
+pub async fn post_delete_cascades_to_comments<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let doomed = create_post(backend, author.id, "Doomed").await;
+    let kept = create_post(backend, author.id, "Kept").await;
+    let gone = create_comment(backend, doomed.id, author.id, "gone").await;
+    let stays = create_comment(backend, kept.id, author.id, "stays").await;
+
+    backend.posts().delete(doomed.id).await.unwrap();
+
+    assert_not_found(backend.posts().find(doomed.id).await);
+    assert_not_found(backend.comments().find(gone.id).await);
+    backend.comments().find(stays.id).await.unwrap();
+    assert_not_found(backend.posts().delete(doomed.id).await);
+}
+
+pub async fn post_find_all_filters_sorts_and_paginates<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let other = create_user(backend, "other").await;
+    for title in ["Delta", "Alpha", "Charlie", "Bravo"] {
+        create_post(backend, author.id, title).await;
+    }
+    let mut published = create_post(backend, other.id, "Echo").await;
+    published.status = PostStatus::Published;
+    published.published_at = Some(now());
+    backend
+        .posts()
+        .update(published.id, published.clone())
+        .await
+        .unwrap();
+
+    let query = PostQuery {
+        filter: PostFilter {
+            author_id: Some(author.id),
+            ..Default::default()
+        },
+        sort: PostSortField::Title,
+        direction: SortDirection::Asc,
+        page: PageRequest::new(Some(1), Some(3)),
+    };
+    let page = backend.posts().find_all(&query).await.unwrap();
+    assert_eq!(page.total, 4);
+    let titles: Vec<_> = page.items.iter().map(|p| p.title.as_str()).collect();
+    assert_eq!(titles, ["Alpha", "Bravo", "Charlie"]);
+    assert!(page.has_next());
+
+    let query = PostQuery {
+        filter: PostFilter {
+            status: Some(PostStatus::Published),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let page = backend.posts().find_all(&query).await.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, published.id);
+}
+
+pub async fn post_publish_due_publishes_only_due_scheduled_posts<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let clock = now();
+    let schedule = |title: &str, publish_at| {
+        let mut post = post(author.id, title);
+        post.status = PostStatus::Scheduled;
+        post.publish_at = Some(publish_at);
+        post
+    };
+    let due = backend
+        .posts()
+        .create(schedule("Due", clock - chrono::Duration::minutes(5)))
+        .await
+        .unwrap();
+    let on_time = backend
+        .posts()
+        .create(schedule("OnTime", clock))
+        .await
+        .unwrap();
+    let later = backend
+        .posts()
+        .create(schedule("Later", clock + chrono::Duration::minutes(5)))
+        .await
+        .unwrap();
+    let draft = create_post(backend, author.id, "Draft").await;
+
+    let published = backend.posts().publish_due(clock).await.unwrap();
+    let mut ids: Vec<_> = published.iter().map(|post| post.id).collect();
+    ids.sort();
+    let mut expected = vec![due.id, on_time.id];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    for (scheduled, at) in [(&due, due.publish_at), (&on_time, on_time.publish_at)] {
+        let found = backend.posts().find(scheduled.id).await.unwrap();
+        assert_eq!(found.status, PostStatus::Published);
+        assert_eq!(found.published_at, at);
+        assert_eq!(found.publish_at, None);
+    }
+    let found = backend.posts().find(later.id).await.unwrap();
+    assert_eq!(found.status, PostStatus::Scheduled);
+    assert_eq!(found.published_at, None);
+    let found = backend.posts().find(draft.id).await.unwrap();
+    assert_eq!(found.status, PostStatus::Draft);
+
+    assert!(backend.posts().publish_due(clock).await.unwrap().is_empty());
+}
+
+pub async fn post_reassign_author_moves_only_that_authors_posts<B: Backend>(backend: &B) {
+    let from = create_user(backend, "from").await;
+    let to = create_user(backend, "to").await;
+    let bystander = create_user(backend, "bystander").await;
+    let first = create_post(backend, from.id, "First").await;
+    let second = create_post(backend, from.id, "Second").await;
+    let untouched = create_post(backend, bystander.id, "Untouched").await;
+
+    let moved = backend
+        .posts()
+        .reassign_author(from.id, to.id)
+        .await
+        .unwrap();
+
+    assert_eq!(moved, 2);
+    for post in [&first, &second] {
+        let reloaded = backend.posts().find(post.id).await.unwrap();
+        assert_eq!(reloaded.author_id, to.id);
+        assert!(reloaded.updated_at >= post.updated_at);
+    }
+    assert_eq!(
+        backend.posts().find(untouched.id).await.unwrap().author_id,
+        bystander.id
+    );
+    assert_eq!(
+        backend
+            .posts()
+            .reassign_author(from.id, to.id)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+pub async fn post_reassign_author_requires_an_existing_target<B: Backend>(backend: &B) {
+    let from = create_user(backend, "from").await;
+    let post = create_post(backend, from.id, "Stays").await;
+
+    assert_invalid_reference(
+        backend
+            .posts()
+            .reassign_author(from.id, Uuid::new_v4())
+            .await,
+        "author_id",
+    );
+    assert_eq!(
+        backend.posts().find(post.id).await.unwrap().author_id,
+        from.id
+    );
+}
+
+pub async fn post_find_by_slug_follows_former_slugs<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let mut created = backend
+        .posts()
+        .create(Post {
+            slug: "first-slug".to_string(),
+            ..post(author.id, "First")
+        })
+        .await
+        .unwrap();
+
+    let found = backend.posts().find_by_slug("first-slug").await.unwrap();
+    assert_eq!(found.id, created.id);
+
+    created.slug = "second-slug".to_string();
+    backend
+        .posts()
+        .update(created.id, created.clone())
+        .await
+        .unwrap();
+
+    let found = backend.posts().find_by_slug("first-slug").await.unwrap();
+    assert_eq!(found.id, created.id);
+    assert_eq!(found.slug, "second-slug");
+    assert_not_found(backend.posts().find_by_slug("never-used").await);
+}
+
+pub async fn post_slug_series_covers_current_and_former_slugs<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let mut ids = Vec::new();
+    for slug in [
+        "news",
+        "news-2",
+        "news-4",
+        "news-02",
+        "newsroom",
+        "news-flash",
+    ] {
+        let created = backend
+            .posts()
+            .create(Post {
+                slug: slug.to_string(),
+                ..post(author.id, slug)
+            })
+            .await
+            .unwrap();
+        ids.push(created);
+    }
+    let mut renamed = ids[2].clone();
+    renamed.slug = "renamed".to_string();
+    backend.posts().update(renamed.id, renamed).await.unwrap();
+
+    let mut series = backend.posts().slug_series("news").await.unwrap();
+    series.sort();
+    assert_eq!(
+        series,
+        [
+            ("news".to_string(), ids[0].id),
+            ("news-2".to_string(), ids[1].id),
+            ("news-4".to_string(), ids[2].id),
+        ]
+    );
+    assert_eq!(backend.posts().slug_series("none").await.unwrap(), []);
+}
+
+pub async fn post_renaming_back_reclaims_a_former_slug<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let mut created = backend
+        .posts()
+        .create(Post {
+            slug: "original".to_string(),
+            ..post(author.id, "Original")
+        })
+        .await
+        .unwrap();
+
+    created.slug = "interim".to_string();
+    backend
+        .posts()
+        .update(created.id, created.clone())
+        .await
+        .unwrap();
+    created.slug = "original".to_string();
+    let updated = backend
+        .posts()
+        .update(created.id, created.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(updated.slug, "original");
+    let found = backend.posts().find_by_slug("interim").await.unwrap();
+    assert_eq!(found.slug, "original");
+}
+
+pub async fn post_slugs_are_unique<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    backend
+        .posts()
+        .create(Post {
+            slug: "taken".to_string(),
+            ..post(author.id, "Taken")
+        })
+        .await
+        .unwrap();
+
+    assert_conflict_on(
+        backend
+            .posts()
+            .create(Post {
+                slug: "taken".to_string(),
+                ..post(author.id, "Again")
+            })
+            .await,
+        "slug",
+    );
+
+    let mut other = create_post(backend, author.id, "Other").await;
+    other.slug = "taken".to_string();
+    assert_conflict_on(backend.posts().update(other.id, other).await, "slug");
+}
+
+pub async fn post_delete_releases_its_slugs<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let mut created = backend
+        .posts()
+        .create(Post {
+            slug: "before".to_string(),
+            ..post(author.id, "Before")
+        })
+        .await
+        .unwrap();
+    created.slug = "after".to_string();
+    backend
+        .posts()
+        .update(created.id, created.clone())
+        .await
+        .unwrap();
+
+    backend.posts().delete(created.id).await.unwrap();
+
+    assert_not_found(backend.posts().find_by_slug("before").await);
+    assert_not_found(backend.posts().find_by_slug("after").await);
+    assert!(
+        backend
+            .posts()
+            .slug_series("before")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+// Post revisions
+
+pub async fn post_revisions_are_numbered_per_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let editor = create_user(backend, "editor").await;
+    let mut first = create_post(backend, author.id, "First").await;
+    let second = create_post(backend, author.id, "Second").await;
+
+    let one = backend
+        .post_revisions()
+        .record(&first, Some(author.id))
+        .await
+        .unwrap();
+    first.content = "Changed".to_string();
+    first.status = PostStatus::InReview;
+    let two = backend
+        .post_revisions()
+        .record(&first, Some(editor.id))
+        .await
+        .unwrap();
+    let other = backend
+        .post_revisions()
+        .record(&second, None)
+        .await
+        .unwrap();
+
+    assert_eq!((one.revision, two.revision, other.revision), (1, 2, 1));
+    assert_eq!(two.post_id, first.id);
+    assert_eq!(two.editor_id, Some(editor.id));
+    assert_eq!(two.content, "Changed");
+    assert_eq!(two.status, PostStatus::InReview);
+    assert_eq!(other.editor_id, None);
+
+    let found = backend.post_revisions().find(first.id, 1).await.unwrap();
+    assert_eq!(found.id, one.id);
+    assert_eq!(found.content, one.content);
+    assert_not_found(backend.post_revisions().find(first.id, 3).await);
+    assert_not_found(backend.post_revisions().find(second.id, 2).await);
+}
+
+pub async fn post_revisions_are_listed_newest_first<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let post = create_post(backend, author.id, "Post").await;
+    for _ in 0..3 {
+        backend
+            .post_revisions()
+            .record(&post, Some(author.id))
+            .await
+            .unwrap();
+    }
+
+    let page = backend
+        .post_revisions()
+        .find_by_post(post.id, PageRequest::new(Some(1), Some(2)))
+        .await
+        .unwrap();
+    let numbers: Vec<i32> = page.items.iter().map(|r| r.revision).collect();
+    assert_eq!(numbers, [3, 2]);
+    assert_eq!(page.total, 3);
+
+    let rest = backend
+        .post_revisions()
+        .find_by_post(post.id, PageRequest::new(Some(2), Some(2)))
+        .await
+        .unwrap();
+    assert_eq!(rest.items.len(), 1);
+    assert_eq!(rest.items[0].revision, 1);
+}
+
+pub async fn post_revision_record_requires_an_existing_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let orphan = post(author.id, "Orphan");
+
+    assert_invalid_reference(
+        backend
+            .post_revisions()
+            .record(&orphan, Some(author.id))
+            .await,
+        "post_id",
+    );
+}
+
+pub async fn post_revisions_outlive_their_editor_but_not_their_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let editor = create_user(backend, "editor").await;
+    let post = create_post(backend, author.id, "Post").await;
+    backend
+        .post_revisions()
+        .record(&post, Some(editor.id))
+        .await
+        .unwrap();
+
+    backend.users().delete(editor.id).await.unwrap();
+    let kept = backend.post_revisions().find(post.id, 1).await.unwrap();
+    assert_eq!(kept.editor_id, None);
+
+    backend.posts().delete(post.id).await.unwrap();
+    assert_not_found(backend.post_revisions().find(post.id, 1).await);
+    let page = backend
+        .post_revisions()
+        .find_by_post(post.id, PageRequest::new(Some(1), Some(10)))
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+}
+
+// Tags
+
+pub async fn tag_slugs_are_unique<B: Backend>(backend: &B) {
+    let rust = create_tag(backend, "Rust").await;
+    let go = create_tag(backend, "Go").await;
+
+    assert_conflict_on(backend.tags().create(tag("Rust")).await, "slug");
+    assert_conflict_on(
+        backend
+            .tags()
+            .update(
+                go.id,
+                Tag {
+                    slug: "rust".to_string(),
+                    ..go.clone()
+                },
+            )
+            .await,
+        "slug",
+    );
+
+    let renamed = backend
+        .tags()
+        .update(
+            rust.id,
+            Tag {
+                name: "Rustlang".to_string(),
+                slug: "rustlang".to_string(),
+                ..rust.clone()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.id, rust.id);
+    assert_eq!(
+        backend.tags().find_by_slug("rustlang").await.unwrap(),
+        renamed
+    );
+    assert_not_found(backend.tags().find_by_slug("rust").await);
+    assert_not_found(backend.tags().update(Uuid::new_v4(), go).await);
+}
+
+pub async fn tag_slug_series_covers_only_that_series<B: Backend>(backend: &B) {
+    let mut ids = Vec::new();
+    for slug in [
+        "news",
+        "news-2",
+        "news-4",
+        "news-02",
+        "newsroom",
+        "news-flash",
+    ] {
+        ids.push(create_tag(backend, slug).await.id);
+    }
+
+    let mut series = backend.tags().slug_series("news").await.unwrap();
+    series.sort();
+    assert_eq!(
+        series,
+        [
+            ("news".to_string(), ids[0]),
+            ("news-2".to_string(), ids[1]),
+            ("news-4".to_string(), ids[2]),
+        ]
+    );
+    assert_eq!(backend.tags().slug_series("none").await.unwrap(), []);
+}
+
+pub async fn tag_find_all_is_ordered_by_name<B: Backend>(backend: &B) {
+    for name in ["Charlie", "Alpha", "Bravo"] {
+        create_tag(backend, name).await;
+    }
+
+    let page = backend
+        .tags()
+        .find_all(PageRequest::new(Some(1), Some(2)))
+        .await
+        .unwrap();
+    let names: Vec<_> = page.items.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["Alpha", "Bravo"]);
+    assert_eq!(page.total, 3);
+
+    let found = backend
+        .tags()
+        .find_by_slugs(&["charlie".to_string(), "missing".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].name, "Charlie");
+}
+
+pub async fn tag_set_for_post_replaces_the_posts_tags<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let first = create_post(backend, author.id, "First").await;
+    let second = create_post(backend, author.id, "Second").await;
+    let rust = create_tag(backend, "Rust").await;
+    let web = create_tag(backend, "Web").await;
+
+    backend
+        .tags()
+        .set_for_post(first.id, &[web.id, rust.id])
+        .await
+        .unwrap();
+    backend
+        .tags()
+        .set_for_post(second.id, &[web.id])
+        .await
+        .unwrap();
+    backend
+        .tags()
+        .set_for_post(second.id, &[rust.id])
+        .await
+        .unwrap();
+
+    let tags = backend
+        .tags()
+        .find_by_posts(&[first.id, second.id, Uuid::new_v4()])
+        .await
+        .unwrap();
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[&first.id], [rust.clone(), web]);
+    assert_eq!(tags[&second.id], [rust]);
+
+    backend.tags().set_for_post(second.id, &[]).await.unwrap();
+    let tags = backend.tags().find_by_posts(&[second.id]).await.unwrap();
+    assert!(tags.is_empty());
+}
+
+pub async fn tag_set_for_post_requires_existing_post_and_tags<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let post = create_post(backend, author.id, "Post").await;
+    let rust = create_tag(backend, "Rust").await;
+
+    assert_invalid_reference(
+        backend
+            .tags()
+            .set_for_post(Uuid::new_v4(), &[rust.id])
+            .await,
+        "post_id",
+    );
+    assert_invalid_reference(
+        backend
+            .tags()
+            .set_for_post(post.id, &[Uuid::new_v4()])
+            .await,
+        "tag_id",
+    );
+}
+
+pub async fn tag_counts_and_filters_cover_only_tagged_posts<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let rust = create_tag(backend, "Rust").await;
+    let go = create_tag(backend, "Go").await;
+    let unused = create_tag(backend, "Unused").await;
+    let live = publish(backend, create_post(backend, author.id, "Live").await).await;
+    let draft = create_post(backend, author.id, "Draft").await;
+    create_post(backend, author.id, "Untagged").await;
+    backend
+        .tags()
+        .set_for_post(live.id, &[rust.id, go.id])
+        .await
+        .unwrap();
+    backend
+        .tags()
+        .set_for_post(draft.id, &[rust.id])
+        .await
+        .unwrap();
+
+    let counts = backend
+        .tags()
+        .count_published(&[rust.id, go.id, unused.id])
+        .await
+        .unwrap();
+    assert_eq!(counts.len(), 2);
+    assert_eq!((counts[&rust.id], counts[&go.id]), (1, 1));
+
+    let query = PostQuery {
+        filter: PostFilter {
+            tag_id: Some(rust.id),
+            ..Default::default()
+        },
+        sort: PostSortField::Title,
+        direction: SortDirection::Asc,
+        ..Default::default()
+    };
+    let page = backend.posts().find_all(&query).await.unwrap();
+    let titles: Vec<_> = page.items.iter().map(|p| p.title.as_str()).collect();
+    assert_eq!(titles, ["Draft", "Live"]);
+    assert_eq!(page.total, 2);
+}
+
+pub async fn tag_delete_takes_it_off_every_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let post = create_post(backend, author.id, "Post").await;
+    let gone = create_tag(backend, "Gone").await;
+    let stays = create_tag(backend, "Stays").await;
+    backend
+        .tags()
+        .set_for_post(post.id, &[gone.id, stays.id])
+        .await
+        .unwrap();
+
+    backend.tags().delete(gone.id).await.unwrap();
+
+    assert_not_found(backend.tags().find_by_slug("gone").await);
+    let tags = backend.tags().find_by_posts(&[post.id]).await.unwrap();
+    assert_eq!(tags[&post.id], [stays]);
+    assert_not_found(backend.tags().delete(gone.id).await);
+
+    // Deleting the post releases its tags rather than the other way round.
+    backend.posts().delete(post.id).await.unwrap();
+    backend.tags().find_by_slug("stays").await.unwrap();
+}
+
+// Categories
+
+pub async fn category_parent_must_exist<B: Backend>(backend: &B) {
+    let root = create_category(backend, "Root", None).await;
+    let child = create_category(backend, "Child", Some(root.id)).await;
+
+    assert_eq!(backend.categories().find(child.id).await.unwrap(), child);
+    assert_eq!(
+        backend.categories().find_by_slug("child").await.unwrap(),
+        child
+    );
+    assert_invalid_reference(
+        backend
+            .categories()
+            .create(category("Orphan", Some(Uuid::new_v4())))
+            .await,
+        "parent_id",
+    );
+    assert_invalid_reference(
+        backend
+            .categories()
+            .update(
+                root.id,
+                Category {
+                    parent_id: Some(Uuid::new_v4()),
+                    ..root.clone()
+                },
+            )
+            .await,
+        "parent_id",
+    );
+    assert_conflict_on(
+        backend.categories().create(category("Root", None)).await,
+        "slug",
+    );
+}
+
+pub async fn category_posts_must_name_an_existing_category<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+
+    assert_invalid_reference(
+        backend
+            .posts()
+            .create(Post {
+                category_id: Some(Uuid::new_v4()),
+                ..post(author.id, "Lost")
+            })
+            .await,
+        "category_id",
+    );
+}
+
+pub async fn category_delete_moves_children_up_and_clears_posts<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let root = create_category(backend, "Root", None).await;
+    let middle = create_category(backend, "Middle", Some(root.id)).await;
+    let leaf = create_category(backend, "Leaf", Some(middle.id)).await;
+    let filed = backend
+        .posts()
+        .create(Post {
+            category_id: Some(middle.id),
+            ..post(author.id, "Filed")
+        })
+        .await
+        .unwrap();
+
+    backend.categories().delete(middle.id).await.unwrap();
+
+    assert_not_found(backend.categories().find(middle.id).await);
+    assert_eq!(
+        backend.categories().find(leaf.id).await.unwrap().parent_id,
+        Some(root.id)
+    );
+    assert_eq!(
+        backend.posts().find(filed.id).await.unwrap().category_id,
+        None
+    );
+    assert_eq!(backend.categories().find_all().await.unwrap().len(), 2);
+    assert_not_found(backend.categories().delete(middle.id).await);
+}
+
+pub async fn category_counts_only_published_posts_filed_directly<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let root = create_category(backend, "Root", None).await;
+    let child = create_category(backend, "Child", Some(root.id)).await;
+    let filed = |title: &str, category_id| Post {
+        category_id: Some(category_id),
+        ..post(author.id, title)
+    };
+    let live = backend
+        .posts()
+        .create(filed("Live", child.id))
+        .await
+        .unwrap();
+    publish(backend, live).await;
+    backend
+        .posts()
+        .create(filed("Draft", child.id))
+        .await
+        .unwrap();
+
+    let counts = backend.categories().count_published().await.unwrap();
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[&child.id], 1);
+
+    let query = PostQuery {
+        filter: PostFilter {
+            category_ids: Some(vec![root.id, child.id]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(backend.posts().find_all(&query).await.unwrap().total, 2);
+}
+
+// Search
+
+fn search_for(q: &str, scope: SearchScope) -> SearchQuery {
+    SearchQuery {
+        terms: SearchTerms::parse(q).unwrap(),
+        scope,
+        author_id: None,
+        tag_id: None,
+        created_after: None,
+        created_before: None,
+        page: PageRequest::default(),
+    }
+}
+
+async fn create_published<B: Backend>(
+    backend: &B,
+    author_id: Uuid,
+    title: &str,
+    content: &str,
+) -> Post {
+    let post = backend
+        .posts()
+        .create(Post {
+            content: content.to_string(),
+            ..post(author_id, title)
+        })
+        .await
+        .unwrap();
+    publish(backend, post).await
+}
+
+fn hit_ids(page: &Page<SearchHit>) -> Vec<Uuid> {
+    page.items.iter().map(|hit| hit.id).collect()
+}
+
+pub async fn search_ranks_title_matches_first_and_skips_unpublished<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let titled = create_published(backend, author.id, "Kernel", "Notes on scheduling").await;
+    let body = create_published(backend, author.id, "Notes", "Building a kernel module").await;
+    backend
+        .posts()
+        .create(Post {
+            content: "Kernel draft".to_string(),
+            ..post(author.id, "Kernel")
+        })
+        .await
+        .unwrap();
+
+    let page = backend
+        .search()
+        .search(&search_for("kernel", SearchScope::Posts))
+        .await
+        .unwrap();
+    assert_eq!(page.total, 2);
+    assert_eq!(hit_ids(&page), [titled.id, body.id]);
+    assert!(page.items[0].rank > page.items[1].rank);
+    assert_eq!(page.items[1].post_id, body.id);
+    assert_eq!(page.items[1].post_slug, body.slug);
+    assert_eq!(page.items[1].post_title, "Notes");
+    assert!(
+        page.items[1].snippet.contains("<mark>kernel</mark>"),
+        "{}",
+        page.items[1].snippet
+    );
+}
+
+pub async fn search_supports_phrases_prefixes_and_exclusions<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let first = create_published(backend, author.id, "One", "kernel panic tuning").await;
+    let second = create_published(backend, author.id, "Two", "panic kernel").await;
+    let search = |q: &str| {
+        let query = search_for(q, SearchScope::Posts);
+        async move { hit_ids(&backend.search().search(&query).await.unwrap()) }
+    };
+
+    assert_eq!(search("\"kernel panic\"").await, [first.id]);
+    assert_eq!(search("tun*").await, [first.id]);
+    assert_eq!(search("kernel -tuning").await, [second.id]);
+    assert_eq!(search("\"panic kernel\" or tuning").await.len(), 2);
+    assert!(search("zebra").await.is_empty());
+}
+
+pub async fn search_filters_by_author_tag_and_date<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let other = create_user(backend, "other").await;
+    let tagged = create_published(backend, author.id, "Tagged", "kernel").await;
+    let untagged = create_published(backend, other.id, "Untagged", "kernel").await;
+    let rust = create_tag(backend, "Rust").await;
+    backend
+        .tags()
+        .set_for_post(tagged.id, &[rust.id])
+        .await
+        .unwrap();
+
+    let filtered = |query: SearchQuery| async move {
+        hit_ids(&backend.search().search(&query).await.unwrap())
+    };
+    let kernel = search_for("kernel", SearchScope::Posts);
+    assert_eq!(
+        filtered(SearchQuery {
+            author_id: Some(other.id),
+            ..kernel.clone()
+        })
+        .await,
+        [untagged.id]
+    );
+    assert_eq!(
+        filtered(SearchQuery {
+            tag_id: Some(rust.id),
+            ..kernel.clone()
+        })
+        .await,
+        [tagged.id]
+    );
+    assert!(
+        filtered(SearchQuery {
+            created_after: Some(untagged.created_at),
+            ..kernel.clone()
+        })
+        .await
+        .contains(&untagged.id)
+    );
+    assert!(
+        filtered(SearchQuery {
+            created_before: Some(tagged.created_at),
+            ..kernel
+        })
+        .await
+        .is_empty()
+    );
+}
+
+pub async fn search_comments_covers_only_approved_ones_on_published_posts<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let live = create_published(backend, author.id, "Live", "Nothing").await;
+    let draft = create_post(backend, author.id, "Draft").await;
+    let found = create_comment(backend, live.id, author.id, "Lovely pangolin photos").await;
+    create_comment(backend, draft.id, author.id, "Lovely pangolin photos").await;
+    create_held(backend, live.id, author.id, "Lovely pangolin photos").await;
+
+    let posts = backend
+        .search()
+        .search(&search_for("pangolin", SearchScope::Posts))
+        .await
+        .unwrap();
+    assert_eq!(posts.total, 0);
+
+    let page = backend
+        .search()
+        .search(&search_for("pangolin", SearchScope::Comments))
+        .await
+        .unwrap();
+    assert_eq!(hit_ids(&page), [found.id]);
+    let hit = &page.items[0];
+    assert_eq!(hit.post_id, live.id);
+    assert_eq!(hit.post_title, "Live");
+    assert_eq!(hit.author_id, author.id);
+    assert!(
+        hit.snippet.contains("<mark>pangolin</mark>"),
+        "{}",
+        hit.snippet
+    );
+}
+
+// Comments
+
+pub async fn comment_find_returns_the_requested_row<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    create_comment(backend, target.id, author.id, "first").await;
+    let second = create_comment(backend, target.id, author.id, "second").await;
+
+    let found = backend.comments().find(second.id).await.unwrap();
+    assert_eq!(found.id, second.id);
+    assert_eq!(found.content, "second");
+    assert_not_found(backend.comments().find(Uuid::new_v4()).await);
+}
+
+pub async fn comment_find_by_post_only_returns_that_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let first = create_post(backend, author.id, "First").await;
+    let second = create_post(backend, author.id, "Second").await;
+    create_comment(backend, first.id, author.id, "on first").await;
+    let mine = create_comment(backend, second.id, author.id, "on second").await;
+
+    let page = backend
+        .comments()
+        .find_by_post(second.id, &CommentQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, mine.id);
+
+    let empty = create_post(backend, author.id, "Empty").await;
+    let page = backend
+        .comments()
+        .find_by_post(empty.id, &CommentQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, 0);
+    assert!(page.items.is_empty());
+}
+
+pub async fn comment_create_requires_existing_post_and_author<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+
+    assert_invalid_reference(
+        backend
+            .comments()
+            .create(comment(Uuid::new_v4(), author.id, "lost"))
+            .await,
+        "post_id",
+    );
+    assert_invalid_reference(
+        backend
+            .comments()
+            .create(comment(target.id, Uuid::new_v4(), "anonymous"))
+            .await,
+        "author_id",
+    );
+}
+
+pub async fn comment_update_replaces_content_and_status_only<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let created = create_comment(backend, target.id, author.id, "typo").await;
+
+    let mut changes = created.clone();
+    changes.content = "fixed".to_string();
+    changes.content_html = Some("<p>fixed</p>".to_string());
+    changes.status = CommentStatus::Pending;
+    changes.post_id = Uuid::new_v4();
+    let updated = backend
+        .comments()
+        .update(created.id, changes)
+        .await
+        .unwrap();
+
+    assert_eq!(updated.content, "fixed");
+    assert_eq!(updated.content_html.as_deref(), Some("<p>fixed</p>"));
+    assert_eq!(updated.status, CommentStatus::Pending);
+    assert_eq!(updated.post_id, target.id);
+    assert!(updated.updated_at >= created.updated_at);
+    assert_not_found(
+        backend
+            .comments()
+            .update(Uuid::new_v4(), created.clone())
+            .await,
+    );
+}
+
+pub async fn comment_delete_removes_only_that_row<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let gone = create_comment(backend, target.id, author.id, "gone").await;
+    let stays = create_comment(backend, target.id, author.id, "stays").await;
+
+    backend.comments().delete(gone.id).await.unwrap();
+
+    assert_not_found(backend.comments().find(gone.id).await);
+    backend.comments().find(stays.id).await.unwrap();
+    assert_not_found(backend.comments().delete(gone.id).await);
+}
+
+async fn create_reply<B: Backend>(backend: &B, parent: &Comment, author_id: Uuid) -> Comment {
+    backend
+        .comments()
+        .create(Comment {
+            parent_id: Some(parent.id),
+            ..comment(parent.post_id, author_id, "reply")
+        })
+        .await
+        .unwrap()
+}
+
+pub async fn comment_replies_are_counted_and_need_an_existing_parent<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let parent = create_comment(backend, target.id, author.id, "parent").await;
+
+    let first = create_reply(backend, &parent, author.id).await;
+    let second = create_reply(backend, &parent, author.id).await;
+    assert_eq!(first.parent_id, Some(parent.id));
+    assert_eq!(first.reply_count, 0);
+    assert_eq!(first.deleted_at, None);
+    assert_eq!(
+        backend
+            .comments()
+            .find(parent.id)
+            .await
+            .unwrap()
+            .reply_count,
+        2
+    );
+
+    backend.comments().delete(second.id).await.unwrap();
+    assert_eq!(
+        backend
+            .comments()
+            .find(parent.id)
+            .await
+            .unwrap()
+            .reply_count,
+        1
+    );
+
+    let orphan = Comment {
+        parent_id: Some(Uuid::new_v4()),
+        ..comment(target.id, author.id, "orphan")
+    };
+    assert_invalid_reference(backend.comments().create(orphan).await, "parent_id");
+
+    let roots = backend
+        .comments()
+        .find_top_level(target.id, CommentStatus::Approved, PageRequest::default())
+        .await
+        .unwrap();
+    let ids: Vec<Uuid> = roots.items.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, [parent.id]);
+    let replies = backend
+        .comments()
+        .find_replies(&[parent.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    let ids: Vec<Uuid> = replies.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, [first.id]);
+}
+
+pub async fn comment_threads_page_top_level_comments_and_gather_their_replies<B: Backend>(
+    backend: &B,
+) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let other = create_post(backend, author.id, "Other").await;
+    let mut roots = Vec::new();
+    for n in 0..3 {
+        roots.push(create_comment(backend, target.id, author.id, &format!("root {}", n)).await);
+    }
+    create_comment(backend, other.id, author.id, "elsewhere").await;
+    let reply = create_reply(backend, &roots[0], author.id).await;
+    let nested = create_reply(backend, &reply, author.id).await;
+    let held = create_reply(backend, &roots[0], author.id).await;
+    create_reply(backend, &held, author.id).await;
+    create_reply(backend, &roots[2], author.id).await;
+    backend
+        .comments()
+        .set_status(&[held.id], CommentStatus::Pending)
+        .await
+        .unwrap();
+
+    let page = backend
+        .comments()
+        .find_top_level(
+            target.id,
+            CommentStatus::Approved,
+            PageRequest::new(Some(1), Some(2)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 3);
+    let ids: Vec<Uuid> = page.items.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, [roots[0].id, roots[1].id]);
+
+    // The held reply is left out, and with it the reply beneath it.
+    let mut replies: Vec<Uuid> = backend
+        .comments()
+        .find_replies(&ids, CommentStatus::Approved)
+        .await
+        .unwrap()
+        .iter()
+        .map(|comment| comment.id)
+        .collect();
+    replies.sort();
+    let mut expected = vec![reply.id, nested.id];
+    expected.sort();
+    assert_eq!(replies, expected);
+}
+
+pub async fn comment_delete_leaves_tombstones_only_while_they_have_replies<B: Backend>(
+    backend: &B,
+) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let root = create_comment(backend, target.id, author.id, "root").await;
+    let middle = create_reply(backend, &root, author.id).await;
+    let leaf = create_reply(backend, &middle, author.id).await;
+
+    backend.comments().delete(root.id).await.unwrap();
+    backend.comments().delete(middle.id).await.unwrap();
+    let tombstone = backend.comments().find(middle.id).await.unwrap();
+    assert!(tombstone.is_deleted());
+    assert_eq!(tombstone.content, "");
+    assert_eq!(tombstone.html(), "");
+    assert_eq!(tombstone.reply_count, 1);
+    assert!(backend.comments().find(root.id).await.unwrap().is_deleted());
+
+    // Both tombstones stood only for the leaf.
+    backend.comments().delete(leaf.id).await.unwrap();
+    for gone in [root.id, middle.id, leaf.id] {
+        assert_not_found(backend.comments().find(gone).await);
+    }
+}
+
+pub async fn comment_reply_counts_only_see_public_replies<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let root = create_comment(backend, target.id, author.id, "root").await;
+    let reply_count =
+        |id: Uuid| async move { backend.comments().find(id).await.unwrap().reply_count };
+
+    let approved = create_reply(backend, &root, author.id).await;
+    let held = backend
+        .comments()
+        .create(Comment {
+            parent_id: Some(root.id),
+            status: CommentStatus::Pending,
+            ..comment(target.id, author.id, "held")
+        })
+        .await
+        .unwrap();
+    assert_eq!(reply_count(root.id).await, 1);
+    backend
+        .comments()
+        .set_status(&[held.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    assert_eq!(reply_count(root.id).await, 2);
+    let mut edit = backend.comments().find(approved.id).await.unwrap();
+    edit.status = CommentStatus::Pending;
+    backend.comments().update(approved.id, edit).await.unwrap();
+    assert_eq!(reply_count(root.id).await, 1);
+
+    // A tombstone over a held reply stays, but counts for nothing and is
+    // left out of listings until the reply is approved.
+    let leaf = create_reply(backend, &held, author.id).await;
+    backend
+        .comments()
+        .set_status(&[leaf.id], CommentStatus::Pending)
+        .await
+        .unwrap();
+    backend.comments().delete(held.id).await.unwrap();
+    let tombstone = backend.comments().find(held.id).await.unwrap();
+    assert!(tombstone.is_deleted());
+    assert_eq!(tombstone.reply_count, 0);
+    assert_eq!(reply_count(root.id).await, 0);
+    let replies = backend
+        .comments()
+        .find_replies(&[root.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    assert!(replies.is_empty());
+    let listed = backend
+        .comments()
+        .find_by_post(target.id, &CommentQuery::default())
+        .await
+        .unwrap();
+    assert!(listed.items.iter().all(|comment| comment.id != held.id));
+
+    backend
+        .comments()
+        .set_status(&[leaf.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    assert_eq!(reply_count(held.id).await, 1);
+    assert_eq!(reply_count(root.id).await, 1);
+    let replies = backend
+        .comments()
+        .find_replies(&[root.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    assert_eq!(replies.len(), 2);
+
+    backend.comments().delete(leaf.id).await.unwrap();
+    assert_not_found(backend.comments().find(held.id).await);
+    assert_eq!(reply_count(root.id).await, 0);
+}
+
+pub async fn comment_replies_outlive_their_parents_author<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let leaving = create_user(backend, "leaving").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let parent = create_comment(backend, target.id, leaving.id, "parent").await;
+    let reply = create_reply(backend, &parent, author.id).await;
+    let own_reply = create_reply(backend, &reply, leaving.id).await;
+
+    backend.users().delete(leaving.id).await.unwrap();
+
+    assert_not_found(backend.comments().find(parent.id).await);
+    assert_not_found(backend.comments().find(own_reply.id).await);
+    let promoted = backend.comments().find(reply.id).await.unwrap();
+    assert_eq!(promoted.parent_id, None);
+    assert_eq!(promoted.reply_count, 0);
+}
+
+async fn create_held<B: Backend>(
+    backend: &B,
+    post_id: Uuid,
+    author_id: Uuid,
+    content: &str,
+) -> Comment {
+    backend
+        .comments()
+        .create(Comment {
+            status: CommentStatus::Pending,
+            ..comment(post_id, author_id, content)
+        })
+        .await
+        .unwrap()
+}
+
+fn queue(status: CommentStatus, post_id: Option<Uuid>) -> ModerationQueueQuery {
+    ModerationQueueQuery {
+        status,
+        post_id,
+        page: PageRequest::new(None, None),
+    }
+}
+
+pub async fn comment_status_filters_listings_and_the_queue<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let other = create_post(backend, author.id, "Other").await;
+    let approved = create_comment(backend, target.id, author.id, "approved").await;
+    let first = create_held(backend, target.id, author.id, "first").await;
+    let second = create_held(backend, other.id, author.id, "second").await;
+    assert_eq!(first.status, CommentStatus::Pending);
+    assert_eq!(
+        backend.comments().find(first.id).await.unwrap().status,
+        CommentStatus::Pending
+    );
+
+    let query = CommentQuery {
+        filter: CommentFilter {
+            status: Some(CommentStatus::Approved),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let page = backend
+        .comments()
+        .find_by_post(target.id, &query)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, approved.id);
+
+    let page = backend
+        .comments()
+        .find_queue(&queue(CommentStatus::Pending, None))
+        .await
+        .unwrap();
+    let ids: Vec<Uuid> = page.items.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, [first.id, second.id]);
+    assert_eq!(page.total, 2);
+
+    let page = backend
+        .comments()
+        .find_queue(&queue(CommentStatus::Pending, Some(other.id)))
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].id, second.id);
+}
+
+pub async fn comment_set_status_skips_tombstones_and_unknown_ids<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let held = create_held(backend, target.id, author.id, "held").await;
+    let parent = create_comment(backend, target.id, author.id, "parent").await;
+    create_reply(backend, &parent, author.id).await;
+    backend.comments().delete(parent.id).await.unwrap();
+
+    let updated = backend
+        .comments()
+        .set_status(&[held.id, parent.id, Uuid::new_v4()], CommentStatus::Spam)
+        .await
+        .unwrap();
+    let ids: Vec<Uuid> = updated.iter().map(|comment| comment.id).collect();
+    assert_eq!(ids, [held.id]);
+    assert_eq!(updated[0].status, CommentStatus::Spam);
+    assert_eq!(
+        backend.comments().find(parent.id).await.unwrap().status,
+        CommentStatus::Approved
+    );
+
+    let spam = backend
+        .comments()
+        .find_queue(&queue(CommentStatus::Spam, None))
+        .await
+        .unwrap();
+    assert_eq!(spam.total, 1);
+    let tombstones = backend
+        .comments()
+        .find_queue(&queue(CommentStatus::Approved, None))
+        .await
+        .unwrap();
+    assert!(
+        tombstones
+            .items
+            .iter()
+            .all(|comment| comment.id != parent.id)
+    );
+}
+
+pub async fn comment_has_approved_ignores_other_statuses<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let reader = create_user(backend, "reader").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let held = create_held(backend, target.id, reader.id, "held").await;
+    assert!(!backend.comments().has_approved(reader.id).await.unwrap());
+
+    backend
+        .comments()
+        .set_status(&[held.id], CommentStatus::Approved)
+        .await
+        .unwrap();
+    assert!(backend.comments().has_approved(reader.id).await.unwrap());
+    assert!(!backend.comments().has_approved(author.id).await.unwrap());
+}
+
+pub async fn comment_moderation_overrides_belong_to_an_existing_post<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+
+    let none = backend.comments().find_moderation(target.id).await.unwrap();
+    assert_eq!(
+        none,
+        ModerationOverrides {
+            post_id: target.id,
+            ..Default::default()
+        }
+    );
+    assert_not_found(backend.comments().find_moderation(Uuid::new_v4()).await);
+
+    let held = ModerationOverrides {
+        post_id: target.id,
+        require_approval: Some(true),
+        auto_approve_trusted: Some(false),
+        hold_first_time: None,
+    };
+    backend
+        .comments()
+        .save_moderation(held.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        backend.comments().find_moderation(target.id).await.unwrap(),
+        held
+    );
+
+    // Saving again replaces every override, clearing those left out.
+    let relaxed = ModerationOverrides {
+        post_id: target.id,
+        hold_first_time: Some(false),
+        ..Default::default()
+    };
+    let saved = backend
+        .comments()
+        .save_moderation(relaxed.clone())
+        .await
+        .unwrap();
+    assert_eq!(saved, relaxed);
+    assert_eq!(
+        backend.comments().find_moderation(target.id).await.unwrap(),
+        relaxed
+    );
+
+    let missing = ModerationOverrides {
+        post_id: Uuid::new_v4(),
+        ..Default::default()
+    };
+    assert_invalid_reference(backend.comments().save_moderation(missing).await, "post_id");
+
+    backend.posts().delete(target.id).await.unwrap();
+    assert_not_found(backend.comments().find_moderation(target.id).await);
+}
+
+pub async fn comment_stream_visits_every_row_once<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let target = create_post(backend, author.id, "Post").await;
+    let mut expected = Vec::new();
+    for n in 0..7 {
+        expected.push(
+            create_comment(backend, target.id, author.id, &format!("c{}", n))
+                .await
+                .id,
+        );
+    }
+
+    let mut seen = Vec::new();
+    let mut after = None;
+    loop {
+        let query = CommentStreamQuery {
+            direction: SortDirection::Asc,
+            after,
+            limit: 3,
+            ..Default::default()
+        };
+        let page = backend
+            .comments()
+            .stream_by_post(target.id, &query)
+            .await
+            .unwrap();
+        assert!(page.items.len() <= 3);
+        seen.extend(page.items.iter().map(|c| c.id));
+        match page.next {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+
+    let mut sorted_seen = seen.clone();
+    sorted_seen.sort();
+    sorted_seen.dedup();
+    assert_eq!(
+        sorted_seen.len(),
+        seen.len(),
+        "a comment was returned twice"
+    );
+    expected.sort();
+    assert_eq!(sorted_seen, expected);
+}
+
+// Unit of work
+
+pub async fn unit_of_work_commits_on_success<B: Backend>(backend: &B) {
+    let from = create_user(backend, "leaving").await;
+    let to = create_user(backend, "staying").await;
+    let kept = create_post(backend, from.id, "Kept").await;
+
+    let moved = backend
+        .unit_of_work()
+        .transaction(|tx| async move {
+            let moved = tx.posts().reassign_author(from.id, to.id).await?;
+            tx.users().delete(from.id).await?;
+            Ok(moved)
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(moved, 1);
+    assert_not_found(backend.users().find(from.id).await);
+    assert_eq!(
+        backend.posts().find(kept.id).await.unwrap().author_id,
+        to.id
+    );
+}
+
+pub async fn unit_of_work_rolls_back_on_error<B: Backend>(backend: &B) {
+    let from = create_user(backend, "leaving").await;
+    let to = create_user(backend, "staying").await;
+    let kept = create_post(backend, from.id, "Kept").await;
+
+    let result: Result<(), ApiError> = backend
+        .unit_of_work()
+        .transaction(|tx| async move {
+            tx.posts().reassign_author(from.id, to.id).await?;
+            tx.users().create(user("newcomer")).await?;
+            Err(ApiError::Conflict {
+                field: "anything".to_string(),
+            })
+        })
+        .await;
+
+    assert_conflict_on(result, "anything");
+    assert_eq!(
+        backend.posts().find(kept.id).await.unwrap().author_id,
+        from.id
+    );
+    assert_not_found(backend.users().find_by_email("newcomer@example.com").await);
+}
+
+pub async fn unit_of_work_rolls_back_after_a_failed_statement<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let post = create_post(backend, author.id, "Survives").await;
+
+    let result = backend
+        .unit_of_work()
+        .transaction(|tx| async move {
+            tx.posts().delete(post.id).await?;
+            tx.users().create(user("author")).await
+        })
+        .await;
+
+    assert_conflict_on(result, "username");
+    backend.posts().find(post.id).await.unwrap();
+}
+
+pub async fn unit_of_work_sees_its_own_writes<B: Backend>(backend: &B) {
+    let created = backend
+        .unit_of_work()
+        .transaction(|tx| async move {
+            let created = tx.users().create(user("visible")).await?;
+            let found = tx.users().find(created.id).await?;
+            assert_eq!(found.username, "visible");
+            Ok(created)
+        })
+        .await
+        .unwrap();
+
+    backend.users().find(created.id).await.unwrap();
+}
+
+pub async fn unit_of_work_rolls_back_when_abandoned<B: Backend>(backend: &B) {
+    let abandoned = backend.unit_of_work().transaction(|tx| async move {
+        tx.users().create(user("abandoned")).await?;
+        std::future::pending::<Result<(), ApiError>>().await
+    });
+
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), abandoned)
+            .await
+            .is_err()
+    );
+    assert_not_found(backend.users().find_by_email("abandoned@example.com").await);
+}
+
+pub async fn unit_of_work_rollback_keeps_concurrent_commits<B: Backend>(backend: &B) {
+    let failing = backend.unit_of_work().transaction(|tx| async move {
+        tx.users().create(user("failing")).await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Err::<(), _>(ApiError::Conflict {
+            field: "anything".to_string(),
+        })
+    });
+    let committing = async {
+        // Start once the failing transaction is already open.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        backend
+            .unit_of_work()
+            .transaction(|tx| async move { tx.users().create(user("committed")).await })
+            .await
+    };
+
+    let (failed, committed) = tokio::join!(failing, committing);
+    assert_conflict_on(failed, "anything");
+    let committed = committed.unwrap();
+    backend.users().find(committed.id).await.unwrap();
+    assert_not_found(backend.users().find_by_email("failing@example.com").await);
+}
+
+pub async fn unit_of_work_find_for_update_waits_for_the_holder<B: Backend>(backend: &B) {
+    let author = create_user(backend, "author").await;
+    let created = create_post(backend, author.id, "Draft").await;
+
+    let holding = backend.unit_of_work().transaction(|tx| async move {
+        let mut post = tx.posts().find_for_update(created.id).await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        post.title = "Renamed".to_string();
+        tx.posts().update(post.id, post).await
+    });
+    let waiting = async {
+        // Start once the holding transaction has the post.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        backend
+            .unit_of_work()
+            .transaction(|tx| async move {
+                let mut post = tx.posts().find_for_update(created.id).await?;
+                post.content = "Edited".to_string();
+                tx.posts().update(post.id, post).await
+            })
+            .await
+    };
+
+    let (held, waited) = tokio::join!(holding, waiting);
+    held.unwrap();
+    let waited = waited.unwrap();
+    assert_eq!(waited.title, "Renamed");
+    assert_eq!(waited.content, "Edited");
+    assert_not_found(
+        backend
+            .unit_of_work()
+            .transaction(|tx| async move { tx.posts().find_for_update(Uuid::new_v4()).await })
+            .await,
+    );
+}
+
+pub async fn unit_of_work_find_all_categories_for_update_waits_for_the_holder<B: Backend>(
+    backend: &B,
+) {
+    let tech = create_category(backend, "Tech", None).await;
+    let news = create_category(backend, "News", None).await;
+
+    let mut moved = tech.clone();
+    moved.parent_id = Some(news.id);
+    let holding = backend.unit_of_work().transaction(|tx| async move {
+        tx.categories().find_all_for_update().await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tx.categories().update(moved.id, moved).await
+    });
+    let waiting = async {
+        // Start once the holding transaction has the categories.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        backend
+            .unit_of_work()
+            .transaction(|tx| async move { tx.categories().find_all_for_update().await })
+            .await
+    };
+
+    let (held, seen) = tokio::join!(holding, waiting);
+    held.unwrap();
+    let seen = seen.unwrap();
+    let tech = seen.iter().find(|category| category.id == tech.id).unwrap();
+    assert_eq!(tech.parent_id, Some(news.id));
+}
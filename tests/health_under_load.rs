@@ -0,0 +1,300 @@
+//! Saturates the database executor and checks the runtime stays responsive.
+//!
+//! The pool points at a port nothing listens on, so every checkout sits in
+//! r2d2 until its connection timeout. With Diesel calls on the runtime's own
+//! workers that would wedge both of them; on the blocking executor `/health`
+//! must still answer promptly and excess database work must be shed with 503.
+//! Readiness, which does need the database, must report the outage, and do
+//! so within its own probe timeout rather than queueing behind the flood.
+//! Against a live database, overlapping readiness probes must all pass.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    response::Response,
+};
+use blog::{
+    application::routes::{create_routes, health_routes::health_router},
+    domain::models::comment::ModerationSettings,
+    domain::services::{
+        auth_service::{AuthConfig, AuthServiceImpl, TokenCodec},
+        comment_service::CommentServiceImpl,
+        health_service::HealthServiceImpl,
+        post_service::PostServiceImpl,
+        search_service::SearchServiceImpl,
+        taxonomy_service::TaxonomyServiceImpl,
+        user_service::UserServiceImpl,
+    },
+    infrastructure::{
+        database::{connection::init_pool, executor::DbExecutor, migrations},
+        repositories::{
+            category_repository_impl::CategoryRepositoryImpl,
+            comment_repository_impl::CommentRepositoryImpl,
+            health_repository_impl::HealthRepositoryImpl, post_repository_impl::PostRepositoryImpl,
+            post_revision_repository_impl::PostRevisionRepositoryImpl,
+            refresh_token_repository_impl::RefreshTokenRepositoryImpl,
+            search_repository_impl::SearchRepositoryImpl, tag_repository_impl::TagRepositoryImpl,
+            unit_of_work_impl::UnitOfWorkImpl, user_repository_impl::UserRepositoryImpl,
+        },
+    },
+    shared::{
+        config::{Config, DatabaseConfig},
+        shutdown::Shutdown,
+    },
+};
+use diesel::{
+    Connection, RunQueryDsl,
+    pg::PgConnection,
+    r2d2::{ConnectionManager, Pool},
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+const POOL_SIZE: u32 = 2;
+const CHECKOUT_TIMEOUT: Duration = Duration::from_secs(2);
+const QUEUE_TIMEOUT: Duration = Duration::from_millis(200);
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+fn saturated_app() -> Router {
+    let manager = ConnectionManager::<PgConnection>::new("postgres://postgres@127.0.0.1:1/blog");
+    let pool = Pool::builder()
+        .max_size(POOL_SIZE)
+        .min_idle(Some(0))
+        .connection_timeout(CHECKOUT_TIMEOUT)
+        .build_unchecked(manager);
+    app_on(
+        DbExecutor::new(pool, POOL_SIZE as usize, QUEUE_TIMEOUT).with_probe_timeout(PROBE_TIMEOUT),
+    )
+}
+
+fn app_on(db: DbExecutor) -> Router {
+    let mut config = Config::default();
+    config.auth.jwt_secret = "test-secret".to_string();
+
+    let user_repository = Arc::new(UserRepositoryImpl::new(db.clone()));
+    let tag_repository = Arc::new(TagRepositoryImpl::new(db.clone()));
+    let post_repository = Arc::new(PostRepositoryImpl::new(db.clone()));
+    let post_service = Arc::new(PostServiceImpl::new(
+        Arc::clone(&post_repository),
+        Arc::new(PostRevisionRepositoryImpl::new(db.clone())),
+        Arc::clone(&tag_repository),
+        Arc::new(UnitOfWorkImpl::new(db.clone())),
+    ));
+    let search_service = Arc::new(SearchServiceImpl::new(
+        Arc::new(SearchRepositoryImpl::new(db.clone())),
+        Arc::clone(&tag_repository),
+    ));
+    let taxonomy_service = Arc::new(TaxonomyServiceImpl::new(
+        tag_repository,
+        Arc::new(CategoryRepositoryImpl::new(db.clone())),
+        Arc::new(UnitOfWorkImpl::new(db.clone())),
+    ));
+    let comment_service = Arc::new(CommentServiceImpl::new(
+        Arc::new(CommentRepositoryImpl::new(db.clone())),
+        post_repository,
+        ModerationSettings::from(&config.moderation),
+    ));
+    let user_service = Arc::new(UserServiceImpl::new(
+        Arc::clone(&user_repository),
+        Arc::new(UnitOfWorkImpl::new(db.clone())),
+    ));
+    let auth_service = Arc::new(AuthServiceImpl::new(
+        user_repository,
+        Arc::new(RefreshTokenRepositoryImpl::new(db.clone())),
+        TokenCodec::new(&AuthConfig::from(&config.auth)),
+    ));
+    let health_service = Arc::new(HealthServiceImpl::new(
+        Arc::new(HealthRepositoryImpl::new(db)),
+        Shutdown::new(),
+    ));
+
+    Router::new()
+        .nest(
+            "/api",
+            create_routes(
+                comment_service,
+                post_service,
+                taxonomy_service,
+                search_service,
+                user_service,
+                auth_service,
+                &config,
+            ),
+        )
+        .nest("/health", health_router(health_service))
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+async fn database_status(response: Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    body["checks"]["database"]["status"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn health_stays_responsive_while_pool_is_saturated() {
+    let app = saturated_app();
+
+    let in_flight: Vec<_> = (0..16)
+        .map(|_| {
+            let app = app.clone();
+            tokio::spawn(async move { app.oneshot(get("/api/posts")).await.unwrap().status() })
+        })
+        .collect();
+
+    // Give the flood time to occupy every executor slot.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    let response = app.clone().oneshot(get("/health/live")).await.unwrap();
+    let elapsed = started.elapsed();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        elapsed < Duration::from_millis(250),
+        "/health/live took {:?} while the pool was saturated",
+        elapsed
+    );
+
+    let started = Instant::now();
+    let response = app.clone().oneshot(get("/health/ready")).await.unwrap();
+    let elapsed = started.elapsed();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(database_status(response).await, "down");
+    assert!(
+        elapsed < PROBE_TIMEOUT + Duration::from_millis(200),
+        "/health/ready took {:?} while the pool was saturated",
+        elapsed
+    );
+
+    let mut shed = 0;
+    for handle in in_flight {
+        let status = handle.await.unwrap();
+        assert_ne!(status, StatusCode::OK);
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            shed += 1;
+        }
+    }
+
+    // Only POOL_SIZE requests may hold a slot; the rest are turned away
+    // once they have queued for QUEUE_TIMEOUT.
+    assert!(
+        shed >= 16 - POOL_SIZE as usize,
+        "expected excess requests to be shed, got {} 503s",
+        shed
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn readiness_reports_a_busy_pool_as_saturated_not_down() {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+    let config = DatabaseConfig {
+        url,
+        max_size: POOL_SIZE,
+        min_idle: Some(0),
+        queue_timeout_ms: Some(QUEUE_TIMEOUT.as_millis() as u64),
+        probe_timeout_ms: PROBE_TIMEOUT.as_millis() as u64,
+        ..Default::default()
+    };
+    let db = DbExecutor::from_config(init_pool(&config).unwrap(), &config);
+    let app = app_on(db.clone());
+
+    // Open transactions pin every executor slot until they are dropped.
+    let mut held = Vec::new();
+    for _ in 0..POOL_SIZE {
+        held.push(db.begin().await.unwrap());
+    }
+    let response = app.clone().oneshot(get("/api/posts")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // The scratch admin database has no migrations applied, so only the
+    // database check itself is meaningful here.
+    let started = Instant::now();
+    let response = app.clone().oneshot(get("/health/ready")).await.unwrap();
+    assert!(started.elapsed() < PROBE_TIMEOUT);
+    assert_eq!(database_status(response).await, "saturated");
+
+    drop(held);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore = "requires TEST_DATABASE_URL"]
+async fn overlapping_readiness_probes_all_pass() {
+    let admin_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+    let database = ScratchDatabase::create(&admin_url);
+    let config = DatabaseConfig {
+        url: database.url.clone(),
+        max_size: POOL_SIZE,
+        min_idle: Some(0),
+        ..Default::default()
+    };
+    let pool = init_pool(&config).unwrap();
+    migrations::run_pending(&mut pool.get().unwrap()).unwrap();
+    let app = app_on(DbExecutor::from_config(pool, &config));
+
+    let probes: Vec<_> = (0..8)
+        .map(|_| {
+            let app = app.clone();
+            tokio::spawn(async move { app.oneshot(get("/health/ready")).await.unwrap() })
+        })
+        .collect();
+    for probe in probes {
+        let response = probe.await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(database_status(response).await, "up");
+    }
+}
+
+/// A freshly created database on the server behind `TEST_DATABASE_URL`,
+/// dropped afterwards.
+struct ScratchDatabase {
+    admin_url: String,
+    name: String,
+    url: String,
+}
+
+impl ScratchDatabase {
+    fn create(admin_url: &str) -> Self {
+        let name = format!("health_{}", uuid::Uuid::new_v4().simple());
+        let mut conn =
+            PgConnection::establish(admin_url).expect("failed to connect to TEST_DATABASE_URL");
+        diesel::sql_query(format!("CREATE DATABASE {}", name))
+            .execute(&mut conn)
+            .expect("failed to create scratch database");
+
+        let (base, _) = admin_url
+            .rsplit_once('/')
+            .expect("TEST_DATABASE_URL must name a database");
+        Self {
+            admin_url: admin_url.to_string(),
+            url: format!("{}/{}", base, name),
+            name,
+        }
+    }
+}
+
+impl Drop for ScratchDatabase {
+    fn drop(&mut self) {
+        if let Ok(mut conn) = PgConnection::establish(&self.admin_url) {
+            let _ = diesel::sql_query(format!(
+                "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+                self.name
+            ))
+            .execute(&mut conn);
+        }
+    }
+}
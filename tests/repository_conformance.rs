@@ -0,0 +1,338 @@
+//! Runs the shared repository conformance checks against every backend.
+//!
+//! The in-memory backend always runs. The Diesel backend needs a Postgres
+//! server it may create databases on; point `TEST_DATABASE_URL` at one (any
+//! existing database works, e.g. `postgres://postgres@127.0.0.1/postgres`).
+//! Each check gets its own freshly migrated database, dropped afterwards.
+//! The Diesel checks are ignored by default; run them with
+//! `cargo test -- --include-ignored`, which fails if the variable is unset.
+
+mod conformance;
+
+use conformance::Backend;
+
+macro_rules! conformance_tests {
+    ($backend:expr $(, $attr:meta)?) => {
+        conformance_tests!(
+            @cases [$backend] [$($attr)?];
+            user_find_returns_the_requested_row,
+            user_find_missing_is_not_found,
+            user_find_by_email_matches_the_address,
+            user_create_assigns_id_and_timestamps,
+            user_username_and_email_are_unique,
+            user_update_replaces_fields_and_bumps_updated_at,
+            user_update_keeps_uniqueness,
+            user_update_missing_is_not_found,
+            user_delete_cascades_to_posts_and_comments,
+            user_delete_missing_is_not_found,
+            user_find_all_filters_and_paginates,
+            post_find_returns_the_requested_row,
+            post_find_missing_is_not_found,
+            post_create_requires_an_existing_author,
+            post_update_replaces_content_but_not_author,
+            post_update_missing_is_not_found,
+            post_delete_cascades_to_comments,
+            post_find_all_filters_sorts_and_paginates,
+            post_publish_due_publishes_only_due_scheduled_posts,
+            post_reassign_author_moves_only_that_authors_posts,
+            post_reassign_author_requires_an_existing_target,
+            post_find_by_slug_follows_former_slugs,
+            post_slug_series_covers_current_and_former_slugs,
+            post_renaming_back_reclaims_a_former_slug,
+            post_slugs_are_unique,
+            post_delete_releases_its_slugs,
+            post_revisions_are_numbered_per_post,
+            post_revisions_are_listed_newest_first,
+            post_revision_record_requires_an_existing_post,
+            post_revisions_outlive_their_editor_but_not_their_post,
+            tag_slugs_are_unique,
+            tag_slug_series_covers_only_that_series,
+            tag_find_all_is_ordered_by_name,
+            tag_set_for_post_replaces_the_posts_tags,
+            tag_set_for_post_requires_existing_post_and_tags,
+            tag_counts_and_filters_cover_only_tagged_posts,
+            tag_delete_takes_it_off_every_post,
+            category_parent_must_exist,
+            category_posts_must_name_an_existing_category,
+            category_delete_moves_children_up_and_clears_posts,
+            category_counts_only_published_posts_filed_directly,
+            search_ranks_title_matches_first_and_skips_unpublished,
+            search_supports_phrases_prefixes_and_exclusions,
+            search_filters_by_author_tag_and_date,
+            search_comments_covers_only_approved_ones_on_published_posts,
+            comment_find_returns_the_requested_row,
+            comment_find_by_post_only_returns_that_post,
+            comment_create_requires_existing_post_and_author,
+            comment_update_replaces_content_and_status_only,
+            comment_delete_removes_only_that_row,
+            comment_stream_visits_every_row_once,
+            comment_replies_are_counted_and_need_an_existing_parent,
+            comment_threads_page_top_level_comments_and_gather_their_replies,
+            comment_delete_leaves_tombstones_only_while_they_have_replies,
+            comment_reply_counts_only_see_public_replies,
+            comment_replies_outlive_their_parents_author,
+            comment_status_filters_listings_and_the_queue,
+            comment_set_status_skips_tombstones_and_unknown_ids,
+            comment_has_approved_ignores_other_statuses,
+            comment_moderation_overrides_belong_to_an_existing_post,
+            unit_of_work_commits_on_success,
+            unit_of_work_rolls_back_on_error,
+            unit_of_work_rolls_back_after_a_failed_statement,
+            unit_of_work_sees_its_own_writes,
+            unit_of_work_rolls_back_when_abandoned,
+            unit_of_work_rollback_keeps_concurrent_commits,
+            unit_of_work_find_for_update_waits_for_the_holder,
+            unit_of_work_find_all_categories_for_update_waits_for_the_holder,
+        );
+    };
+    (@cases [$backend:expr] []; $($case:ident),+ $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $case() {
+                crate::conformance::$case(&$backend).await;
+            }
+        )+
+    };
+    (@cases [$backend:expr] [$attr:meta]; $($case:ident),+ $(,)?) => {
+        $(
+            #[tokio::test]
+            #[$attr]
+            async fn $case() {
+                crate::conformance::$case(&$backend).await;
+            }
+        )+
+    };
+}
+
+mod in_memory {
+    use blog::infrastructure::repositories::in_memory::{
+        InMemoryStore, category_repository::InMemoryCategoryRepository,
+        comment_repository::InMemoryCommentRepository, post_repository::InMemoryPostRepository,
+        post_revision_repository::InMemoryPostRevisionRepository,
+        search_repository::InMemorySearchRepository, tag_repository::InMemoryTagRepository,
+        unit_of_work::InMemoryUnitOfWork, user_repository::InMemoryUserRepository,
+    };
+
+    use super::Backend;
+
+    pub struct InMemory {
+        users: InMemoryUserRepository,
+        posts: InMemoryPostRepository,
+        post_revisions: InMemoryPostRevisionRepository,
+        tags: InMemoryTagRepository,
+        categories: InMemoryCategoryRepository,
+        comments: InMemoryCommentRepository,
+        search: InMemorySearchRepository,
+        unit_of_work: InMemoryUnitOfWork,
+    }
+
+    impl InMemory {
+        pub fn new() -> Self {
+            let store = InMemoryStore::new();
+            Self {
+                users: InMemoryUserRepository::new(store.clone()),
+                posts: InMemoryPostRepository::new(store.clone()),
+                post_revisions: InMemoryPostRevisionRepository::new(store.clone()),
+                tags: InMemoryTagRepository::new(store.clone()),
+                categories: InMemoryCategoryRepository::new(store.clone()),
+                comments: InMemoryCommentRepository::new(store.clone()),
+                search: InMemorySearchRepository::new(store.clone()),
+                unit_of_work: InMemoryUnitOfWork::new(store),
+            }
+        }
+    }
+
+    impl Backend for InMemory {
+        type Users = InMemoryUserRepository;
+        type Posts = InMemoryPostRepository;
+        type PostRevisions = InMemoryPostRevisionRepository;
+        type Tags = InMemoryTagRepository;
+        type Categories = InMemoryCategoryRepository;
+        type Comments = InMemoryCommentRepository;
+        type Search = InMemorySearchRepository;
+        type Work = InMemoryUnitOfWork;
+
+        fn users(&self) -> &Self::Users {
+            &self.users
+        }
+
+        fn posts(&self) -> &Self::Posts {
+            &self.posts
+        }
+
+        fn post_revisions(&self) -> &Self::PostRevisions {
+            &self.post_revisions
+        }
+
+        fn tags(&self) -> &Self::Tags {
+            &self.tags
+        }
+
+        fn categories(&self) -> &Self::Categories {
+            &self.categories
+        }
+
+        fn comments(&self) -> &Self::Comments {
+            &self.comments
+        }
+
+        fn search(&self) -> &Self::Search {
+            &self.search
+        }
+
+        fn unit_of_work(&self) -> &Self::Work {
+            &self.unit_of_work
+        }
+    }
+
+    conformance_tests!(InMemory::new());
+}
+
+mod diesel_postgres {
+    use std::time::Duration;
+
+    use blog::infrastructure::{
+        database::{connection::init_pool, executor::DbExecutor, migrations},
+        repositories::{
+            category_repository_impl::CategoryRepositoryImpl,
+            comment_repository_impl::CommentRepositoryImpl,
+            post_repository_impl::PostRepositoryImpl,
+            post_revision_repository_impl::PostRevisionRepositoryImpl,
+            search_repository_impl::SearchRepositoryImpl, tag_repository_impl::TagRepositoryImpl,
+            unit_of_work_impl::UnitOfWorkImpl, user_repository_impl::UserRepositoryImpl,
+        },
+    };
+    use blog::shared::config::DatabaseConfig;
+    use diesel::{Connection, RunQueryDsl, pg::PgConnection};
+
+    use super::Backend;
+
+    pub struct DieselPostgres {
+        users: UserRepositoryImpl,
+        posts: PostRepositoryImpl,
+        post_revisions: PostRevisionRepositoryImpl,
+        tags: TagRepositoryImpl,
+        categories: CategoryRepositoryImpl,
+        comments: CommentRepositoryImpl,
+        search: SearchRepositoryImpl,
+        unit_of_work: UnitOfWorkImpl,
+        // Declared last so the repositories, and with them the pool, are
+        // dropped before the database is.
+        _database: ScratchDatabase,
+    }
+
+    impl DieselPostgres {
+        pub fn new() -> Self {
+            let admin_url = std::env::var("TEST_DATABASE_URL")
+                .expect("TEST_DATABASE_URL must point at a Postgres server for the Diesel checks");
+
+            let database = ScratchDatabase::create(&admin_url);
+            let config = DatabaseConfig {
+                url: database.url.clone(),
+                max_size: 2,
+                min_idle: Some(0),
+                ..Default::default()
+            };
+            let pool = init_pool(&config).expect("failed to connect to scratch database");
+            migrations::run_pending(&mut pool.get().unwrap())
+                .expect("failed to migrate scratch database");
+            let db = DbExecutor::new(pool, 2, Duration::from_secs(5));
+
+            Self {
+                users: UserRepositoryImpl::new(db.clone()),
+                posts: PostRepositoryImpl::new(db.clone()),
+                post_revisions: PostRevisionRepositoryImpl::new(db.clone()),
+                tags: TagRepositoryImpl::new(db.clone()),
+                categories: CategoryRepositoryImpl::new(db.clone()),
+                comments: CommentRepositoryImpl::new(db.clone()),
+                search: SearchRepositoryImpl::new(db.clone()),
+                unit_of_work: UnitOfWorkImpl::new(db),
+                _database: database,
+            }
+        }
+    }
+
+    impl Backend for DieselPostgres {
+        type Users = UserRepositoryImpl;
+        type Posts = PostRepositoryImpl;
+        type PostRevisions = PostRevisionRepositoryImpl;
+        type Tags = TagRepositoryImpl;
+        type Categories = CategoryRepositoryImpl;
+        type Comments = CommentRepositoryImpl;
+        type Search = SearchRepositoryImpl;
+        type Work = UnitOfWorkImpl;
+
+        fn users(&self) -> &Self::Users {
+            &self.users
+        }
+
+        fn posts(&self) -> &Self::Posts {
+            &self.posts
+        }
+
+        fn post_revisions(&self) -> &Self::PostRevisions {
+            &self.post_revisions
+        }
+
+        fn tags(&self) -> &Self::Tags {
+            &self.tags
+        }
+
+        fn categories(&self) -> &Self::Categories {
+            &self.categories
+        }
+
+        fn comments(&self) -> &Self::Comments {
+            &self.comments
+        }
+
+        fn search(&self) -> &Self::Search {
+            &self.search
+        }
+
+        fn unit_of_work(&self) -> &Self::Work {
+            &self.unit_of_work
+        }
+    }
+
+    /// A throwaway database on the server behind `TEST_DATABASE_URL`.
+    struct ScratchDatabase {
+        admin_url: String,
+        name: String,
+        url: String,
+    }
+
+    impl ScratchDatabase {
+        fn create(admin_url: &str) -> Self {
+            let name = format!("conformance_{}", uuid::Uuid::new_v4().simple());
+            let mut conn =
+                PgConnection::establish(admin_url).expect("failed to connect to TEST_DATABASE_URL");
+            diesel::sql_query(format!("CREATE DATABASE {}", name))
+                .execute(&mut conn)
+                .expect("failed to create scratch database");
+
+            let (base, _) = admin_url
+                .rsplit_once('/')
+                .expect("TEST_DATABASE_URL must name a database");
+            Self {
+                admin_url: admin_url.to_string(),
+                url: format!("{}/{}", base, name),
+                name,
+            }
+        }
+    }
+
+    impl Drop for ScratchDatabase {
+        fn drop(&mut self) {
+            if let Ok(mut conn) = PgConnection::establish(&self.admin_url) {
+                let _ = diesel::sql_query(format!(
+                    "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+                    self.name
+                ))
+                .execute(&mut conn);
+            }
+        }
+    }
+
+    conformance_tests!(DieselPostgres::new(), ignore = "requires TEST_DATABASE_URL");
+}